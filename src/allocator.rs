@@ -0,0 +1,267 @@
+//! Allocator support for the gap buffers
+//!
+//! With the `allocator_api` feature, [`Allocator`] and [`Global`] are re-exported from the
+//! standard library and any allocator can be provided to the `new_in` and `with_grower_in`
+//! constructors. Without it, a sealed stand-in trait is provided which is only implemented for
+//! [`Global`], so the default type parameters keep working on a stable compiler.
+
+use alloc::{boxed::Box, collections::TryReserveError, vec::Vec};
+use core::{mem::MaybeUninit, ptr::NonNull};
+
+#[cfg(feature = "allocator_api")]
+pub use alloc::alloc::{Allocator, Global};
+
+#[cfg(not(feature = "allocator_api"))]
+pub use stable::{Allocator, Global};
+
+#[cfg(not(feature = "allocator_api"))]
+mod stable {
+    /// Stand-in for the unstable `Allocator` trait
+    ///
+    /// This trait is sealed and is only implemented for [`Global`]. Enable the `allocator_api`
+    /// feature on a nightly compiler to use other allocators.
+    pub trait Allocator: sealed::Sealed {}
+
+    /// The global memory allocator
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct Global;
+
+    impl Allocator for Global {}
+
+    mod sealed {
+        pub trait Sealed {}
+        impl Sealed for super::Global {}
+    }
+}
+
+/// Allocates an uninitialized buffer for exactly `len` T's
+#[inline]
+pub(crate) fn allocate<T, A: Allocator>(len: usize, alloc: &A) -> NonNull<[MaybeUninit<T>]> {
+    #[cfg(feature = "allocator_api")]
+    let ptr = Box::into_raw_with_allocator(Box::<[T], &A>::new_uninit_slice_in(len, alloc)).0;
+    #[cfg(not(feature = "allocator_api"))]
+    let ptr = {
+        let _ = alloc;
+        Box::into_raw(Box::<[T]>::new_uninit_slice(len))
+    };
+
+    // SAFETY: pointers returned by a box are never null
+    unsafe { NonNull::new_unchecked(ptr) }
+}
+
+/// Allocates an uninitialized buffer for exactly `len` T's
+///
+/// Same as [`allocate`] but if `fallible` is true, an allocation failure returns an error instead
+/// of aborting.
+#[inline]
+pub(crate) fn try_allocate<T, A: Allocator>(
+    len: usize,
+    alloc: &A,
+    fallible: bool,
+) -> Result<NonNull<T>, TryReserveError> {
+    // SAFETY: a dangling pointer without any capacity is a valid empty vec
+    let mut v = unsafe { into_vec(NonNull::dangling(), 0, alloc) };
+    if fallible {
+        v.try_reserve_exact(len)?;
+    } else {
+        v.reserve_exact(len);
+    }
+
+    // SAFETY: we are storing uninits anyway
+    unsafe { v.set_len(len) };
+    Ok(into_raw(v))
+}
+
+/// Grows a buffer of `len` T's by exactly `additional` T's
+///
+/// The allocator extends the buffer in place when it can, otherwise the buffer is copied to the
+/// new allocation once.
+///
+/// If `fallible` is false, an allocation failure aborts instead of returning an error. The buffer
+/// is left untouched if an error is returned.
+///
+/// # Safety
+/// The buffer must have been allocated by this module with the same allocator and with a length
+/// of `len`.
+#[inline]
+pub(crate) unsafe fn grow<T, A: Allocator>(
+    ptr: NonNull<T>,
+    len: usize,
+    additional: usize,
+    alloc: &A,
+    fallible: bool,
+) -> Result<NonNull<T>, TryReserveError> {
+    let mut v = into_vec(ptr, len, alloc);
+    if fallible {
+        if let Err(err) = v.try_reserve_exact(additional) {
+            // the allocation is untouched on failure and is still owned by the caller
+            core::mem::forget(v);
+            return Err(err);
+        }
+    } else {
+        v.reserve_exact(additional);
+    }
+
+    // reserve_exact can overallocate a bit, set the length to an exact value so converting into a
+    // boxed slice gives us an allocation that is the correct size
+    //
+    // SAFETY: we are storing uninits anyway
+    v.set_len(len + additional);
+    Ok(into_raw(v))
+}
+
+/// Shrinks a buffer of `len` T's to exactly `new_len` T's
+///
+/// Same as [`grow`], the buffer is copied at most once.
+///
+/// # Safety
+/// The buffer must have been allocated by this module with the same allocator and with a length
+/// of `len`. `new_len` must not be greater than `len`.
+#[inline]
+pub(crate) unsafe fn shrink<T, A: Allocator>(
+    ptr: NonNull<T>,
+    len: usize,
+    new_len: usize,
+    alloc: &A,
+) -> NonNull<T> {
+    let mut v = into_vec(ptr, len, alloc);
+    // the buffer is shrunk during the conversion into a boxed slice
+    //
+    // SAFETY: we are storing uninits anyway
+    v.set_len(new_len);
+    into_raw(v)
+}
+
+#[cfg(feature = "allocator_api")]
+#[inline(always)]
+unsafe fn into_vec<T, A: Allocator>(
+    ptr: NonNull<T>,
+    len: usize,
+    alloc: &A,
+) -> Vec<MaybeUninit<T>, &A> {
+    Vec::from_raw_parts_in(ptr.cast::<MaybeUninit<T>>().as_ptr(), len, len, alloc)
+}
+
+#[cfg(not(feature = "allocator_api"))]
+#[inline(always)]
+unsafe fn into_vec<T, A: Allocator>(ptr: NonNull<T>, len: usize, _: &A) -> Vec<MaybeUninit<T>> {
+    Vec::from_raw_parts(ptr.cast::<MaybeUninit<T>>().as_ptr(), len, len)
+}
+
+#[cfg(feature = "allocator_api")]
+#[inline(always)]
+fn into_raw<T, A: Allocator>(v: Vec<MaybeUninit<T>, &A>) -> NonNull<T> {
+    let ptr = Box::into_raw_with_allocator(v.into_boxed_slice()).0;
+    // SAFETY: pointers returned by a box are never null
+    unsafe { NonNull::new_unchecked(ptr).cast::<T>() }
+}
+
+#[cfg(not(feature = "allocator_api"))]
+#[inline(always)]
+fn into_raw<T>(v: Vec<MaybeUninit<T>>) -> NonNull<T> {
+    NonNull::from(Box::leak(v.into_boxed_slice())).cast::<T>()
+}
+
+/// Deallocates a buffer of `len` T's without dropping them
+///
+/// # Safety
+/// The buffer must have been allocated by this module with the same allocator and with a length
+/// of `len`.
+#[inline]
+pub(crate) unsafe fn deallocate<T, A: Allocator>(ptr: NonNull<T>, len: usize, alloc: &A) {
+    let ptr = NonNull::slice_from_raw_parts(ptr.cast::<MaybeUninit<T>>(), len).as_ptr();
+    #[cfg(feature = "allocator_api")]
+    drop(Box::from_raw_in(ptr, alloc));
+    #[cfg(not(feature = "allocator_api"))]
+    {
+        let _ = alloc;
+        drop(Box::from_raw(ptr));
+    }
+}
+
+#[cfg(all(test, feature = "allocator_api"))]
+mod tests {
+    use std::{
+        alloc::{AllocError, Layout},
+        cell::Cell,
+        ptr::NonNull,
+    };
+
+    use rstest::rstest;
+    use rstest_reuse::apply;
+
+    use crate::{
+        gap_buf::GrowingGapBuf,
+        gapstr::{GrowingGapString, SmallGapString},
+        testing::*,
+    };
+
+    use super::{Allocator, Global};
+
+    /// Counts the live allocations made through it
+    #[derive(Default)]
+    struct Counting {
+        live: Cell<usize>,
+    }
+
+    unsafe impl Allocator for Counting {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            let ptr = Global.allocate(layout)?;
+            self.live.set(self.live.get() + 1);
+            Ok(ptr)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.live.set(self.live.get() - 1);
+            Global.deallocate(ptr, layout);
+        }
+    }
+
+    #[apply(grower_template)]
+    fn gap_string_in(g: TestGrower) {
+        let alloc = Counting::default();
+        let mut s_buf = GrowingGapString::with_grower_in(g, &alloc);
+        s_buf.insert("Hello", 0);
+        s_buf.insert("Bye", 2);
+        s_buf.replace_range(0..2, "1234");
+        assert_eq!(s_buf.remove(1..3), "23");
+        s_buf.shrink_gap(s_buf.gap_len());
+        assert!(s_buf.try_reserve(usize::MAX).is_err());
+        assert_eq!(alloc.live.get(), 1);
+
+        let cloned = s_buf.clone();
+        assert_eq!(alloc.live.get(), 2);
+        assert_eq!(cloned.get(..).unwrap(), "14Byello");
+
+        drop(s_buf);
+        drop(cloned);
+        assert_eq!(alloc.live.get(), 0);
+    }
+
+    #[apply(grower_template)]
+    fn small_gap_string_in(g: TestGrower) {
+        let alloc = Counting::default();
+        let mut s = SmallGapString::<4, _, _>::with_grower_in(g, &alloc);
+        s.insert("abc", 0);
+        assert!(s.try_insert("de", 0).is_ok());
+        assert_eq!(alloc.live.get(), 1);
+        assert_eq!(s.get(..).unwrap(), "deabc");
+
+        drop(s);
+        assert_eq!(alloc.live.get(), 0);
+    }
+
+    #[apply(grower_template)]
+    fn gap_buf_in(g: TestGrower) {
+        let alloc = Counting::default();
+        let mut buf = GrowingGapBuf::with_grower_in(g, &alloc);
+        buf.insert_many(["1", "2", "3"].map(String::from).into_iter(), 0);
+        buf.insert(1, String::from("4"));
+        assert_eq!(buf.remove(0), "1");
+        assert_eq!(buf.get_range(..).unwrap(), ["4", "2", "3"]);
+        assert_eq!(alloc.live.get(), 1);
+
+        drop(buf);
+        assert_eq!(alloc.live.get(), 0);
+    }
+}
@@ -0,0 +1,603 @@
+use core::{
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ops::RangeBounds,
+    ptr::{self, NonNull},
+    slice,
+};
+
+use crate::{
+    error::EditError,
+    gap_buf::Drain,
+    raw_gap_buf::{gap_position_out_of, move_gap},
+    utils::{get_range, split_range},
+    view::GapSlice,
+};
+
+/// A gap buffer with a fixed capacity of `N` T's stored inline
+///
+/// Unlike [`GrowingGapBuf`](crate::gap_buf::GrowingGapBuf) this never allocates. Inserting into
+/// a full buffer returns [`EditError::CapacityExceeded`] from the `try_*` methods, and panics
+/// otherwise.
+pub struct ArrayGapBuf<T, const N: usize> {
+    buf: [MaybeUninit<T>; N],
+    start_len: usize,
+    end_len: usize,
+}
+
+impl<T, const N: usize> Default for ArrayGapBuf<T, N> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> ArrayGapBuf<T, N> {
+    /// Initialize an empty [`ArrayGapBuf`]
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            buf: [const { MaybeUninit::uninit() }; N],
+            start_len: 0,
+            end_len: 0,
+        }
+    }
+
+    /// Returns the total length of the buffer excluding the gap length
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.start_len + self.end_len
+    }
+
+    /// Returns true if the buffer is empty
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the gap length
+    #[inline(always)]
+    pub const fn gap_len(&self) -> usize {
+        N - self.len()
+    }
+
+    /// Returns the fixed capacity of the buffer
+    #[inline(always)]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns true if there is no space left in the gap
+    #[inline(always)]
+    pub const fn is_full(&self) -> bool {
+        self.gap_len() == 0
+    }
+
+    /// Get the left and right side of the gap buffer
+    #[inline(always)]
+    pub const fn get_parts(&self) -> [&[T]; 2] {
+        let ptr = self.buf.as_ptr().cast::<T>();
+        // SAFETY: the first start_len and the last end_len items are always initialized
+        unsafe {
+            [
+                slice::from_raw_parts(ptr, self.start_len),
+                slice::from_raw_parts(ptr.add(N - self.end_len), self.end_len),
+            ]
+        }
+    }
+
+    /// Get the left and right side of the gap buffer as mutable slices
+    #[inline(always)]
+    pub const fn get_parts_mut(&mut self) -> [&mut [T]; 2] {
+        let ptr = self.buf.as_mut_ptr().cast::<T>();
+        // SAFETY: the first start_len and the last end_len items are always initialized, and
+        // both slices never overlap
+        unsafe {
+            [
+                slice::from_raw_parts_mut(ptr, self.start_len),
+                slice::from_raw_parts_mut(ptr.add(N - self.end_len), self.end_len),
+            ]
+        }
+    }
+
+    /// Get the value at the provided index
+    ///
+    /// Returns [`None`] if index is out of bounds.
+    #[inline(always)]
+    pub const fn get(&self, index: usize) -> Option<&T> {
+        let [start, end] = self.get_parts();
+        if index < start.len() {
+            Some(&start[index])
+        } else if index - start.len() < end.len() {
+            Some(&end[index - start.len()])
+        } else {
+            None
+        }
+    }
+
+    /// Get the value at the provided index
+    ///
+    /// Same as [`ArrayGapBuf::get`] but returns mutable reference to T.
+    #[inline(always)]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let [start, end] = self.get_parts_mut();
+        let start_len = start.len();
+        if index < start_len {
+            Some(&mut start[index])
+        } else {
+            end.get_mut(index - start_len)
+        }
+    }
+
+    /// Get a view of the values in the range
+    ///
+    /// Returns [`None`] if the provided range is out of bounds.
+    #[inline]
+    pub fn get_range<RB: RangeBounds<usize>>(&self, r: RB) -> Option<GapSlice<'_, T>> {
+        let r = get_range(self.len(), r)?;
+        let [start_r, end_r] = split_range(self.start_len, r);
+        let [start, end] = self.get_parts();
+        Some(GapSlice::new(&start[start_r], &end[end_r]))
+    }
+
+    /// Get the mutable slices of the values in the range
+    ///
+    /// Returns [`None`] if the provided range is out of bounds.
+    #[inline]
+    pub fn get_range_mut<RB: RangeBounds<usize>>(&mut self, r: RB) -> Option<[&mut [T]; 2]> {
+        let r = get_range(self.len(), r)?;
+        let [start_r, end_r] = split_range(self.start_len, r);
+        let [start, end] = self.get_parts_mut();
+        Some([&mut start[start_r], &mut end[end_r]])
+    }
+
+    /// Get a contiguous slice of the values in the range
+    ///
+    /// Returns [`None`] if the provided range is out of bounds.
+    ///
+    /// The gap is moved out of the requested range, prefer [`ArrayGapBuf::get_range`] wherever
+    /// possible.
+    #[inline]
+    pub fn get_slice<RB: RangeBounds<usize>>(&mut self, r: RB) -> Option<&mut [T]> {
+        let r = get_range(self.len(), r)?;
+        self.move_gap_out_of(r.clone());
+        let start_len = self.start_len;
+        let [start, end] = self.get_parts_mut();
+        if r.start >= start_len {
+            Some(&mut end[r.start - start_len..r.end - start_len])
+        } else {
+            Some(&mut start[r])
+        }
+    }
+
+    /// Moves the gap to the end and returns a slice of all values
+    ///
+    /// Prefer [`ArrayGapBuf::get_range`] or [`ArrayGapBuf::get_slice`] whenever possible.
+    #[inline]
+    pub fn make_contiguous(&mut self) -> &[T] {
+        self.make_contiguous_mut()
+    }
+
+    /// Same as [`ArrayGapBuf::make_contiguous`] but returns a mutable slice
+    #[inline]
+    pub fn make_contiguous_mut(&mut self) -> &mut [T] {
+        self.move_gap_start_to(self.len());
+        let [start, _] = self.get_parts_mut();
+        start
+    }
+
+    /// Moves the gap's start to the provided position
+    ///
+    /// # Panics
+    /// Panics if the provided position is greater than len.
+    #[inline]
+    pub fn move_gap_start_to(&mut self, to: usize) {
+        let len = self.len();
+        assert!(to <= len, "gap position should never be out of bounds");
+        // SAFETY: the buffer has start_len items, the gap, and then end_len items
+        unsafe { move_gap(self.as_ptr(), self.start_len, self.gap_len(), to) };
+        self.start_len = to;
+        self.end_len = len - to;
+    }
+
+    /// Moves the gap out of the provided range
+    ///
+    /// Returns without modifying the gap is already not in the provided range.
+    ///
+    /// # Panics
+    /// If the range start is greater than the end or any of them are greater than
+    /// [`ArrayGapBuf::len`].
+    #[inline]
+    pub fn move_gap_out_of<RB: RangeBounds<usize>>(&mut self, r: RB) {
+        let r = get_range(self.len(), r).expect("provided ranges should never be out of bounds");
+        if let Some(to) = gap_position_out_of(self.start_len, r) {
+            self.move_gap_start_to(to);
+        }
+    }
+
+    /// Insert T at the provided position
+    ///
+    /// # Panics
+    /// If the provided position is greater than [`ArrayGapBuf::len`] or the buffer is full.
+    #[inline]
+    pub fn insert(&mut self, at: usize, val: T) {
+        if let Err(err) = self.try_insert(at, val) {
+            panic!("{err}");
+        }
+    }
+
+    /// Same as [`ArrayGapBuf::insert`] but returns an error instead of panicking
+    ///
+    /// The buffer is left unchanged and the value is dropped if an error is returned.
+    #[inline]
+    pub fn try_insert(&mut self, at: usize, val: T) -> Result<(), EditError> {
+        let len = self.len();
+        if at > len {
+            return Err(EditError::OutOfBounds { at, len });
+        }
+        if self.is_full() {
+            return Err(EditError::CapacityExceeded {
+                needed: len + 1,
+                capacity: N,
+            });
+        }
+
+        self.move_gap_start_to(at);
+        self.buf[self.start_len].write(val);
+        self.start_len += 1;
+        Ok(())
+    }
+
+    /// Insert many T's from an iterator at the provided position
+    ///
+    /// # Panics
+    /// If the provided position is greater than [`ArrayGapBuf::len`] or the items do not fit.
+    #[inline]
+    pub fn insert_many<I: Iterator<Item = T>>(&mut self, iter: I, at: usize) {
+        if let Err(err) = self.try_insert_many(iter, at) {
+            panic!("{err}");
+        }
+    }
+
+    /// Same as [`ArrayGapBuf::insert_many`] but returns an error instead of panicking
+    ///
+    /// Iterators whose [`Iterator::size_hint`] lower bound does not fit are rejected before
+    /// taking any item. Otherwise, if the buffer gets full while the iterator still has items,
+    /// the items inserted so far are removed and dropped so the buffer is left unchanged. As
+    /// the total number of items is not known, the `needed` length of the returned
+    /// [`EditError::CapacityExceeded`] is a minimum based on the size hint.
+    pub fn try_insert_many<I: Iterator<Item = T>>(
+        &mut self,
+        mut iter: I,
+        at: usize,
+    ) -> Result<(), EditError> {
+        let len = self.len();
+        if at > len {
+            return Err(EditError::OutOfBounds { at, len });
+        }
+        let hint = iter.size_hint().0;
+        if hint > self.gap_len() {
+            return Err(EditError::CapacityExceeded {
+                needed: len.saturating_add(hint),
+                capacity: N,
+            });
+        }
+
+        self.move_gap_start_to(at);
+        let mut inserted = 0;
+        while let Some(val) = iter.next() {
+            if self.is_full() {
+                drop(val);
+                let needed = (len + inserted + 1).saturating_add(iter.size_hint().0);
+                drop(self.drain(at..at + inserted));
+                return Err(EditError::CapacityExceeded {
+                    needed,
+                    capacity: N,
+                });
+            }
+            self.buf[self.start_len].write(val);
+            self.start_len += 1;
+            inserted += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Removes and returns the T at the provided position
+    ///
+    /// # Panics
+    /// If the provided position is greater than or equal to [`ArrayGapBuf::len`].
+    #[inline]
+    pub fn remove(&mut self, at: usize) -> T {
+        match self.try_remove(at) {
+            Ok(val) => val,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Same as [`ArrayGapBuf::remove`] but returns an error instead of panicking
+    #[inline]
+    pub fn try_remove(&mut self, at: usize) -> Result<T, EditError> {
+        let len = self.len();
+        if at >= len {
+            return Err(EditError::OutOfBounds { at, len });
+        }
+
+        let removed = if self.start_len == at {
+            self.shrink_end(1)
+        } else {
+            self.move_gap_start_to(at + 1);
+            self.shrink_start(1)
+        };
+
+        // SAFETY: the removed T is now in the gap and will not be dropped by the buffer
+        Ok(unsafe { removed.cast::<T>().read() })
+    }
+
+    /// Drains the provided range from the buffer
+    ///
+    /// Same as [`GrowingGapBuf::drain`](crate::gap_buf::GrowingGapBuf::drain), the removed T's
+    /// are returned by the [`Drain`] iterator and the ones that are not taken are dropped with it.
+    ///
+    /// Returns [`None`] if the provided range is out of bounds.
+    #[inline]
+    pub fn drain<RB: RangeBounds<usize>>(&mut self, r: RB) -> Option<Drain<'_, T>> {
+        let r = get_range(self.len(), r)?;
+        // avoid moving the gap when it is already right before the range
+        let ptr = if self.start_len == r.start {
+            self.shrink_end(r.len())
+        } else {
+            self.move_gap_start_to(r.end);
+            self.shrink_start(r.len())
+        };
+
+        Some(Drain {
+            ptr,
+            __p: PhantomData,
+        })
+    }
+
+    /// Removes all of the values
+    #[inline]
+    pub fn clear(&mut self) {
+        let [start, end] = self.get_parts_mut().map(NonNull::from);
+        self.start_len = 0;
+        self.end_len = 0;
+        // SAFETY: the lengths are reset first so a panicking drop cannot cause a double drop
+        unsafe {
+            start.drop_in_place();
+            end.drop_in_place();
+        }
+    }
+
+    /// Panics if any of the internal invariants of the buffer do not hold
+    ///
+    /// Both parts must fit in the capacity. Meant for tests, see the
+    /// [`testing`](crate::testing) module.
+    #[inline]
+    pub fn check_invariants(&self) {
+        assert!(
+            self.start_len
+                .checked_add(self.end_len)
+                .is_some_and(|len| len <= N),
+            "items do not fit in the capacity"
+        );
+    }
+
+    /// Copies the provided values to the start of the gap
+    ///
+    /// # Panics
+    /// If the gap is smaller than the provided slice.
+    #[inline]
+    pub(crate) fn grow_start_with_slice(&mut self, val: &[T])
+    where
+        T: Copy,
+    {
+        assert!(self.gap_len() >= val.len());
+        // SAFETY: the gap has enough space for the values and T is Copy
+        unsafe {
+            ptr::copy_nonoverlapping(
+                val.as_ptr(),
+                self.as_ptr().add(self.start_len).as_ptr(),
+                val.len(),
+            );
+        }
+        self.start_len += val.len();
+    }
+
+    /// Shrinks the start slice and returns the removed values, which are now in the gap
+    ///
+    /// Caller must deal with T's drop code.
+    #[inline]
+    pub(crate) fn shrink_start(&mut self, by: usize) -> NonNull<[T]> {
+        assert!(self.start_len >= by);
+        self.start_len -= by;
+        // SAFETY: the removed values are still in bounds of the buffer
+        NonNull::slice_from_raw_parts(unsafe { self.as_ptr().add(self.start_len) }, by)
+    }
+
+    /// Shrinks the end slice and returns the removed values, which are now in the gap
+    ///
+    /// Caller must deal with T's drop code.
+    #[inline]
+    pub(crate) fn shrink_end(&mut self, by: usize) -> NonNull<[T]> {
+        assert!(self.end_len >= by);
+        // SAFETY: the removed values are still in bounds of the buffer
+        let removed = unsafe { self.as_ptr().add(N - self.end_len) };
+        self.end_len -= by;
+        NonNull::slice_from_raw_parts(removed, by)
+    }
+
+    #[inline(always)]
+    fn as_ptr(&mut self) -> NonNull<T> {
+        NonNull::from(&mut self.buf).cast::<T>()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for ArrayGapBuf<T, N> {
+    fn clone(&self) -> Self {
+        let mut cloned = Self::new();
+        let [start, end] = self.get_parts();
+        // the lengths are only increased after a value is written, so a panicking clone only
+        // drops the values that were already cloned
+        for val in start {
+            cloned.buf[cloned.start_len].write(val.clone());
+            cloned.start_len += 1;
+        }
+        for val in end.iter().rev() {
+            cloned.buf[N - cloned.end_len - 1].write(val.clone());
+            cloned.end_len += 1;
+        }
+
+        cloned
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayGapBuf<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use crate::error::EditError;
+
+    use super::ArrayGapBuf;
+
+    const EMPTY: ArrayGapBuf<u8, 4> = ArrayGapBuf::new();
+
+    #[test]
+    fn insert_remove() {
+        let mut buf = ArrayGapBuf::<String, 4>::new();
+        assert!(EMPTY.is_empty());
+        assert_eq!(buf.capacity(), 4);
+
+        for (i, val) in ["1", "2", "3"].into_iter().enumerate() {
+            buf.insert(i, String::from(val));
+        }
+        buf.insert(1, String::from("4"));
+        assert!(buf.is_full());
+        assert_eq!(buf.get_range(..).unwrap(), ["1", "4", "2", "3"]);
+        assert_eq!(
+            buf.try_insert(0, String::from("5")),
+            Err(EditError::CapacityExceeded {
+                needed: 5,
+                capacity: 4
+            })
+        );
+
+        assert_eq!(buf.remove(0), "1");
+        let [start, end] = buf.get_parts();
+        assert!(start.is_empty());
+        assert_eq!(end, ["4", "2", "3"]);
+        buf.move_gap_start_to(2);
+        assert_eq!(buf.remove(2), "3");
+        assert_eq!(buf.remove(0), "4");
+        assert_eq!(
+            buf.try_remove(1),
+            Err(EditError::OutOfBounds { at: 1, len: 1 })
+        );
+        assert_eq!(
+            buf.try_insert(2, String::new()),
+            Err(EditError::OutOfBounds { at: 2, len: 1 })
+        );
+        assert_eq!(buf.get(0).map(String::as_str), Some("2"));
+        assert_eq!(buf.get(1), None);
+    }
+
+    #[test]
+    fn move_gap() {
+        let mut buf = ArrayGapBuf::<String, 8>::new();
+        for (i, val) in ["1", "2", "3", "4", "5"].into_iter().enumerate() {
+            buf.insert(i, String::from(val));
+        }
+
+        for to in [0, 5, 2, 3, 1, 4] {
+            buf.move_gap_start_to(to);
+            assert_eq!(buf.get_parts()[0].len(), to);
+            assert_eq!(buf.get_range(..).unwrap(), ["1", "2", "3", "4", "5"]);
+        }
+
+        buf.move_gap_start_to(1);
+        buf.move_gap_out_of(0..4);
+        assert_eq!(buf.get_parts()[0].len(), 0);
+        buf.move_gap_out_of(1..3);
+        assert_eq!(buf.get_parts()[0].len(), 0);
+
+        *buf.get_mut(4).unwrap() = String::from("6");
+        let cloned = buf.clone();
+        assert_eq!(cloned.get_range(1..).unwrap(), ["2", "3", "4", "6"]);
+        buf.clear();
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn insert_many_drain() {
+        let mut buf = ArrayGapBuf::<String, 6>::new();
+        buf.insert_many(["1", "2", "3", "4"].map(String::from).into_iter(), 0);
+        buf.move_gap_start_to(1);
+
+        // the size hint is checked before taking any item
+        assert_eq!(
+            buf.try_insert_many((0..3).map(|i| i.to_string()), 1),
+            Err(EditError::CapacityExceeded {
+                needed: 7,
+                capacity: 6
+            })
+        );
+        // items are removed if the iterator has more than it hinted
+        assert_eq!(
+            buf.try_insert_many((0..3).map(|i| i.to_string()).filter(|_| true), 1),
+            Err(EditError::CapacityExceeded {
+                needed: 7,
+                capacity: 6
+            })
+        );
+        assert_eq!(buf.get_range(..).unwrap(), ["1", "2", "3", "4"]);
+        buf.check_invariants();
+
+        buf.try_insert_many(["a", "b"].map(String::from).into_iter(), 2)
+            .unwrap();
+        assert_eq!(buf.get_range(..).unwrap(), ["1", "2", "a", "b", "3", "4"]);
+        let mut drain = buf.drain(1..4).unwrap();
+        assert_eq!(drain.next().as_deref(), Some("2"));
+        assert_eq!(drain.next_back().as_deref(), Some("b"));
+        drop(drain);
+        assert_eq!(buf.get_range(..).unwrap(), ["1", "3", "4"]);
+        assert!(buf.drain(2..4).is_none());
+        assert_eq!(buf.drain(1..1).unwrap().count(), 0);
+        buf.check_invariants();
+    }
+
+    #[test]
+    fn slices() {
+        let mut buf = ArrayGapBuf::<u8, 8>::new();
+        buf.insert_many(1..=6, 0);
+        buf.move_gap_start_to(3);
+
+        let [start, end] = buf.get_range_mut(1..5).unwrap();
+        assert_eq!(start, [2, 3]);
+        assert_eq!(end, [4, 5]);
+        start[0] = 7;
+        assert_eq!(buf.get_range(..).unwrap(), [1, 7, 3, 4, 5, 6]);
+        assert!(buf.get_range_mut(5..7).is_none());
+
+        assert_eq!(buf.get_slice(2..5).unwrap(), [3, 4, 5]);
+        assert_eq!(buf.get_slice(0..1).unwrap(), [1]);
+        assert!(buf.get_slice(..7).is_none());
+        buf.make_contiguous_mut()[5] = 0;
+        assert_eq!(buf.make_contiguous(), [1, 7, 3, 4, 5, 0]);
+        assert_eq!(buf.gap_len(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_panics() {
+        let mut buf = ArrayGapBuf::<u8, 1>::new();
+        buf.insert(0, 1);
+        buf.insert(0, 1);
+    }
+}
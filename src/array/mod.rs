@@ -0,0 +1,7 @@
+//! Fixed-capacity gap buffers that store their items inline and never allocate
+
+mod buf;
+mod string;
+
+pub use buf::ArrayGapBuf;
+pub use string::ArrayGapString;
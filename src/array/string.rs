@@ -0,0 +1,426 @@
+#[cfg(doc)]
+use crate::gapstr::GrowingGapString;
+use core::{
+    fmt,
+    ops::{Range, RangeBounds},
+    str::{self, from_utf8_unchecked, from_utf8_unchecked_mut},
+};
+
+use crate::{error::EditError, gapstr::Graphemes, utils::try_get_range, view::GapStr};
+
+use super::ArrayGapBuf;
+
+/// A gap buffer string with a fixed capacity of `N` bytes stored inline
+///
+/// Unlike [`GrowingGapString`] this never allocates. Edits that do not fit in the capacity return
+/// [`EditError::CapacityExceeded`] from the `try_*` methods, and panic otherwise.
+///
+/// The line, grapheme, width and word queries are shared with the growing string through
+/// [`GapStr`], the word queries are available from [`ArrayGapString::as_gap_str`]. Cursors,
+/// observers, protected regions, the maximum length and the edit history depend on the
+/// allocating buffer and are only provided by [`GrowingGapString`], edit an [`ArrayGapString`]
+/// by byte position instead.
+#[derive(Clone, Default)]
+pub struct ArrayGapString<const N: usize> {
+    buf: ArrayGapBuf<u8, N>,
+}
+
+impl<const N: usize> ArrayGapString<N> {
+    /// Initialize an empty [`ArrayGapString`]
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            buf: ArrayGapBuf::new(),
+        }
+    }
+
+    /// Returns the total length excluding the gap
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns true if the string is empty
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Returns the gaps length
+    #[inline(always)]
+    pub const fn gap_len(&self) -> usize {
+        self.buf.gap_len()
+    }
+
+    /// Returns the fixed capacity in bytes
+    #[inline(always)]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns both sides of the gap buffer
+    #[inline(always)]
+    pub const fn get_parts(&self) -> [&str; 2] {
+        let [start, end] = self.buf.get_parts();
+        // SAFETY: the gap is never positioned between char boundaries, both parts are always
+        // valid UTF-8 string slices
+        unsafe { [from_utf8_unchecked(start), from_utf8_unchecked(end)] }
+    }
+
+    /// Returns both sides of the gap buffer as mutable string slices
+    #[inline(always)]
+    pub fn get_parts_mut(&mut self) -> [&mut str; 2] {
+        // SAFETY: the gap is never positioned between char boundaries, both parts are always
+        // valid UTF-8 string slices
+        self.buf
+            .get_parts_mut()
+            .map(|s| unsafe { from_utf8_unchecked_mut(s) })
+    }
+
+    /// Returns a [`GapStr`] over the whole string
+    #[inline(always)]
+    pub const fn as_gap_str(&self) -> GapStr<'_> {
+        let [start, end] = self.get_parts();
+        GapStr::new(start, end)
+    }
+
+    /// Same as [`str::get`] but for gap buffers
+    ///
+    /// Returns [`None`] if the range is out of bounds or is not on a char boundary.
+    #[inline]
+    pub fn get<RB: RangeBounds<usize>>(&self, r: RB) -> Option<GapStr<'_>> {
+        self.as_gap_str().get(r)
+    }
+
+    /// Returns a contiguous slice from the gap buffer
+    ///
+    /// The gap is moved out of the requested range, prefer [`ArrayGapString::get`] wherever
+    /// possible.
+    ///
+    /// Returns [`None`] if the range is out of bounds or is not on a char boundary.
+    #[inline]
+    pub fn get_slice<RB: RangeBounds<usize>>(&mut self, r: RB) -> Option<&str> {
+        let r = try_get_range(self.len(), r).ok()?;
+        self.get(r.clone())?;
+        self.buf.move_gap_out_of(r.clone());
+        let [start, end] = self.get_parts();
+        if r.end <= start.len() {
+            start.get(r)
+        } else {
+            end.get(r.start - start.len()..r.end - start.len())
+        }
+    }
+
+    /// Insert a string at the provided position
+    ///
+    /// # Panics
+    /// If the provided position is out of bounds or not on a char boundary, or if the string
+    /// does not fit.
+    #[inline]
+    pub fn insert(&mut self, s: &str, at: usize) {
+        if let Err(err) = self.try_insert(s, at) {
+            panic!("{err}");
+        }
+    }
+
+    /// Same as [`ArrayGapString::insert`] but returns an error instead of panicking
+    ///
+    /// The string is left unchanged if an error is returned.
+    pub fn try_insert(&mut self, s: &str, at: usize) -> Result<(), EditError> {
+        self.check_position(at)?;
+        self.check_capacity(self.len() + s.len())?;
+        self.buf.move_gap_start_to(at);
+        self.buf.grow_start_with_slice(s.as_bytes());
+        Ok(())
+    }
+
+    /// Removes the provided range and returns it as a string slice
+    ///
+    /// # Panics
+    /// If the provided range is out of bounds, the range start is greater than its end, or the
+    /// range does not lie on a char boundary.
+    #[inline]
+    pub fn remove<RB: RangeBounds<usize>>(&mut self, r: RB) -> &str {
+        match self.try_remove(r) {
+            Ok(s) => s,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Same as [`ArrayGapString::remove`] but returns an error instead of panicking
+    ///
+    /// The string is left unchanged if an error is returned.
+    pub fn try_remove<RB: RangeBounds<usize>>(&mut self, r: RB) -> Result<&str, EditError> {
+        let r = self.check_range(r)?;
+        // avoid moving the gap when it is already right before the range
+        let removed = if self.buf.get_parts()[0].len() == r.start {
+            self.buf.shrink_end(r.len())
+        } else {
+            self.buf.move_gap_start_to(r.end);
+            self.buf.shrink_start(r.len())
+        };
+
+        // SAFETY: the removed bytes are now in the gap and were on char boundaries
+        Ok(unsafe { from_utf8_unchecked(removed.as_ref()) })
+    }
+
+    /// Equivalent to [`String::replace_range`](alloc::string::String::replace_range) from the
+    /// standard library
+    ///
+    /// # Panics
+    /// If the provided range is out of bounds, the range start is greater than its end, the
+    /// range does not lie on a char boundary, or the result does not fit.
+    #[inline]
+    pub fn replace_range<RB: RangeBounds<usize>>(&mut self, r: RB, s: &str) {
+        if let Err(err) = self.try_replace_range(r, s) {
+            panic!("{err}");
+        }
+    }
+
+    /// Same as [`ArrayGapString::replace_range`] but returns an error instead of panicking
+    ///
+    /// The string is left unchanged if an error is returned.
+    pub fn try_replace_range<RB: RangeBounds<usize>>(
+        &mut self,
+        r: RB,
+        s: &str,
+    ) -> Result<(), EditError> {
+        let r = self.check_range(r)?;
+        self.check_capacity(self.len() - r.len() + s.len())?;
+        self.buf.move_gap_start_to(r.end);
+        self.buf.shrink_start(r.len());
+        self.buf.grow_start_with_slice(s.as_bytes());
+        Ok(())
+    }
+
+    /// Removes all of the text
+    #[inline]
+    pub fn clear(&mut self) {
+        self.buf.clear();
+    }
+
+    /// See [`GrowingGapString::line_start`]
+    #[inline]
+    pub fn line_start(&self, at: usize) -> usize {
+        self.as_gap_str().line_start(at)
+    }
+
+    /// See [`GrowingGapString::line_end`]
+    #[inline]
+    pub fn line_end(&self, at: usize) -> usize {
+        self.as_gap_str().line_end(at)
+    }
+
+    /// See [`GrowingGapString::line_count`]
+    #[inline]
+    pub fn line_count(&self) -> usize {
+        self.as_gap_str().line_count()
+    }
+
+    /// See [`GrowingGapString::line_of`]
+    #[inline]
+    pub fn line_of(&self, at: usize) -> usize {
+        self.as_gap_str().line_of(at)
+    }
+
+    /// See [`GrowingGapString::line_range`]
+    #[inline]
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        self.as_gap_str().line_range(line)
+    }
+
+    /// See [`GrowingGapString::is_grapheme_boundary`]
+    #[inline]
+    pub fn is_grapheme_boundary(&self, at: usize) -> bool {
+        self.as_gap_str().is_grapheme_boundary(at)
+    }
+
+    /// See [`GrowingGapString::next_grapheme_boundary`]
+    #[inline]
+    pub fn next_grapheme_boundary(&self, at: usize) -> Option<usize> {
+        self.as_gap_str().next_grapheme_boundary(at)
+    }
+
+    /// See [`GrowingGapString::prev_grapheme_boundary`]
+    #[inline]
+    pub fn prev_grapheme_boundary(&self, at: usize) -> Option<usize> {
+        self.as_gap_str().prev_grapheme_boundary(at)
+    }
+
+    /// See [`GrowingGapString::graphemes`]
+    #[inline]
+    pub fn graphemes(&self) -> Graphemes<'_> {
+        self.as_gap_str().graphemes()
+    }
+
+    /// See [`GrowingGapString::column_of`]
+    #[inline]
+    pub fn column_of(&self, at: usize, tab_width: usize) -> usize {
+        self.as_gap_str().column_of(at, tab_width)
+    }
+
+    /// See [`GrowingGapString::byte_at_column`]
+    #[inline]
+    pub fn byte_at_column(&self, line: usize, column: usize, tab_width: usize) -> Option<usize> {
+        self.as_gap_str().byte_at_column(line, column, tab_width)
+    }
+
+    /// See [`GrowingGapString::byte_at_column_from`]
+    #[inline]
+    pub fn byte_at_column_from(&self, line_start: usize, column: usize, tab_width: usize) -> usize {
+        self.as_gap_str()
+            .byte_at_column_from(line_start, column, tab_width)
+    }
+
+    /// See [`GrowingGapString::line_width`]
+    #[inline]
+    pub fn line_width(&self, line: usize, tab_width: usize) -> Option<usize> {
+        self.as_gap_str().line_width(line, tab_width)
+    }
+
+    /// See [`GrowingGapString::line_width_from`]
+    #[inline]
+    pub fn line_width_from(&self, line_start: usize, tab_width: usize) -> usize {
+        self.as_gap_str().line_width_from(line_start, tab_width)
+    }
+
+    /// Panics if any of the internal invariants of the string do not hold
+    ///
+    /// Both parts must fit in the capacity and be valid UTF-8. Meant for tests, see the
+    /// [`testing`](crate::testing) module.
+    pub fn check_invariants(&self) {
+        self.buf.check_invariants();
+        let [start, end] = self.buf.get_parts();
+        assert!(str::from_utf8(start).is_ok(), "start is not valid UTF-8");
+        assert!(str::from_utf8(end).is_ok(), "end is not valid UTF-8");
+    }
+
+    #[inline]
+    fn check_position(&self, at: usize) -> Result<(), EditError> {
+        let len = self.len();
+        if at > len {
+            Err(EditError::OutOfBounds { at, len })
+        } else if !self.as_gap_str().is_char_boundary(at) {
+            Err(EditError::NotCharBoundary { at })
+        } else {
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn check_range<RB: RangeBounds<usize>>(&self, r: RB) -> Result<Range<usize>, EditError> {
+        let r = try_get_range(self.len(), r)?;
+        self.check_position(r.start)?;
+        self.check_position(r.end)?;
+        Ok(r)
+    }
+
+    #[inline]
+    fn check_capacity(&self, needed: usize) -> Result<(), EditError> {
+        if needed > N {
+            Err(EditError::CapacityExceeded {
+                needed,
+                capacity: N,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<const N: usize> fmt::Display for ArrayGapString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.as_gap_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Debug for ArrayGapString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.as_gap_str(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::EditError;
+
+    use super::ArrayGapString;
+
+    #[test]
+    fn edit() {
+        let mut s = ArrayGapString::<8>::new();
+        s.insert("Hllo", 0);
+        s.insert("é", 1);
+        assert_eq!(s.get_parts(), ["Hé", "llo"]);
+        assert_eq!(s.len(), 6);
+
+        assert_eq!(
+            s.try_insert("abc", 0),
+            Err(EditError::CapacityExceeded {
+                needed: 9,
+                capacity: 8
+            })
+        );
+        assert_eq!(
+            s.try_insert("a", 2),
+            Err(EditError::NotCharBoundary { at: 2 })
+        );
+        assert_eq!(
+            s.try_replace_range(0..1, "abcd"),
+            Err(EditError::CapacityExceeded {
+                needed: 9,
+                capacity: 8
+            })
+        );
+        assert_eq!(s.as_gap_str(), "Héllo");
+
+        assert_eq!(s.remove(1..3), "é");
+        s.replace_range(0..1, "Je");
+        assert_eq!(s.get(..).unwrap(), "Jello");
+        s.replace_range(1..4, "");
+        assert_eq!(s.to_string(), "Jo");
+        assert_eq!(format!("{s:?}"), "\"Jo\"");
+
+        s.clear();
+        assert!(s.is_empty());
+        s.insert("12345678", 0);
+        assert_eq!(s.gap_len(), 0);
+    }
+
+    #[test]
+    fn queries() {
+        let mut s = ArrayGapString::<32>::new();
+        s.insert("a\tb中\ne\u{301}x", 0);
+        for gap in [0, 2, 6, 8, 11] {
+            // replacing an empty range moves the gap to it
+            s.replace_range(gap..gap, "");
+            assert_eq!(s.get_parts()[0].len(), gap);
+            s.check_invariants();
+
+            assert_eq!(s.line_count(), 2);
+            assert_eq!(s.line_range(1), Some(7..11));
+            assert_eq!(s.line_of(8), 1);
+            assert_eq!((s.line_start(5), s.line_end(5)), (0, 6));
+            assert!(!s.is_grapheme_boundary(8));
+            assert_eq!(s.next_grapheme_boundary(7), Some(10));
+            assert_eq!(s.prev_grapheme_boundary(10), Some(7));
+            assert_eq!(s.graphemes().count(), 7);
+            assert_eq!(s.column_of(6, 4), 7);
+            assert_eq!(s.byte_at_column(0, 5, 4), Some(3));
+            assert_eq!(s.line_width(1, 4), Some(2));
+            assert_eq!(s.byte_at_column_from(0, 5, 4), 3);
+            assert_eq!(s.line_width_from(7, 4), 2);
+        }
+
+        s.get_parts_mut()[0].make_ascii_uppercase();
+        s.get_parts_mut()[1].make_ascii_uppercase();
+        assert_eq!(s.get(..).unwrap(), "A\tB中\nE\u{301}X");
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_panics() {
+        let mut s = ArrayGapString::<2>::new();
+        s.insert("abc", 0);
+    }
+}
@@ -0,0 +1,65 @@
+use alloc::collections::TryReserveError;
+use core::fmt;
+
+/// The error returned by the fallible editing methods of the gap buffers
+///
+/// Positions are reported in the same units as the failed call, elements for
+/// [`GrowingGapBuf`](crate::gap_buf::GrowingGapBuf) and bytes for
+/// [`GrowingGapString`](crate::gapstr::GrowingGapString).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EditError {
+    /// The position is out of bounds for the length
+    OutOfBounds { at: usize, len: usize },
+    /// The position is not on a char boundary
+    NotCharBoundary { at: usize },
+    /// The range start is greater than its end
+    InvalidRange { start: usize, end: usize },
+    /// The buffer could not allocate enough space for the edit
+    AllocFailed(TryReserveError),
+    /// The edit requires more space than a fixed capacity buffer has, or than the maximum
+    /// length of a buffer allows
+    CapacityExceeded { needed: usize, capacity: usize },
+    /// The edit touches a protected region with the provided range
+    Protected { start: usize, end: usize },
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfBounds { at, len } => {
+                write!(f, "position {at} is out of bounds for length {len}")
+            }
+            Self::NotCharBoundary { at } => write!(f, "position {at} is not on a char boundary"),
+            Self::InvalidRange { start, end } => {
+                write!(f, "range start {start} is greater than its end {end}")
+            }
+            Self::AllocFailed(err) => write!(f, "allocation failed: {err}"),
+            Self::CapacityExceeded { needed, capacity } => {
+                write!(
+                    f,
+                    "edit needs a length of {needed} but the capacity is {capacity}"
+                )
+            }
+            Self::Protected { start, end } => {
+                write!(f, "edit touches the protected range {start}..{end}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EditError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::AllocFailed(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<TryReserveError> for EditError {
+    #[inline]
+    fn from(value: TryReserveError) -> Self {
+        Self::AllocFailed(value)
+    }
+}
@@ -1,22 +1,50 @@
-use std::{marker::PhantomData, ops::RangeBounds};
+#[cfg(doc)]
+use alloc::vec::Vec;
+use alloc::{boxed::Box, collections::TryReserveError};
+use core::{
+    marker::PhantomData,
+    ops::{Range, RangeBounds},
+};
 
-use crate::{grower::Grower, raw_gap_buf::RawGapBuf, utils::get_range};
+#[cfg(feature = "stats")]
+use crate::stats::{BufStats, EventHook};
+use crate::{
+    allocator::{Allocator, Global},
+    error::EditError,
+    gapstr::Point,
+    grower::{ContextGrower, GapContext, History, OpKind},
+    limit::{MaxLen, OverflowPolicy},
+    raw_gap_buf::RawGapBuf,
+    utils::{get_range, point_of},
+    view::GapSlice,
+};
 
+pub use super::cursor::{Cursor, CursorMut};
 pub use super::drain::Drain;
 
 #[derive(Clone)]
-pub struct GrowingGapBuf<T, G: Grower<[T]>> {
-    raw: RawGapBuf<T>,
+pub struct GrowingGapBuf<T, G: ContextGrower<[T]>, A: Allocator = Global> {
+    raw: RawGapBuf<T, A>,
     grower: G,
+    history: History,
+    /// Allocated the first time a maximum length is set, so buffers that do not use it stay
+    /// small
+    extras: Option<Box<Extras>>,
 }
 
-impl<T, G: Grower<[T]> + Default> Default for GrowingGapBuf<T, G> {
+/// The rarely used state of a [`GrowingGapBuf`]
+#[derive(Clone, Default)]
+struct Extras {
+    max_len: Option<MaxLen>,
+}
+
+impl<T, G: ContextGrower<[T]> + Default> Default for GrowingGapBuf<T, G> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<T, G: Grower<[T]>> GrowingGapBuf<T, G> {
+impl<T, G: ContextGrower<[T]>> GrowingGapBuf<T, G> {
     /// Initialize an empty gap buffer with the default state of the grower
     #[inline(always)]
     pub fn new() -> GrowingGapBuf<T, G>
@@ -26,28 +54,67 @@ impl<T, G: Grower<[T]>> GrowingGapBuf<T, G> {
         Self {
             raw: RawGapBuf::default(),
             grower: G::default(),
+            history: History::new(),
+            extras: None,
         }
     }
 
     /// Initialize a gap buffer with the provided grower
     ///
     /// Depending on the type and use case you may prefer a different strategy when growing
-    /// or shrinking the gap buffer. This allows you to provide your own [`Grower`] to limit how
+    /// or shrinking the gap buffer. This allows you to provide your own [`ContextGrower`] to limit how
     /// much extra capacity can be allocated.
     #[inline(always)]
     pub const fn with_grower(grower: G) -> Self {
         Self {
             raw: RawGapBuf::new(),
             grower,
+            history: History::new(),
+            extras: None,
+        }
+    }
+}
+
+impl<T, G: ContextGrower<[T]>, A: Allocator> GrowingGapBuf<T, G, A> {
+    /// Initialize an empty gap buffer that allocates in the provided allocator
+    #[inline(always)]
+    pub fn new_in(alloc: A) -> Self
+    where
+        G: Default,
+    {
+        Self::with_grower_in(G::default(), alloc)
+    }
+
+    /// Initialize an empty gap buffer with the provided grower that allocates in the provided
+    /// allocator
+    #[inline(always)]
+    pub const fn with_grower_in(grower: G, alloc: A) -> Self {
+        Self {
+            raw: RawGapBuf::new_in(alloc),
+            grower,
+            history: History::new(),
+            extras: None,
         }
     }
 
+    /// Returns a reference to the underlying allocator
+    #[inline(always)]
+    pub fn allocator(&self) -> &A {
+        self.raw.allocator()
+    }
+
     /// Returns the total length of the buffer excluding the gap length
     #[inline(always)]
-    pub(crate) fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         self.raw.len()
     }
 
+    /// Returns true if the buffer is empty
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
     /// Returns the gap length
     ///
     /// This is the same as [`Vec::capacity`] but for a gap buffer.
@@ -98,16 +165,17 @@ impl<T, G: Grower<[T]>> GrowingGapBuf<T, G> {
         self.raw.get_mut(index)
     }
 
-    /// Get slices of the values in the range
+    /// Get a view of the values in the range
     ///
-    /// If the provided range is not out of bounds, returns two slices of T.
-    /// The first one being before the gap, and the second one being after the gap.
+    /// If the provided range is not out of bounds, returns a [`GapSlice`] over the values before
+    /// and after the gap.
     ///
     /// If a single contiguous slice is needed [`GrowingGapBuf::get_slice`] can be used.
     #[inline(always)]
-    pub fn get_range<RB: RangeBounds<usize>>(&self, r: RB) -> Option<[&[T]; 2]> {
+    pub fn get_range<RB: RangeBounds<usize>>(&self, r: RB) -> Option<GapSlice<'_, T>> {
         let r = get_range(self.raw.len(), r)?;
-        self.raw.get_range(r)
+        let [start, end] = self.raw.get_range(r)?;
+        Some(GapSlice::new(start, end))
     }
 
     /// Get a slice of the values in the range
@@ -161,16 +229,31 @@ impl<T, G: Grower<[T]>> GrowingGapBuf<T, G> {
     #[inline]
     pub fn insert(&mut self, at: usize, val: T) {
         assert!(self.raw.len() >= at);
-        if self.raw.gap_len() == 0 {
-            let [start, end] = self.raw.get_parts();
-            let base = self.grower.base_gap_size(start, end);
-            let max = self.grower.max_gap_size(start, end);
-            self.grow_gap_at(base.min(max) + 1, at);
-        } else {
-            self.raw.move_gap_start_to(at);
+        if self.fit(1).unwrap_or_else(|err| panic!("{err}")) == 0 {
+            return;
         }
+        self.prepare_infallible(OpKind::Insert, at..at, 1);
+        self.raw.move_gap_start_to(at);
+        self.raw.grow_start_with(val);
+    }
 
+    /// Same as [`GrowingGapBuf::insert`] but returns an error instead of panicking
+    ///
+    /// The buffer is left unchanged and the value is dropped if an error is returned.
+    #[inline]
+    pub fn try_insert(&mut self, at: usize, val: T) -> Result<(), EditError> {
+        let len = self.raw.len();
+        if at > len {
+            return Err(EditError::OutOfBounds { at, len });
+        }
+        if self.fit(1)? == 0 {
+            return Ok(());
+        }
+
+        self.prepare(OpKind::Insert, at..at, 1, true)?;
+        self.raw.move_gap_start_to(at);
         self.raw.grow_start_with(val);
+        Ok(())
     }
 
     /// Insert many T's from an iterator at the provided position
@@ -181,20 +264,167 @@ impl<T, G: Grower<[T]>> GrowingGapBuf<T, G> {
     ///
     /// # Panics
     /// If the provided position is greater than [`GrowingGapBuf::len`] panics.
+    /// If the items exceed a [`MaxLen`] rejecting the edit, see
+    /// [`GrowingGapBuf::try_insert_many`].
     #[inline]
     pub fn insert_many<I: Iterator<Item = T>>(&mut self, mut iter: I, at: usize) {
+        assert!(
+            self.raw.len() >= at,
+            "insert index should never be out of bounds"
+        );
+        if let Err(err) = self.check_hint(iter.size_hint().0) {
+            panic!("{err}");
+        }
+
+        let (gap_start, gap_len, history) =
+            (self.raw.start_len(), self.raw.gap_len(), self.history);
         let mut hint = iter.size_hint().0.max(1);
+        let mut room = self.room();
+        let mut inserted = 0;
         self.raw.move_gap_start_to(at);
         while let Some(item) = iter.next() {
-            if self.raw.gap_len() < hint {
-                self.grow_gap(hint);
+            if room == 0 {
+                drop(item);
+                if let Err(err) = self.overflow(iter.size_hint().0) {
+                    self.undo_insert(inserted, gap_start, gap_len, history);
+                    panic!("{err}");
+                }
+                break;
+            }
+            if self.raw.gap_len() < hint.min(room) {
+                let pos = self.raw.start_len();
+                self.prepare_infallible(OpKind::Insert, pos..pos, hint.min(room));
             }
 
             self.raw.grow_start_with(item);
+            inserted += 1;
+            room -= 1;
             hint = iter.size_hint().0.max(1);
         }
     }
 
+    /// Same as [`GrowingGapBuf::insert_many`] but returns an error instead of panicking or
+    /// aborting
+    ///
+    /// If growing the gap fails, the items inserted so far are dropped and the buffer is left
+    /// exactly as it was before the call, with the same items, gap position and gap length.
+    ///
+    /// # Errors
+    /// If the lower bound of [`Iterator::size_hint`] exceeds a [`MaxLen`] rejecting the edit, an
+    /// error is returned before anything is inserted and the buffer is left untouched. Otherwise
+    /// the items only turn out to exceed it while inserting them. They are then dropped and the
+    /// buffer is left exactly as it was, and the `needed` length of the error is only a minimum,
+    /// computed from the items inserted so far and the lower bound of what remains.
+    #[inline]
+    pub fn try_insert_many<I: Iterator<Item = T>>(
+        &mut self,
+        mut iter: I,
+        at: usize,
+    ) -> Result<(), EditError> {
+        let len = self.raw.len();
+        if at > len {
+            return Err(EditError::OutOfBounds { at, len });
+        }
+        self.check_hint(iter.size_hint().0)?;
+
+        let (gap_start, gap_len, history) =
+            (self.raw.start_len(), self.raw.gap_len(), self.history);
+        let mut hint = iter.size_hint().0.max(1);
+        let mut room = self.room();
+        let mut inserted = 0;
+        self.raw.move_gap_start_to(at);
+        while let Some(item) = iter.next() {
+            if room == 0 {
+                drop(item);
+                let result = self.overflow(iter.size_hint().0);
+                if result.is_err() {
+                    self.undo_insert(inserted, gap_start, gap_len, history);
+                }
+                return result;
+            }
+            if self.raw.gap_len() < hint.min(room) {
+                let pos = self.raw.start_len();
+                if let Err(err) = self.prepare(OpKind::Insert, pos..pos, hint.min(room), true) {
+                    drop(item);
+                    self.undo_insert(inserted, gap_start, gap_len, history);
+                    return Err(err.into());
+                }
+            }
+            self.raw.grow_start_with(item);
+            inserted += 1;
+            room -= 1;
+            hint = iter.size_hint().0.max(1);
+        }
+
+        Ok(())
+    }
+
+    /// Removes and returns the T at the provided position
+    ///
+    /// Same as [`Vec::remove`] but the gap is positioned at the removed index instead of shifting
+    /// all of the items after it.
+    ///
+    /// # Panics
+    /// If the provided position is greater than or equal to [`GrowingGapBuf::len`].
+    #[inline]
+    pub fn remove(&mut self, at: usize) -> T {
+        assert!(
+            self.raw.len() > at,
+            "removal index should never be out of bounds"
+        );
+        self.prepare_infallible(OpKind::Remove, at..at + 1, 0);
+        let removed = if self.raw.start_len() == at {
+            self.raw.shrink_end(1)
+        } else {
+            self.raw.move_gap_start_to(at + 1);
+            self.raw.shrink_start(1)
+        };
+
+        // SAFETY: the removed T is now in the gap and will not be dropped by the buffer
+        unsafe { removed.cast::<T>().read() }
+    }
+
+    /// Same as [`GrowingGapBuf::remove`] but returns an error instead of panicking
+    #[inline]
+    pub fn try_remove(&mut self, at: usize) -> Result<T, EditError> {
+        let len = self.raw.len();
+        if at >= len {
+            return Err(EditError::OutOfBounds { at, len });
+        }
+
+        Ok(self.remove(at))
+    }
+
+    /// Returns a [`Cursor`] pointing at the provided position
+    ///
+    /// The position can be [`GrowingGapBuf::len`] to point at the "ghost" element.
+    ///
+    /// # Panics
+    /// If the provided position is greater than [`GrowingGapBuf::len`].
+    #[inline]
+    pub fn cursor_at(&self, at: usize) -> Cursor<'_, T, G, A> {
+        assert!(
+            self.raw.len() >= at,
+            "cursor position should never be out of bounds"
+        );
+        Cursor::new(self, at)
+    }
+
+    /// Returns a [`CursorMut`] pointing at the provided position
+    ///
+    /// The position can be [`GrowingGapBuf::len`] to point at the "ghost" element.
+    ///
+    /// # Panics
+    /// If the provided position is greater than [`GrowingGapBuf::len`].
+    #[inline]
+    pub fn cursor_mut_at(&mut self, at: usize) -> CursorMut<'_, T, G, A> {
+        assert!(
+            self.raw.len() >= at,
+            "cursor position should never be out of bounds"
+        );
+        CursorMut::new(self, at)
+    }
+
     /// Drains the provided range from the gap buffer
     ///
     /// If the provided ranges are out of bounds returns None.
@@ -203,10 +433,14 @@ impl<T, G: Grower<[T]>> GrowingGapBuf<T, G> {
     ///
     /// Returns [`None`] if the provided range is out of bounds.
     ///
+    /// Same as the other removing methods, the gap is shrunk beforehand if
+    /// [`ContextGrower`] decides so.
+    ///
     /// See [`Drain`] for its available methods.
     #[inline(always)]
     pub fn drain<RB: RangeBounds<usize>>(&mut self, r: RB) -> Option<Drain<'_, T>> {
         let r = get_range(self.raw.len(), r)?;
+        self.prepare_infallible(OpKind::Remove, r.clone(), 0);
         self.raw.move_gap_start_to(r.end);
 
         let drain_ptr = self.raw.shrink_start(r.len());
@@ -217,18 +451,256 @@ impl<T, G: Grower<[T]>> GrowingGapBuf<T, G> {
         })
     }
 
-    /// See [`RawGapBuf::realloc`]
-    pub(crate) fn grow_gap(&mut self, by: usize) {
-        self.raw.grow_gap(by);
+    /// Shrink the gap
+    ///
+    /// This is the equivalent of [`Vec::shrink_to`] but for the gap. The provided [`ContextGrower`]
+    /// will handle shrinking by default but this method allows you to shrink the gap explicitly.
+    ///
+    /// # Panics
+    /// If the provided value is greater than [`GrowingGapBuf::gap_len`].
+    #[inline]
+    pub fn shrink_gap(&mut self, by: usize) {
+        self.raw.shrink_gap(by);
+    }
+
+    /// Same as [`GrowingGapBuf::shrink_gap`] but returns an error instead of panicking
+    ///
+    /// Returns [`EditError::OutOfBounds`] with the gap length if the provided value is greater
+    /// than [`GrowingGapBuf::gap_len`].
+    #[inline]
+    pub fn try_shrink_gap(&mut self, by: usize) -> Result<(), EditError> {
+        let len = self.raw.gap_len();
+        if by > len {
+            return Err(EditError::OutOfBounds { at: by, len });
+        }
+
+        self.raw.shrink_gap(by);
+        Ok(())
+    }
+
+    /// Returns the reallocation and gap movement counters of the buffer
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub fn stats(&self) -> BufStats {
+        self.raw.stats()
+    }
+
+    /// Resets the counters returned by [`GrowingGapBuf::stats`]
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub fn reset_stats(&mut self) {
+        self.raw.reset_stats();
+    }
+
+    /// Sets a function to be called on every reallocation and gap movement
+    ///
+    /// Clones of the buffer start without a hook.
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub fn set_event_hook(&mut self, hook: Option<EventHook>) {
+        self.raw.set_event_hook(hook);
+    }
+
+    /// Returns the maximum length of the buffer
+    #[inline(always)]
+    pub fn max_len(&self) -> Option<MaxLen> {
+        self.extras.as_ref().and_then(|extras| extras.max_len)
+    }
+
+    /// Sets or removes the maximum length of the buffer
+    ///
+    /// [`GrowingGapBuf::insert`] and [`GrowingGapBuf::insert_many`] as well as their `try_*`
+    /// variants handle edits exceeding it according to its [`OverflowPolicy`]. When truncating,
+    /// the items that do not fit are dropped and the rest of the iterator is not consumed. When
+    /// rejecting, [`GrowingGapBuf::insert_many`] removes the items it inserted before failing.
+    ///
+    /// The [`ContextGrower`] never grows the capacity past the maximum length and the gap is
+    /// shrunk right away if the capacity already exceeds it. Gaps grown explicitly with
+    /// [`GrowingGapBuf::try_grow_gap`] are not limited.
+    pub fn set_max_len(&mut self, max_len: Option<MaxLen>) {
+        if max_len.is_none() && self.extras.is_none() {
+            return;
+        }
+        self.extras.get_or_insert_with(Default::default).max_len = max_len;
+        if let Some(max_len) = max_len {
+            self.raw
+                .shrink_gap(max_len.excess_gap(self.raw.len(), self.raw.gap_len()));
+        }
+    }
+
+    /// Panics if any of the internal invariants of the buffer do not hold
+    ///
+    /// The buffer must describe a single allocation with the gap between both parts. Meant for
+    /// tests, see the [`testing`](crate::testing) module.
+    #[inline]
+    pub fn check_invariants(&self) {
+        self.raw.check_invariants();
+    }
+
+    /// Grows the gap by exactly the provided value
+    ///
+    /// Returns an error instead of aborting if the allocation fails, in which case the buffer is
+    /// left unchanged.
+    #[inline]
+    pub fn try_grow_gap(&mut self, by: usize) -> Result<(), TryReserveError> {
+        self.raw.try_grow_gap(by)
+    }
+
+    /// Reserves space for at least `additional` more T's in the gap
+    ///
+    /// Same as [`Vec::try_reserve`] but the extra capacity is decided by the [`ContextGrower`] in the
+    /// same way as the inserts. Does nothing if the gap is already large enough.
+    ///
+    /// The buffer is left unchanged if an error is returned.
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let at = self.raw.start_len();
+        let additional = self.max_len().map_or(additional, |max_len| {
+            max_len.reservable(self.len(), additional)
+        });
+        self.prepare(OpKind::Reserve, at..at, additional, true)
+    }
+
+    /// Asks the [`ContextGrower`] what to do with the gap before an operation that replaces the
+    /// provided range with `inserted` T's, and applies the decision
+    ///
+    /// If the gap has to grow, its start is positioned at the end of the range. The buffer is
+    /// left unchanged if an error is returned.
+    fn prepare(
+        &mut self,
+        kind: OpKind,
+        r: Range<usize>,
+        inserted: usize,
+        fallible: bool,
+    ) -> Result<(), TryReserveError> {
+        let requested = inserted.saturating_sub(r.len());
+        let [start, end] = self.raw.get_parts();
+        let decision = self.grower.decide(&GapContext {
+            kind,
+            start,
+            end,
+            position: r.start,
+            requested,
+            removed: r.len().saturating_sub(inserted),
+            gap_len: self.raw.gap_len(),
+            history: self.history,
+        });
+        let decision = match self.max_len() {
+            Some(max_len) => max_len.cap(decision, self.raw.len(), self.raw.gap_len()),
+            None => decision,
+        };
+        self.raw
+            .apply_decision(decision, requested, r.end, fallible)?;
+        self.history.record(kind, r.start);
+        Ok(())
+    }
+
+    /// Returns how many of `inserted` T's can be inserted without exceeding the maximum length
+    #[inline(always)]
+    fn fit(&self, inserted: usize) -> Result<usize, EditError> {
+        self.max_len().map_or(Ok(inserted), |max_len| {
+            max_len.fit(self.raw.len(), 0, inserted)
+        })
+    }
+
+    /// Returns how many more T's can be inserted before reaching the maximum length
+    #[inline(always)]
+    pub(super) fn room(&self) -> usize {
+        self.max_len().map_or(usize::MAX, |max_len| {
+            max_len.len.saturating_sub(self.raw.len())
+        })
     }
 
-    /// See [`RawGapBuf::realloc_gap_at`]
-    pub(crate) fn grow_gap_at(&mut self, by: usize, at: usize) {
-        self.raw.grow_gap_at(by, at);
+    /// Returns an error if at least `hint` T's are about to be inserted and the maximum length
+    /// rejects them
+    #[inline]
+    fn check_hint(&self, hint: usize) -> Result<(), EditError> {
+        match self.max_len() {
+            Some(max_len) if max_len.policy == OverflowPolicy::Reject && hint > self.room() => {
+                Err(EditError::CapacityExceeded {
+                    needed: self.raw.len().saturating_add(hint),
+                    capacity: max_len.len,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Handles an item that does not fit in the maximum length, with at least `remaining` T's
+    /// left after it
+    ///
+    /// Returns an error if the maximum length rejects the edit, in which case the caller has to
+    /// undo the T's it inserted.
+    fn overflow(&self, remaining: usize) -> Result<(), EditError> {
+        let Some(max_len) = self.max_len() else {
+            return Ok(());
+        };
+        match max_len.policy {
+            OverflowPolicy::Truncate => Ok(()),
+            OverflowPolicy::Reject => {
+                // the length before the edit plus every item seen so far
+                let needed = (self.raw.len() + 1).saturating_add(remaining);
+                Err(EditError::CapacityExceeded {
+                    needed,
+                    capacity: max_len.len,
+                })
+            }
+        }
+    }
+
+    /// Drops the `inserted` T's right before the gap and restores the gap start, gap length and
+    /// history the buffer had before inserting them
+    ///
+    /// Unlike removing the T's, the [`ContextGrower`] is not asked about the gap. The gap is only
+    /// ever grown while inserting, so restoring its length shrinks it back.
+    fn undo_insert(&mut self, inserted: usize, gap_start: usize, gap_len: usize, history: History) {
+        // SAFETY: the inserted T's are now in the gap and are never read again
+        unsafe { self.raw.shrink_start(inserted).drop_in_place() };
+        self.raw.move_gap_start_to(gap_start);
+        self.raw.shrink_gap(self.raw.gap_len() - gap_len);
+        self.history = history;
+    }
+
+    /// Same as [`GrowingGapBuf::prepare`] but aborts if the allocation fails
+    #[inline]
+    fn prepare_infallible(&mut self, kind: OpKind, r: Range<usize>, inserted: usize) {
+        self.prepare(kind, r, inserted, false)
+            .unwrap_or_else(|_| unreachable!("infallible reservation never returns an error"));
     }
 }
 
-impl<T, G: Grower<[T]>> Drop for GrowingGapBuf<T, G> {
+impl<G: ContextGrower<[u8]>, A: Allocator> GrowingGapBuf<u8, G, A> {
+    /// Returns the longest contiguous slice starting at the provided position
+    ///
+    /// The slice ends at the gap or at the end of the buffer, and the gap is never moved. Reading
+    /// from the end of the returned slice gives the next chunk, which allows consuming the whole
+    /// buffer without copying. At [`GrowingGapBuf::len`] an empty slice is returned. Use
+    /// [`GrowingGapBuf::point_at`] if the row and column of the position are needed.
+    ///
+    /// Returns [`None`] if the position is out of bounds.
+    #[inline]
+    pub fn chunk_at(&self, at: usize) -> Option<&[u8]> {
+        let [start, end] = self.raw.get_parts();
+        match at.checked_sub(start.len()) {
+            Some(at) => end.get(at..),
+            None => Some(&start[at..]),
+        }
+    }
+
+    /// Returns the row and column of the provided position
+    ///
+    /// Rows are separated by `\n` bytes. Takes time proportional to the position.
+    ///
+    /// # Panics
+    /// If the provided position is greater than [`GrowingGapBuf::len`].
+    #[inline]
+    pub fn point_at(&self, at: usize) -> Point {
+        assert!(self.len() >= at, "position should never be out of bounds");
+        point_of(self.raw.get_parts(), at)
+    }
+}
+
+impl<T, G: ContextGrower<[T]>, A: Allocator> Drop for GrowingGapBuf<T, G, A> {
     fn drop(&mut self) {
         // SAFETY: after calling this function self cannot be reused
         // it is safe to drop the inner values
@@ -241,12 +713,27 @@ mod tests {
     use rstest::rstest;
     use rstest_reuse::apply;
 
-    use crate::grower::test_utils::*;
+    use crate::{
+        error::EditError,
+        gapstr::Point,
+        grower::{DefaultGrower, Grower, History},
+        raw_gap_buf::RawGapBuf,
+        testing::*,
+    };
 
     use super::GrowingGapBuf;
 
     type GapBuf = GrowingGapBuf<String, TestGrower>;
 
+    #[test]
+    fn size() {
+        // the maximum length only takes a pointer until used
+        assert_eq!(
+            size_of::<GrowingGapBuf<String, DefaultGrower>>(),
+            size_of::<RawGapBuf<String>>() + size_of::<History>() + size_of::<usize>()
+        );
+    }
+
     fn fill_gap_buf<G: Grower<[String]>>(gap_buf: &mut GrowingGapBuf<String, G>) {
         for (i, item) in ["1", "2", "3", "4", "5", "6"]
             .map(String::from)
@@ -299,6 +786,124 @@ mod tests {
         );
     }
 
+    #[apply(grower_template)]
+    fn remove(#[case] g: TestGrower) {
+        let mut s_buf = GapBuf::with_grower(g);
+        fill_gap_buf(&mut s_buf);
+
+        assert_eq!(s_buf.remove(2), "3");
+        assert_eq!(s_buf.get_range(..).unwrap(), ["1", "2", "4", "5", "6"]);
+
+        // gap is already at the removed position
+        assert_eq!(s_buf.remove(2), "4");
+        assert_eq!(s_buf.remove(0), "1");
+        assert_eq!(s_buf.remove(2), "6");
+        assert_eq!(s_buf.get_range(..).unwrap(), ["2", "5"]);
+        assert_eq!(s_buf.len(), 2);
+    }
+
+    #[apply(grower_template)]
+    #[should_panic]
+    fn remove_panics(#[case] g: TestGrower) {
+        let mut s_buf = GapBuf::with_grower(g);
+        s_buf.remove(0);
+    }
+
+    #[apply(grower_template)]
+    fn try_edit(#[case] g: TestGrower) {
+        let mut s_buf = GapBuf::with_grower(g);
+        fill_gap_buf(&mut s_buf);
+
+        assert_eq!(
+            s_buf.try_insert(7, String::from("7")),
+            Err(EditError::OutOfBounds { at: 7, len: 6 })
+        );
+        assert_eq!(
+            s_buf.try_remove(6),
+            Err(EditError::OutOfBounds { at: 6, len: 6 })
+        );
+        let gap_len = s_buf.gap_len();
+        assert_eq!(
+            s_buf.try_shrink_gap(gap_len + 1),
+            Err(EditError::OutOfBounds {
+                at: gap_len + 1,
+                len: gap_len
+            })
+        );
+
+        assert_eq!(s_buf.try_insert(6, String::from("7")), Ok(()));
+        assert_eq!(s_buf.try_remove(0).as_deref(), Ok("1"));
+        assert_eq!(s_buf.try_shrink_gap(s_buf.gap_len()), Ok(()));
+        assert_eq!(s_buf.gap_len(), 0);
+        assert_eq!(s_buf.get_range(..).unwrap(), ["2", "3", "4", "5", "6", "7"]);
+    }
+
+    #[apply(grower_template)]
+    fn try_reserve(#[case] g: TestGrower) {
+        let mut s_buf = GapBuf::with_grower(g);
+        fill_gap_buf(&mut s_buf);
+        s_buf.move_gap_start_to(2);
+        let gap_len = s_buf.gap_len();
+
+        assert!(s_buf.try_grow_gap(usize::MAX).is_err());
+        assert!(s_buf.try_reserve(usize::MAX).is_err());
+        assert_eq!(s_buf.gap_len(), gap_len);
+        assert_eq!(s_buf.get_range(..).unwrap().as_parts()[0], ["1", "2"]);
+
+        s_buf.try_reserve(gap_len + 10).unwrap();
+        assert!(s_buf.gap_len() >= gap_len + 10);
+        s_buf.try_grow_gap(3).unwrap();
+        assert_eq!(s_buf.get_range(..).unwrap(), ["1", "2", "3", "4", "5", "6"]);
+    }
+
+    #[apply(grower_template)]
+    fn try_insert_many(#[case] g: TestGrower) {
+        /// Reports a size hint that can never be allocated after the first few items
+        struct Greedy(usize);
+        impl Iterator for Greedy {
+            type Item = String;
+            fn next(&mut self) -> Option<String> {
+                self.0 += 1;
+                Some(self.0.to_string())
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                if self.0 < 3 {
+                    (0, None)
+                } else {
+                    (usize::MAX / 2, None)
+                }
+            }
+        }
+
+        let mut s_buf = GapBuf::with_grower(g);
+        fill_gap_buf(&mut s_buf);
+        s_buf.move_gap_start_to(4);
+        let sample = s_buf.clone();
+        let gap_len = s_buf.gap_len();
+
+        // the gap is moved back and shrunk to its previous length
+        assert!(matches!(
+            s_buf.try_insert_many(Greedy(0), 2),
+            Err(EditError::AllocFailed(_))
+        ));
+        assert_eq!(s_buf.get_parts(), sample.get_parts());
+        assert_eq!(s_buf.gap_len(), gap_len);
+        s_buf.check_invariants();
+        assert_eq!(
+            s_buf.try_insert_many(Greedy(0), 7),
+            Err(EditError::OutOfBounds { at: 7, len: 6 })
+        );
+
+        s_buf
+            .try_insert_many(["a", "b"].map(String::from).into_iter(), 6)
+            .unwrap();
+        assert_eq!(
+            s_buf.get_range(..).unwrap(),
+            ["1", "2", "3", "4", "5", "6", "a", "b"]
+        );
+    }
+
     #[apply(grower_template)]
     fn drain(#[case] g: TestGrower) {
         let mut s_buf = GapBuf::with_grower(g);
@@ -392,18 +997,22 @@ mod tests {
         fill_gap_buf(&mut s_buf);
 
         assert_eq!(
-            s_buf.get_range(0..6).unwrap(),
+            s_buf.get_range(0..6).unwrap().as_parts(),
             [["1", "2", "3", "4", "5", "6"].as_slice(), &[]]
         );
 
-        assert_eq!(s_buf.get_range(1..3).unwrap(), [["2", "3"].as_slice(), &[]]);
+        assert_eq!(
+            s_buf.get_range(1..3).unwrap().as_parts(),
+            [["2", "3"].as_slice(), &[]]
+        );
 
         s_buf.raw.move_gap_start_to(1);
 
         assert_eq!(
-            s_buf.get_range(0..3).unwrap(),
+            s_buf.get_range(0..3).unwrap().as_parts(),
             [["1"].as_slice(), ["2", "3"].as_slice()]
         );
+        assert_eq!(s_buf.get_range(0..3).unwrap(), ["1", "2", "3"]);
 
         assert_eq!(s_buf.get_range(0..7), None);
     }
@@ -426,4 +1035,22 @@ mod tests {
 
         assert_eq!(s_buf.get_range(0..7), None);
     }
+
+    #[test]
+    fn chunk_at() {
+        let mut buf = GrowingGapBuf::<u8, DefaultGrower>::new();
+        buf.insert_many(b"ab\ncd\ne".iter().copied(), 0);
+        drop(buf.drain(3..5));
+        buf.insert_many(b"xyz".iter().copied(), 3);
+        assert_eq!(buf.raw.get_parts(), [b"ab\nxyz".as_slice(), b"\ne"]);
+
+        assert_eq!(buf.chunk_at(1), Some(b"b\nxyz".as_slice()));
+        assert_eq!(buf.chunk_at(6), Some(b"\ne".as_slice()));
+        assert_eq!(buf.chunk_at(8), Some(b"".as_slice()));
+        assert_eq!(buf.chunk_at(9), None);
+        assert_eq!(buf.point_at(1), Point::new(0, 1));
+        assert_eq!(buf.point_at(6), Point::new(1, 3));
+        assert_eq!(buf.point_at(8), Point::new(2, 1));
+        assert_eq!(buf.raw.get_parts(), [b"ab\nxyz".as_slice(), b"\ne"]);
+    }
 }
@@ -0,0 +1,404 @@
+use crate::{
+    allocator::{Allocator, Global},
+    error::EditError,
+    grower::ContextGrower,
+};
+
+use super::GrowingGapBuf;
+
+/// A cursor over a [`GrowingGapBuf`]
+///
+/// Similar to the cursors of a [`LinkedList`](alloc::collections::LinkedList), a cursor always
+/// rests on an item or on the "ghost" position after the last item. Moving past either end of the
+/// buffer wraps around through the "ghost" position.
+pub struct Cursor<'a, T, G: ContextGrower<[T]>, A: Allocator = Global> {
+    index: usize,
+    buf: &'a GrowingGapBuf<T, G, A>,
+}
+
+impl<T, G: ContextGrower<[T]>, A: Allocator> Clone for Cursor<'_, T, G, A> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            index: self.index,
+            buf: self.buf,
+        }
+    }
+}
+
+impl<'a, T, G: ContextGrower<[T]>, A: Allocator> Cursor<'a, T, G, A> {
+    #[inline(always)]
+    pub(crate) fn new(buf: &'a GrowingGapBuf<T, G, A>, index: usize) -> Self {
+        Self { index, buf }
+    }
+
+    /// Returns the index of the cursor, or [`None`] if it is on the "ghost" position
+    #[inline(always)]
+    pub fn index(&self) -> Option<usize> {
+        (self.index < self.buf.len()).then_some(self.index)
+    }
+
+    /// Moves the cursor to the next item
+    ///
+    /// If the cursor is on the "ghost" position, it is moved to the first item.
+    #[inline]
+    pub fn move_next(&mut self) {
+        self.index = next_index(self.index, self.buf.len());
+    }
+
+    /// Moves the cursor to the previous item
+    ///
+    /// If the cursor is on the first item, it is moved to the "ghost" position.
+    #[inline]
+    pub fn move_prev(&mut self) {
+        self.index = prev_index(self.index, self.buf.len());
+    }
+
+    /// Moves the cursor to the provided position
+    ///
+    /// # Panics
+    /// If the provided position is greater than [`GrowingGapBuf::len`].
+    #[inline]
+    pub fn seek(&mut self, to: usize) {
+        assert!(
+            self.buf.len() >= to,
+            "cursor position should never be out of bounds"
+        );
+        self.index = to;
+    }
+
+    /// Returns the item the cursor is pointing at
+    ///
+    /// Returns [`None`] if the cursor is on the "ghost" position.
+    #[inline(always)]
+    pub fn current(&self) -> Option<&'a T> {
+        self.buf.get(self.index)
+    }
+
+    /// Returns the item after the cursor without moving it
+    #[inline(always)]
+    pub fn peek_next(&self) -> Option<&'a T> {
+        self.buf.get(next_index(self.index, self.buf.len()))
+    }
+
+    /// Returns the item before the cursor without moving it
+    #[inline(always)]
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        self.buf.get(prev_index(self.index, self.buf.len()))
+    }
+}
+
+/// A cursor over a [`GrowingGapBuf`] with editing operations
+///
+/// Same as [`Cursor`] but allows inserting and removing items around the cursor.
+///
+/// The gap is moved to the cursor lazily, only when an edit is performed. Moving the cursor
+/// does not shift any items, and an edit only shifts the items between the gap and the cursor.
+/// This makes a sequence of edits around the cursor O(1) amortized.
+pub struct CursorMut<'a, T, G: ContextGrower<[T]>, A: Allocator = Global> {
+    index: usize,
+    buf: &'a mut GrowingGapBuf<T, G, A>,
+}
+
+impl<'a, T, G: ContextGrower<[T]>, A: Allocator> CursorMut<'a, T, G, A> {
+    #[inline(always)]
+    pub(crate) fn new(buf: &'a mut GrowingGapBuf<T, G, A>, index: usize) -> Self {
+        Self { index, buf }
+    }
+
+    /// Returns a read only [`Cursor`] at the same position
+    #[inline(always)]
+    pub fn as_cursor(&self) -> Cursor<'_, T, G, A> {
+        Cursor::new(self.buf, self.index)
+    }
+
+    /// Returns the index of the cursor, or [`None`] if it is on the "ghost" position
+    #[inline(always)]
+    pub fn index(&self) -> Option<usize> {
+        (self.index < self.buf.len()).then_some(self.index)
+    }
+
+    /// Moves the cursor to the next item
+    ///
+    /// If the cursor is on the "ghost" position, it is moved to the first item.
+    #[inline]
+    pub fn move_next(&mut self) {
+        self.index = next_index(self.index, self.buf.len());
+    }
+
+    /// Moves the cursor to the previous item
+    ///
+    /// If the cursor is on the first item, it is moved to the "ghost" position.
+    #[inline]
+    pub fn move_prev(&mut self) {
+        self.index = prev_index(self.index, self.buf.len());
+    }
+
+    /// Moves the cursor to the provided position
+    ///
+    /// # Panics
+    /// If the provided position is greater than [`GrowingGapBuf::len`].
+    #[inline]
+    pub fn seek(&mut self, to: usize) {
+        assert!(
+            self.buf.len() >= to,
+            "cursor position should never be out of bounds"
+        );
+        self.index = to;
+    }
+
+    /// Returns the item the cursor is pointing at
+    ///
+    /// Returns [`None`] if the cursor is on the "ghost" position.
+    #[inline(always)]
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.buf.get_mut(self.index)
+    }
+
+    /// Returns the item after the cursor without moving it
+    #[inline(always)]
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.buf.get_mut(next_index(self.index, self.buf.len()))
+    }
+
+    /// Returns the item before the cursor without moving it
+    #[inline(always)]
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        self.buf.get_mut(prev_index(self.index, self.buf.len()))
+    }
+
+    /// Inserts an item before the cursor
+    ///
+    /// If the cursor is on the "ghost" position, the item is inserted at the end of the buffer.
+    /// The cursor keeps pointing at the same item.
+    ///
+    /// Returns false and drops the item if the buffer is at its
+    /// [`MaxLen`](crate::limit::MaxLen), whatever its policy.
+    #[inline]
+    pub fn insert_before(&mut self, val: T) -> bool {
+        if self.buf.room() == 0 {
+            return false;
+        }
+
+        self.buf.insert(self.index, val);
+        self.index += 1;
+        true
+    }
+
+    /// Inserts an item after the cursor
+    ///
+    /// If the cursor is on the "ghost" position, the item is inserted at the start of the buffer.
+    /// The cursor keeps pointing at the same item.
+    ///
+    /// Returns false and drops the item if the buffer is at its
+    /// [`MaxLen`](crate::limit::MaxLen), whatever its policy.
+    #[inline]
+    pub fn insert_after(&mut self, val: T) -> bool {
+        if self.buf.room() == 0 {
+            return false;
+        }
+
+        if self.index == self.buf.len() {
+            self.buf.insert(0, val);
+            self.index += 1;
+        } else {
+            self.buf.insert(self.index + 1, val);
+        }
+        true
+    }
+
+    /// Same as [`CursorMut::insert_before`] but returns an error instead of returning false or
+    /// aborting
+    ///
+    /// The buffer and the cursor are left unchanged if an error is returned.
+    ///
+    /// # Errors
+    /// See [`GrowingGapBuf::try_insert`].
+    #[inline]
+    pub fn try_insert_before(&mut self, val: T) -> Result<(), EditError> {
+        let len = self.buf.len();
+        self.buf.try_insert(self.index, val)?;
+        // the item is dropped instead if it is truncated by the maximum length
+        self.index += self.buf.len() - len;
+        Ok(())
+    }
+
+    /// Same as [`CursorMut::insert_after`] but returns an error instead of returning false or
+    /// aborting
+    ///
+    /// The buffer and the cursor are left unchanged if an error is returned.
+    ///
+    /// # Errors
+    /// See [`GrowingGapBuf::try_insert`].
+    #[inline]
+    pub fn try_insert_after(&mut self, val: T) -> Result<(), EditError> {
+        let len = self.buf.len();
+        if self.index == len {
+            self.buf.try_insert(0, val)?;
+            self.index += self.buf.len() - len;
+        } else {
+            self.buf.try_insert(self.index + 1, val)?;
+        }
+        Ok(())
+    }
+
+    /// Removes and returns the item the cursor is pointing at
+    ///
+    /// The cursor is moved to the next item, or the "ghost" position if the last item was
+    /// removed. Returns [`None`] if the cursor is on the "ghost" position.
+    #[inline]
+    pub fn remove_current(&mut self) -> Option<T> {
+        (self.index < self.buf.len()).then(|| self.buf.remove(self.index))
+    }
+}
+
+#[inline(always)]
+fn next_index(index: usize, len: usize) -> usize {
+    if index >= len {
+        0
+    } else {
+        index + 1
+    }
+}
+
+#[inline(always)]
+fn prev_index(index: usize, len: usize) -> usize {
+    if index == 0 {
+        len
+    } else {
+        index - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use rstest_reuse::apply;
+
+    use crate::{error::EditError, limit::MaxLen, testing::*};
+
+    use super::super::GrowingGapBuf;
+
+    type GapBuf = GrowingGapBuf<String, TestGrower>;
+
+    fn fill_gap_buf(s_buf: &mut GapBuf) {
+        s_buf.insert_many(["1", "2", "3"].map(String::from).into_iter(), 0);
+    }
+
+    #[apply(grower_template)]
+    fn cursor_move(g: TestGrower) {
+        let mut s_buf = GapBuf::with_grower(g);
+        fill_gap_buf(&mut s_buf);
+
+        let mut cursor = s_buf.cursor_at(0);
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current().unwrap(), "1");
+        assert_eq!(cursor.peek_prev(), None);
+        assert_eq!(cursor.peek_next().unwrap(), "2");
+
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current().unwrap(), "3");
+        assert_eq!(cursor.peek_next(), None);
+
+        // ghost position
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next().unwrap(), "1");
+        assert_eq!(cursor.peek_prev().unwrap(), "3");
+
+        // wraps around
+        cursor.move_next();
+        assert_eq!(cursor.current().unwrap(), "1");
+        cursor.move_prev();
+        assert_eq!(cursor.index(), None);
+        cursor.move_prev();
+        assert_eq!(cursor.current().unwrap(), "3");
+
+        cursor.seek(1);
+        assert_eq!(cursor.current().unwrap(), "2");
+    }
+
+    #[apply(grower_template)]
+    #[should_panic]
+    fn cursor_seek_panics(g: TestGrower) {
+        let mut s_buf = GapBuf::with_grower(g);
+        fill_gap_buf(&mut s_buf);
+        s_buf.cursor_at(0).seek(4);
+    }
+
+    #[apply(grower_template)]
+    fn cursor_mut_insert(g: TestGrower) {
+        let mut s_buf = GapBuf::with_grower(g);
+        fill_gap_buf(&mut s_buf);
+
+        let mut cursor = s_buf.cursor_mut_at(1);
+        cursor.insert_before(String::from("a"));
+        assert_eq!(cursor.current().unwrap(), "2");
+        cursor.insert_after(String::from("b"));
+        assert_eq!(cursor.current().unwrap(), "2");
+        cursor.move_next();
+        assert_eq!(cursor.current().unwrap(), "b");
+
+        // ghost inserts to the end with insert_before and to the start with insert_after
+        cursor.seek(5);
+        cursor.insert_before(String::from("c"));
+        assert_eq!(cursor.index(), None);
+        cursor.insert_after(String::from("d"));
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.peek_next().unwrap(), "d");
+
+        assert_eq!(
+            s_buf.get_range(..).unwrap(),
+            ["d", "1", "a", "2", "b", "3", "c"]
+        );
+    }
+
+    #[apply(grower_template)]
+    fn cursor_mut_remove(g: TestGrower) {
+        let mut s_buf = GapBuf::with_grower(g);
+        fill_gap_buf(&mut s_buf);
+
+        let mut cursor = s_buf.cursor_mut_at(1);
+        assert_eq!(cursor.remove_current().unwrap(), "2");
+        assert_eq!(cursor.current().unwrap(), "3");
+        assert_eq!(cursor.remove_current().unwrap(), "3");
+        assert_eq!(cursor.remove_current(), None);
+        cursor.move_prev();
+        *cursor.current().unwrap() = String::from("4");
+        cursor.insert_before(String::from("5"));
+        assert_eq!(cursor.as_cursor().peek_prev().unwrap(), "5");
+
+        assert_eq!(s_buf.get_range(..).unwrap(), ["5", "4"]);
+    }
+
+    #[apply(grower_template)]
+    fn cursor_mut_max_len(g: TestGrower) {
+        let mut s_buf = GapBuf::with_grower(g);
+        fill_gap_buf(&mut s_buf);
+        s_buf.set_max_len(Some(MaxLen::reject(4)));
+
+        let mut cursor = s_buf.cursor_mut_at(1);
+        cursor.try_insert_before(String::from("a")).unwrap();
+        assert_eq!(cursor.index(), Some(2));
+        let full = Err(EditError::CapacityExceeded {
+            needed: 5,
+            capacity: 4,
+        });
+        assert_eq!(cursor.try_insert_before(String::from("b")), full);
+        assert_eq!(cursor.try_insert_after(String::from("b")), full);
+        assert!(!cursor.insert_before(String::from("b")));
+        assert!(!cursor.insert_after(String::from("b")));
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.current().unwrap(), "2");
+
+        // truncated items are dropped without moving the cursor
+        s_buf.set_max_len(Some(MaxLen::truncate(4)));
+        let mut cursor = s_buf.cursor_mut_at(4);
+        cursor.try_insert_after(String::from("b")).unwrap();
+        assert!(!cursor.insert_after(String::from("b")));
+        assert_eq!(cursor.index(), None);
+        assert_eq!(s_buf.get_range(..).unwrap(), ["1", "a", "2", "3"]);
+    }
+}
@@ -1,4 +1,4 @@
-use std::{iter::FusedIterator, marker::PhantomData, ptr::NonNull};
+use core::{iter::FusedIterator, marker::PhantomData, ptr::NonNull};
 
 #[derive(Debug)]
 pub struct Drain<'a, T> {
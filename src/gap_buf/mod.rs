@@ -1,8 +1,10 @@
 use crate::grower::DefaultGrower;
 
 mod buf;
+mod cursor;
 mod drain;
 
 pub use buf::GrowingGapBuf;
+pub use cursor::{Cursor, CursorMut};
 pub use drain::Drain;
 pub type GapBuf<T> = GrowingGapBuf<T, DefaultGrower>;
@@ -1,50 +1,129 @@
-use core::str;
-use std::{
+#[cfg(doc)]
+use alloc::string::String;
+use alloc::{boxed::Box, collections::TryReserveError};
+use core::{
     cmp::Ordering,
     ops::{Range, RangeBounds},
-    str::{from_utf8_unchecked, from_utf8_unchecked_mut},
+    str::{self, from_utf8_unchecked, from_utf8_unchecked_mut},
 };
 
+use self::{decorations::Layers, observe::Observers};
+#[cfg(feature = "stats")]
+use crate::stats::{BufStats, EventHook};
 use crate::{
-    grower::{DefaultGrower, Grower},
+    allocator::{Allocator, Global},
+    error::EditError,
+    grower::{ContextGrower, DefaultGrower, GapContext, History, OpKind},
+    limit::MaxLen,
     raw_gap_buf::RawGapBuf,
-    utils::{get_range, u8_is_char_boundary},
+    utils::{get_range, try_get_range, u8_is_char_boundary},
+    view::GapStr,
 };
 
+mod cursor;
+mod decorations;
+mod grapheme;
+mod lines;
+mod observe;
+mod protect;
+mod small;
+mod width;
+mod word;
+mod wrap;
+
+pub use cursor::EditCursor;
+pub use decorations::{Decoration, DecorationId, DecorationIter, Decorations, LayerId};
+pub use grapheme::Graphemes;
+pub use observe::{InputEdit, Observer, ObserverId, Point};
+pub use protect::ProtectedId;
+pub use small::SmallGapString;
+pub use word::{CharClass, WordMode, Words};
+pub use wrap::{SoftWrap, VisualLine, VisualLines};
+
 pub type GapString = GrowingGapString<DefaultGrower>;
 
-#[derive(Clone)]
-pub struct GrowingGapString<G: Grower<str>> {
-    buf: RawGapBuf<u8>,
+pub struct GrowingGapString<G: ContextGrower<str>, A: Allocator = Global> {
+    buf: RawGapBuf<u8, A>,
     grower: G,
+    history: History,
+    /// Allocated the first time an observer, a protected region, decorations or a maximum
+    /// length is set, so strings that do not use them stay small
+    extras: Option<Box<Extras>>,
+}
+
+/// The rarely used state of a [`GrowingGapString`]
+#[derive(Default)]
+struct Extras {
+    observers: Observers,
+    protected: Decorations<()>,
+    layers: Layers,
+    max_len: Option<MaxLen>,
+}
+
+impl Extras {
+    /// Shifts the protected regions and the attached decorations, and notifies the observers
+    /// after replacing the provided range with `inserted` bytes
+    #[inline(always)]
+    fn finish_edit(
+        extras: &mut Option<Box<Self>>,
+        r: Range<usize>,
+        inserted: usize,
+        edit: Option<InputEdit>,
+    ) {
+        if let Some(extras) = extras {
+            extras.layers.shift(r.clone(), inserted);
+            protect::shift(&mut extras.protected, r, inserted);
+            extras.observers.notify(edit);
+        }
+    }
+}
+
+impl Clone for Extras {
+    /// Clones the protected regions, the attached decorations and the maximum length without the
+    /// observers
+    fn clone(&self) -> Self {
+        Self {
+            observers: Observers::new(),
+            protected: self.protected.clone(),
+            layers: self.layers.clone(),
+            max_len: self.max_len,
+        }
+    }
 }
 
-impl<G: Grower<str> + Default> Default for GrowingGapString<G> {
+impl<G: ContextGrower<str> + Clone, A: Allocator + Clone> Clone for GrowingGapString<G, A> {
+    /// Clones the string, its protected regions and its attached decorations without its
+    /// observers
+    fn clone(&self) -> Self {
+        Self {
+            buf: self.buf.clone(),
+            grower: self.grower.clone(),
+            history: self.history,
+            extras: self.extras.clone(),
+        }
+    }
+}
+
+impl<G: ContextGrower<str> + Default> Default for GrowingGapString<G> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<G: Grower<str>> GrowingGapString<G> {
+impl<G: ContextGrower<str>> GrowingGapString<G> {
     /// Initialize an empty [`GrowingGapString`]
     #[inline]
     pub fn new() -> Self
     where
         G: Default,
     {
-        Self {
-            buf: RawGapBuf::new(),
-            grower: Default::default(),
-        }
+        Self::from_raw(RawGapBuf::new(), Default::default())
     }
 
-    /// Initialize an empty [`GrowingGapString`] with a [`Grower`]
+    /// Initialize an empty [`GrowingGapString`] with a [`ContextGrower`]
     #[inline]
     pub const fn with_grower(grower: G) -> Self {
-        Self {
-            buf: RawGapBuf::new(),
-            grower,
-        }
+        Self::from_raw(RawGapBuf::new(), grower)
     }
 
     /// Initialize a [`GrowingGapString`] using multiple string slices and a gap size.
@@ -53,20 +132,54 @@ impl<G: Grower<str>> GrowingGapString<G> {
     where
         G: Default,
     {
-        let grower = G::default();
+        // SAFETY: str and [u8] have the same alignment and layout
+        let buf = unsafe {
+            RawGapBuf::new_with_slice(
+                core::mem::transmute::<&[&str], &[&[u8]]>(start),
+                gap_size,
+                core::mem::transmute::<&[&str], &[&[u8]]>(end),
+            )
+        };
+        Self::from_raw(buf, G::default())
+    }
+}
+
+impl<G: ContextGrower<str>, A: Allocator> GrowingGapString<G, A> {
+    /// Initialize an empty [`GrowingGapString`] that allocates in the provided allocator
+    #[inline]
+    pub fn new_in(alloc: A) -> Self
+    where
+        G: Default,
+    {
+        Self::with_grower_in(Default::default(), alloc)
+    }
+
+    /// Initialize an empty [`GrowingGapString`] with a [`ContextGrower`] that allocates in the provided
+    /// allocator
+    #[inline]
+    pub const fn with_grower_in(grower: G, alloc: A) -> Self {
+        Self::from_raw(RawGapBuf::new_in(alloc), grower)
+    }
+
+    /// Initialize a [`GrowingGapString`] from a buffer and a [`ContextGrower`]
+    ///
+    /// Both parts of the buffer must be valid UTF-8.
+    #[inline(always)]
+    pub(crate) const fn from_raw(buf: RawGapBuf<u8, A>, grower: G) -> Self {
         Self {
-            // SAFETY: str and [u8] have the same alignment and layout
-            buf: unsafe {
-                RawGapBuf::new_with_slice(
-                    core::mem::transmute::<&[&str], &[&[u8]]>(start),
-                    gap_size,
-                    core::mem::transmute::<&[&str], &[&[u8]]>(end),
-                )
-            },
+            buf,
             grower,
+            history: History::new(),
+            extras: None,
         }
     }
 
+    /// Returns a reference to the underlying allocator
+    #[inline(always)]
+    pub fn allocator(&self) -> &A {
+        self.buf.allocator()
+    }
+
     /// Returns the total length excluding the gap
     #[inline(always)]
     pub fn len(&self) -> usize {
@@ -87,16 +200,18 @@ impl<G: Grower<str>> GrowingGapString<G> {
 
     /// Same as [`str::get`] but for gap buffers
     ///
+    /// Returns a [`GapStr`] over the string slices before and after the gap.
+    ///
     /// Returns [`None`] if the range is out of bounds or is not on a char boundary.
     #[inline]
-    pub fn get<RB: RangeBounds<usize>>(&self, r: RB) -> Option<[&str; 2]> {
+    pub fn get<RB: RangeBounds<usize>>(&self, r: RB) -> Option<GapStr<'_>> {
         let r = get_range(self.buf.len(), r)?;
         if !self.is_get_char_boundary(r.start..r.end) {
             return None;
         }
 
         let [start, end] = self.buf.get_range(r)?;
-        unsafe { Some([to_str(start), to_str(end)]) }
+        unsafe { Some(GapStr::new(to_str(start), to_str(end))) }
     }
 
     /// Same as [`str::get_mut`] but for gap buffers
@@ -157,6 +272,23 @@ impl<G: Grower<str>> GrowingGapString<G> {
         })
     }
 
+    /// Returns the longest contiguous string slice starting at the provided position
+    ///
+    /// The slice ends at the gap or at the end of the string, and the gap is never moved. Reading
+    /// from the end of the returned slice gives the next chunk, which allows consuming the whole
+    /// string without copying. At [`GrowingGapString::len`] an empty slice is returned. Use
+    /// [`GrowingGapString::point_at`] if the row and column of the position are needed.
+    ///
+    /// Returns [`None`] if the position is out of bounds or not on a char boundary.
+    #[inline]
+    pub fn chunk_at(&self, at: usize) -> Option<&str> {
+        let [start, end] = self.get_parts();
+        match at.checked_sub(start.len()) {
+            Some(at) => end.get(at..),
+            None => start.get(at..),
+        }
+    }
+
     /// Returns both sides of the gap buffer as mutable slices
     #[inline(always)]
     pub fn get_parts_mut(&mut self) -> [&mut str; 2] {
@@ -181,6 +313,101 @@ impl<G: Grower<str>> GrowingGapString<G> {
         )
     }
 
+    /// Asks the [`ContextGrower`] what to do with the gap before an operation that replaces the
+    /// provided range with `inserted` bytes, and applies the decision
+    ///
+    /// If the gap has to grow, its start is positioned at the end of the range. The string is
+    /// left unchanged if an error is returned.
+    fn prepare(
+        &mut self,
+        kind: OpKind,
+        r: Range<usize>,
+        inserted: usize,
+        fallible: bool,
+    ) -> Result<(), TryReserveError> {
+        let requested = inserted.saturating_sub(r.len());
+        // SAFETY: we do not allow the gap to be positioned between char boundaries both parts
+        // are always valid UTF-8 string slice
+        let [start, end] = self.buf.get_parts().map(|s| unsafe { to_str(s) });
+        let decision = self.grower.decide(&GapContext {
+            kind,
+            start,
+            end,
+            position: r.start,
+            requested,
+            removed: r.len().saturating_sub(inserted),
+            gap_len: self.buf.gap_len(),
+            history: self.history,
+        });
+        let decision = match self.max_len() {
+            Some(max_len) => max_len.cap(decision, self.buf.len(), self.buf.gap_len()),
+            None => decision,
+        };
+        self.buf
+            .apply_decision(decision, requested, r.end, fallible)?;
+        self.history.record(kind, r.start);
+        Ok(())
+    }
+
+    /// Same as [`GrowingGapString::prepare`] but aborts if the allocation fails
+    #[inline]
+    fn prepare_infallible(&mut self, kind: OpKind, r: Range<usize>, inserted: usize) {
+        self.prepare(kind, r, inserted, false)
+            .unwrap_or_else(|_| unreachable!("infallible reservation never returns an error"));
+    }
+
+    /// Checks if the provided position is in bounds and on a char boundary
+    #[inline]
+    fn check_position(&self, at: usize) -> Result<(), EditError> {
+        let len = self.len();
+        if at > len {
+            Err(EditError::OutOfBounds { at, len })
+        } else if !self.is_get_char_boundary(at..at) {
+            Err(EditError::NotCharBoundary { at })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Same as [`GrowingGapString::check_position`] but for both ends of a range
+    #[inline]
+    fn check_range<RB: RangeBounds<usize>>(&self, r: RB) -> Result<Range<usize>, EditError> {
+        let r = try_get_range(self.len(), r)?;
+        self.check_position(r.start)?;
+        self.check_position(r.end)?;
+        Ok(r)
+    }
+
+    /// Returns the part of `s` that can replace the provided range without exceeding the
+    /// maximum length
+    ///
+    /// Truncated strings are cut at the closest char boundary.
+    #[inline]
+    fn fit<'s>(&self, r: Range<usize>, s: &'s str) -> Result<&'s str, EditError> {
+        let Some(max_len) = self.max_len() else {
+            return Ok(s);
+        };
+        let mut len = max_len.fit(self.len(), r.len(), s.len())?;
+        while !s.is_char_boundary(len) {
+            len -= 1;
+        }
+        Ok(&s[..len])
+    }
+
+    /// Reserves space for at least `additional` more bytes in the gap
+    ///
+    /// Same as [`String::try_reserve`] but the extra capacity is decided by the [`ContextGrower`] in
+    /// the same way as the inserts. Does nothing if the gap is already large enough.
+    ///
+    /// The string is left unchanged if an error is returned.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let at = self.buf.start_len();
+        let additional = self.max_len().map_or(additional, |max_len| {
+            max_len.reservable(self.len(), additional)
+        });
+        self.prepare(OpKind::Reserve, at..at, additional, true)
+    }
+
     /// Insert a string at the provided position
     ///
     /// # Panics
@@ -191,32 +418,40 @@ impl<G: Grower<str>> GrowingGapString<G> {
             self.is_get_char_boundary(at..at),
             "insertion should always be on a char boundary"
         );
-        // polonius moment
-        // can't use [`GrowingGapString::get_parts`] as borrow checker can't infer that the grower
-        // field and slices are unrelated in regards to mutability.
-        let [start, end] = self.buf.get_parts().map(|s| unsafe {
-            // SAFETY: we do not allow the gap to be positioned between char boundaries both
-            // parts are always valid UTF-8 string slice
-            to_str(s)
-        });
-        if self.buf.gap_len() < s.len() {
-            let new_gap_size = self
-                .grower
-                .base_gap_size(start, end)
-                .min(self.grower.max_gap_size(start, end));
-            self.buf.grow_gap(new_gap_size + s.len());
-        }
-        self.buf.move_gap_start_to(at);
+        self.assert_unprotected(at..at);
+        let s = self.fit(at..at, s).unwrap_or_else(|err| panic!("{err}"));
+        self.prepare_infallible(OpKind::Insert, at..at, s.len());
+        self.insert_prepared(s, at);
+    }
 
+    /// Inserts a string after the gap was prepared to fit it
+    #[inline(always)]
+    fn insert_prepared(&mut self, s: &str, at: usize) {
+        let edit = self.pending_edit(at..at, s);
+        self.buf.move_gap_start_to(at);
         self.buf.grow_start_with_slice(s.as_bytes());
+        Extras::finish_edit(&mut self.extras, at..at, s.len(), edit);
+    }
+
+    /// Same as [`GrowingGapString::insert`] but returns an error instead of panicking
+    ///
+    /// The string is left unchanged if an error is returned.
+    pub fn try_insert(&mut self, s: &str, at: usize) -> Result<(), EditError> {
+        self.check_position(at)?;
+        self.check_protected(at..at)?;
+        let s = self.fit(at..at, s)?;
+        self.prepare(OpKind::Insert, at..at, s.len(), true)?;
+        self.insert_prepared(s, at);
+        Ok(())
     }
 
     /// Equivalent to [`String::drain`] from the standard library
     ///
     /// Returns a string slice to the removed range.
     ///
-    /// Shifts the gap's start position to the end of the range, and returns the string slice at
-    /// the provided range.
+    /// Shifts the gap's start position to the start of the range, and returns the string slice at
+    /// the provided range. If the gap is already at the start or end of the range, no bytes are
+    /// shifted.
     ///
     /// # Panics
     /// If the provided range is out of bounds or the range start is greater than its end.
@@ -225,22 +460,34 @@ impl<G: Grower<str>> GrowingGapString<G> {
         let r = get_range(self.buf.len(), r)
             .expect("range should never be out of bounds when draining");
         assert!(self.is_get_char_boundary(r.start..r.end));
-
-        let [start, end] = self.buf.get_parts().map(|s| unsafe { to_str(s) });
-
-        let max_gap_size = self.grower.max_gap_size(start, end);
-        let gap_len = self.gap_len();
-        if gap_len > max_gap_size {
-            let new_gap_size = self.grower.base_gap_size(start, end).min(max_gap_size);
-            self.shrink_gap(gap_len - new_gap_size);
-        }
-
-        self.buf.move_gap_start_to(r.end);
-        let removed = self.buf.shrink_start_with(r.len());
+        self.assert_unprotected(r.clone());
+
+        let edit = self.pending_edit(r.clone(), "");
+        self.prepare_infallible(OpKind::Remove, r.clone(), 0);
+
+        // avoid moving the gap when it is already right before the range, this is the case for
+        // forward deletes in editors
+        let removed = if self.buf.start_len() == r.start {
+            // SAFETY: the removed bytes are now in the gap and are not accessed by the buffer
+            unsafe { self.buf.shrink_end(r.len()).as_mut() }
+        } else {
+            self.buf.move_gap_start_to(r.end);
+            self.buf.shrink_start_with(r.len())
+        };
+        Extras::finish_edit(&mut self.extras, r, 0, edit);
 
         unsafe { to_str(removed) }
     }
 
+    /// Same as [`GrowingGapString::remove`] but returns an error instead of panicking
+    ///
+    /// The string is left unchanged if an error is returned.
+    pub fn try_remove<RB: RangeBounds<usize>>(&mut self, r: RB) -> Result<&str, EditError> {
+        let r = self.check_range(r)?;
+        self.check_protected(r.clone())?;
+        Ok(self.remove(r))
+    }
+
     /// Equivalent to [`String::replace_range`] from the standard library
     ///
     /// # Panics
@@ -249,6 +496,16 @@ impl<G: Grower<str>> GrowingGapString<G> {
     pub fn replace_range<RB: RangeBounds<usize>>(&mut self, r: RB, s: &str) {
         let r = get_range(self.buf.len(), r).expect("out of bounds range for replace_range");
         assert!(self.is_get_char_boundary(r.start..r.end));
+        self.assert_unprotected(r.clone());
+        let s = self.fit(r.clone(), s).unwrap_or_else(|err| panic!("{err}"));
+        self.prepare_infallible(OpKind::Replace, r.clone(), s.len());
+        self.replace_prepared(r, s);
+    }
+
+    /// Replaces a range after the gap was prepared to fit the replacement
+    #[inline]
+    fn replace_prepared(&mut self, r: Range<usize>, s: &str) {
+        let edit = self.pending_edit(r.clone(), s);
         match r.len().cmp(&s.len()) {
             Ordering::Greater => {
                 self.buf.move_gap_start_to(r.end);
@@ -257,16 +514,6 @@ impl<G: Grower<str>> GrowingGapString<G> {
                 self.buf.shrink_start(r.len() - s.len());
             }
             Ordering::Less => {
-                let needed_space = s.len() - r.len();
-                if self.buf.gap_len() < needed_space {
-                    let [start, end] = self.buf.get_parts().map(|s| unsafe { to_str(s) });
-                    let new_gap_size = self
-                        .grower
-                        .base_gap_size(start, end)
-                        .min(self.grower.max_gap_size(start, end));
-                    self.buf.grow_gap(needed_space + new_gap_size);
-                }
-
                 self.buf.move_gap_start_to(r.end);
                 let start = &mut self.buf.get_parts_mut()[0];
                 let (pre, post) = s.as_bytes().split_at(r.len());
@@ -280,6 +527,29 @@ impl<G: Grower<str>> GrowingGapString<G> {
                     .copy_from_slice(s.as_bytes());
             }
         }
+        Extras::finish_edit(&mut self.extras, r, s.len(), edit);
+    }
+
+    /// Returns the rarely used state of the string, allocating it if needed
+    #[inline]
+    fn extras_mut(&mut self) -> &mut Extras {
+        self.extras.get_or_insert_with(Default::default)
+    }
+
+    /// Same as [`GrowingGapString::replace_range`] but returns an error instead of panicking
+    ///
+    /// The string is left unchanged if an error is returned.
+    pub fn try_replace_range<RB: RangeBounds<usize>>(
+        &mut self,
+        r: RB,
+        s: &str,
+    ) -> Result<(), EditError> {
+        let r = self.check_range(r)?;
+        self.check_protected(r.clone())?;
+        let s = self.fit(r.clone(), s)?;
+        self.prepare(OpKind::Replace, r.clone(), s.len(), true)?;
+        self.replace_prepared(r, s);
+        Ok(())
     }
 
     /// Equivalent of [`String::reserve_exact`] from the standard library
@@ -290,10 +560,18 @@ impl<G: Grower<str>> GrowingGapString<G> {
         self.buf.grow_gap(by);
     }
 
+    /// Same as [`GrowingGapString::grow_gap`] but returns an error instead of aborting if the
+    /// allocation fails
+    ///
+    /// The string is left unchanged if an error is returned.
+    pub fn try_grow_gap(&mut self, by: usize) -> Result<(), TryReserveError> {
+        self.buf.try_grow_gap(by)
+    }
+
     /// Shrink the gap
     ///
     /// This is the equivalent of [`String::shrink_to`] from the standard library. The provided
-    /// [`Grower`] will handle shrinking by default but this method allows you to shrink the gap
+    /// [`ContextGrower`] will handle shrinking by default but this method allows you to shrink the gap
     /// explicitly.
     ///
     /// # Panics
@@ -301,6 +579,81 @@ impl<G: Grower<str>> GrowingGapString<G> {
     pub fn shrink_gap(&mut self, by: usize) {
         self.buf.shrink_gap(by);
     }
+
+    /// Same as [`GrowingGapString::shrink_gap`] but returns an error instead of panicking
+    ///
+    /// Returns [`EditError::OutOfBounds`] with the gap length if the provided value is greater
+    /// than [`GrowingGapString::gap_len`].
+    pub fn try_shrink_gap(&mut self, by: usize) -> Result<(), EditError> {
+        let len = self.gap_len();
+        if by > len {
+            return Err(EditError::OutOfBounds { at: by, len });
+        }
+
+        self.shrink_gap(by);
+        Ok(())
+    }
+
+    /// Returns the maximum length of the string
+    #[inline(always)]
+    pub fn max_len(&self) -> Option<MaxLen> {
+        self.extras.as_ref().and_then(|extras| extras.max_len)
+    }
+
+    /// Sets or removes the maximum length of the string in bytes
+    ///
+    /// [`GrowingGapString::insert`] and [`GrowingGapString::replace_range`] as well as their
+    /// `try_*` variants handle edits exceeding it according to its
+    /// [`OverflowPolicy`](crate::limit::OverflowPolicy). Truncated strings are cut at a char
+    /// boundary. Edits that do not grow the string are always allowed, even if the string is
+    /// already longer than the maximum length.
+    ///
+    /// The [`ContextGrower`] never grows the capacity past the maximum length and the gap is
+    /// shrunk right away if the capacity already exceeds it. Gaps grown explicitly with
+    /// [`GrowingGapString::grow_gap`] are not limited.
+    pub fn set_max_len(&mut self, max_len: Option<MaxLen>) {
+        if max_len.is_none() && self.extras.is_none() {
+            return;
+        }
+        self.extras_mut().max_len = max_len;
+        if let Some(max_len) = max_len {
+            self.shrink_gap(max_len.excess_gap(self.len(), self.gap_len()));
+        }
+    }
+
+    /// Returns the reallocation and gap movement counters of the string
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub fn stats(&self) -> BufStats {
+        self.buf.stats()
+    }
+
+    /// Resets the counters returned by [`GrowingGapString::stats`]
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub fn reset_stats(&mut self) {
+        self.buf.reset_stats();
+    }
+
+    /// Sets a function to be called on every reallocation and gap movement
+    ///
+    /// Clones of the string start without a hook.
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub fn set_event_hook(&mut self, hook: Option<EventHook>) {
+        self.buf.set_event_hook(hook);
+    }
+
+    /// Panics if any of the internal invariants of the string do not hold
+    ///
+    /// The buffer must describe a single allocation with the gap between both parts, and both
+    /// parts must be valid UTF-8. Meant for tests, see the [`testing`](crate::testing) module.
+    pub fn check_invariants(&self) {
+        self.buf.check_invariants();
+        let [start, end] = self.buf.get_parts();
+        assert!(str::from_utf8(start).is_ok(), "start is not valid UTF-8");
+        assert!(str::from_utf8(end).is_ok(), "end is not valid UTF-8");
+    }
 }
 
 /// [`from_utf8_unchecked`] but panics in debug mode if the bytes are not UTF-8 encoded
@@ -322,9 +675,23 @@ mod tests {
     use rstest::rstest;
     use rstest_reuse::apply;
 
-    use crate::grower::test_utils::*;
-
-    use super::GrowingGapString;
+    use crate::{
+        error::EditError,
+        grower::{ContextGrower, GapContext, GapDecision, History, OpKind},
+        raw_gap_buf::{take_copies, RawGapBuf},
+        testing::*,
+    };
+
+    use super::{GapString, GrowingGapString};
+
+    #[test]
+    fn size() {
+        // observers, protected regions and the maximum length only take a pointer until used
+        assert_eq!(
+            size_of::<GapString>(),
+            size_of::<RawGapBuf<u8>>() + size_of::<History>() + size_of::<usize>()
+        );
+    }
 
     #[apply(grower_template)]
     fn insert(g: TestGrower) {
@@ -350,19 +717,22 @@ mod tests {
         assert!(s_buf
             .get(..)
             .unwrap()
+            .as_parts()
             .map(|s| s.len())
             .into_iter()
             .all(|len| len == 0));
         assert_eq!(s_buf.get(0..5), None);
 
         s_buf.insert("Hello", 0);
-        assert_eq!(s_buf.get(1..3).unwrap(), ["el", ""]);
-        assert_eq!(s_buf.get(2..4).unwrap(), ["ll", ""]);
-        assert_eq!(s_buf.get(2..5).unwrap(), ["llo", ""]);
+        assert_eq!(s_buf.get(1..3).unwrap().as_parts(), ["el", ""]);
+        assert_eq!(s_buf.get(2..4).unwrap().as_parts(), ["ll", ""]);
+        assert_eq!(s_buf.get(2..5).unwrap().as_parts(), ["llo", ""]);
 
         s_buf.insert("Bye", 2);
-        assert_eq!(s_buf.get(..).unwrap(), ["HeBye", "llo"]);
-        assert_eq!(s_buf.get(1..7).unwrap(), ["eBye", "ll"]);
+        assert_eq!(s_buf.get(..).unwrap().as_parts(), ["HeBye", "llo"]);
+        assert_eq!(s_buf.get(1..7).unwrap().as_parts(), ["eBye", "ll"]);
+        assert_eq!(s_buf.get(1..7).unwrap(), "eByell");
+        assert_eq!(s_buf.get(1..2).unwrap().chars().collect::<Vec<_>>(), ['e']);
     }
 
     #[apply(grower_template)]
@@ -371,6 +741,7 @@ mod tests {
         assert!(s_buf
             .get(..)
             .unwrap()
+            .as_parts()
             .map(|s| s.len())
             .into_iter()
             .all(|len| len == 0));
@@ -398,6 +769,12 @@ mod tests {
         assert_eq!(s_buf.remove(0..1), "o");
         assert!(s_buf.is_empty());
 
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert("Hello", 0);
+        s_buf.buf.move_gap_start_to(1);
+        assert_eq!(s_buf.remove(1..3), "el");
+        assert_eq!(s_buf.get_parts(), ["H", "lo"]);
+
         let mut s_buf = GrowingGapString::with_grower(g);
         s_buf.insert("Hello", 0);
         let dr = s_buf.remove(..);
@@ -423,4 +800,181 @@ mod tests {
         s_buf.replace_range(5..8, "X");
         assert_eq!(s_buf.get_slice(..).unwrap(), "ABCloX4");
     }
+
+    #[apply(grower_template)]
+    fn try_edit(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert("héllo", 0);
+
+        assert_eq!(
+            s_buf.try_insert("a", 7),
+            Err(EditError::OutOfBounds { at: 7, len: 6 })
+        );
+        assert_eq!(
+            s_buf.try_insert("a", 2),
+            Err(EditError::NotCharBoundary { at: 2 })
+        );
+        let (start, end) = (3, 1);
+        assert_eq!(
+            s_buf.try_remove(start..end),
+            Err(EditError::InvalidRange { start: 3, end: 1 })
+        );
+        assert_eq!(
+            s_buf.try_remove(1..2),
+            Err(EditError::NotCharBoundary { at: 2 })
+        );
+        assert_eq!(
+            s_buf.try_replace_range(4..10, "x"),
+            Err(EditError::OutOfBounds { at: 10, len: 6 })
+        );
+        let gap_len = s_buf.gap_len();
+        assert_eq!(
+            s_buf.try_shrink_gap(gap_len + 1),
+            Err(EditError::OutOfBounds {
+                at: gap_len + 1,
+                len: gap_len
+            })
+        );
+        assert_eq!(s_buf.get(..).unwrap(), "héllo");
+
+        assert_eq!(s_buf.try_insert("a", 6), Ok(()));
+        assert_eq!(s_buf.try_remove(1..3), Ok("é"));
+        assert_eq!(s_buf.try_replace_range(0..1, "yy"), Ok(()));
+        assert_eq!(s_buf.try_shrink_gap(s_buf.gap_len()), Ok(()));
+        assert_eq!(s_buf.get(..).unwrap(), "yylloa");
+    }
+
+    #[apply(grower_template)]
+    fn try_reserve(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert("Hello", 0);
+        s_buf.buf.move_gap_start_to(2);
+        let gap_len = s_buf.gap_len();
+
+        assert!(s_buf.try_grow_gap(usize::MAX).is_err());
+        assert!(s_buf.try_reserve(usize::MAX).is_err());
+        assert_eq!(s_buf.gap_len(), gap_len);
+        assert_eq!(s_buf.get_parts(), ["He", "llo"]);
+
+        s_buf.try_reserve(gap_len + 10).unwrap();
+        assert!(s_buf.gap_len() >= gap_len + 10);
+        s_buf.try_grow_gap(3).unwrap();
+        assert_eq!(s_buf.get(..).unwrap(), "Hello");
+    }
+
+    #[apply(grower_template)]
+    fn grow_copies_once(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert("Hello World", 0);
+        s_buf.shrink_gap(s_buf.gap_len());
+        take_copies();
+
+        // growing positions the gap at the insertion, so each byte is copied once
+        s_buf.insert(",", 5);
+        assert_eq!(take_copies(), 11);
+        s_buf.shrink_gap(s_buf.gap_len());
+        take_copies();
+
+        s_buf.try_replace_range(0..5, "Goodbye").unwrap();
+        assert_eq!(take_copies(), 12);
+        assert_eq!(s_buf.get(..).unwrap(), "Goodbye, World");
+    }
+
+    #[test]
+    fn chunk_at() {
+        let s_buf = GapString::from_slices(&["ab\ncé"], 3, &["\nd"]);
+        assert_eq!(s_buf.chunk_at(0), Some("ab\ncé"));
+        assert_eq!(s_buf.chunk_at(4), Some("é"));
+        assert_eq!(s_buf.chunk_at(5), None);
+        assert_eq!(s_buf.chunk_at(6), Some("\nd"));
+        assert_eq!(s_buf.chunk_at(7), Some("d"));
+        assert_eq!(s_buf.chunk_at(8), Some(""));
+        assert_eq!(s_buf.chunk_at(9), None);
+
+        // reading chunk after chunk yields the whole string and leaves the gap in place
+        let mut read = String::new();
+        while let Some(chunk) = s_buf.chunk_at(read.len()).filter(|c| !c.is_empty()) {
+            read.push_str(chunk);
+        }
+        assert_eq!(read, "ab\ncé\nd");
+        assert_eq!(s_buf.gap_len(), 3);
+    }
+
+    /// Records the contexts it receives and never grows more than requested
+    #[derive(Default)]
+    struct Recorder(Vec<(OpKind, usize, usize, usize, History)>);
+
+    impl ContextGrower<str> for Recorder {
+        fn decide(&mut self, cx: &GapContext<'_, str>) -> GapDecision {
+            self.0
+                .push((cx.kind, cx.position, cx.requested, cx.removed, cx.history));
+            match cx.kind {
+                OpKind::Remove => GapDecision::Shrink(usize::MAX),
+                _ => GapDecision::Keep,
+            }
+        }
+    }
+
+    #[test]
+    fn context_grower() {
+        let mut s_buf = GrowingGapString::with_grower(Recorder::default());
+        s_buf.insert("Hello", 0);
+        s_buf.insert("!", 5);
+        assert_eq!(s_buf.gap_len(), 0);
+        s_buf.replace_range(0..5, "Bye");
+        // the gap is shrunk before removing, the removed bytes then become the gap
+        assert_eq!(s_buf.remove(3..4), "!");
+        assert_eq!(s_buf.gap_len(), 1);
+        s_buf.try_reserve(4).unwrap();
+        assert_eq!(s_buf.gap_len(), 4);
+        assert_eq!(s_buf.get(..).unwrap(), "Bye");
+
+        let insert = Some((OpKind::Insert, 5));
+        assert_eq!(
+            s_buf.grower.0,
+            [
+                (OpKind::Insert, 0, 5, 0, History::new()),
+                (
+                    OpKind::Insert,
+                    5,
+                    1,
+                    0,
+                    History {
+                        last: Some((OpKind::Insert, 0)),
+                        streak: 1
+                    }
+                ),
+                (
+                    OpKind::Replace,
+                    0,
+                    0,
+                    2,
+                    History {
+                        last: insert,
+                        streak: 2
+                    }
+                ),
+                (
+                    OpKind::Remove,
+                    3,
+                    0,
+                    1,
+                    History {
+                        last: Some((OpKind::Replace, 0)),
+                        streak: 1
+                    }
+                ),
+                (
+                    OpKind::Reserve,
+                    3,
+                    4,
+                    0,
+                    History {
+                        last: Some((OpKind::Remove, 3)),
+                        streak: 1
+                    }
+                ),
+            ]
+        );
+    }
 }
@@ -0,0 +1,449 @@
+use crate::{
+    allocator::{Allocator, Global},
+    error::EditError,
+    grower::ContextGrower,
+};
+
+use super::{GrowingGapString, WordMode};
+
+/// An editing cursor over a [`GrowingGapString`]
+///
+/// The cursor mutably borrows the buffer and performs every edit at its position. Since
+/// insertions and removals are done at the gap, typing and deleting around the cursor never
+/// moves the gap, and the buffer is only reallocated when the [`ContextGrower`] asks for it.
+///
+/// Motion and deletion work on extended grapheme clusters, so the cursor never lands inside a
+/// user-perceived character. The cursor position is always on a char boundary.
+pub struct EditCursor<'a, G: ContextGrower<str>, A: Allocator = Global> {
+    buf: &'a mut GrowingGapString<G, A>,
+    pos: usize,
+    word_mode: WordMode,
+}
+
+impl<'a, G: ContextGrower<str>, A: Allocator> EditCursor<'a, G, A> {
+    #[inline(always)]
+    pub(crate) fn new(buf: &'a mut GrowingGapString<G, A>, pos: usize) -> Self {
+        Self {
+            buf,
+            pos,
+            word_mode: WordMode::default(),
+        }
+    }
+
+    /// Returns the byte position of the cursor
+    #[inline(always)]
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Sets the [`WordMode`] used for word motion and deletion
+    #[inline(always)]
+    pub fn set_word_mode(&mut self, mode: WordMode) {
+        self.word_mode = mode;
+    }
+
+    /// Returns the string buffer being edited
+    #[inline(always)]
+    pub fn as_gap_string(&self) -> &GrowingGapString<G, A> {
+        self.buf
+    }
+
+    /// Moves the cursor to the provided byte position
+    ///
+    /// # Panics
+    /// If the position is greater than [`GrowingGapString::len`] or is not on a char boundary.
+    #[inline]
+    pub fn set_position(&mut self, at: usize) {
+        assert!(
+            self.buf.is_get_char_boundary(at..at),
+            "cursor position should always be on a char boundary"
+        );
+        self.pos = at;
+    }
+
+    /// Inserts a string slice at the cursor and moves the cursor after it
+    ///
+    /// Returns false and leaves the string unchanged if the cursor is inside of a protected
+    /// region, see [`GrowingGapString::protect`], or if none of the text fits the
+    /// [`MaxLen`](crate::limit::MaxLen) of the string. Text that only partially fits is truncated
+    /// according to its policy.
+    #[inline]
+    pub fn insert(&mut self, s: &str) -> bool {
+        if self.buf.check_protected(self.pos..self.pos).is_err() {
+            return false;
+        }
+        let fitted = match self.buf.fit(self.pos..self.pos, s) {
+            Ok(fitted) if fitted.is_empty() && !s.is_empty() => return false,
+            Ok(fitted) => fitted,
+            Err(_) => return false,
+        };
+
+        self.buf.insert(fitted, self.pos);
+        self.pos += fitted.len();
+        true
+    }
+
+    /// Inserts a char at the cursor and moves the cursor after it
+    ///
+    /// See [`EditCursor::insert`].
+    #[inline]
+    pub fn insert_char(&mut self, c: char) -> bool {
+        self.insert(c.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Same as [`EditCursor::insert`] but returns an error instead of returning false, panicking
+    /// or aborting
+    ///
+    /// The string and the cursor are left unchanged if an error is returned.
+    ///
+    /// # Errors
+    /// See [`GrowingGapString::try_insert`].
+    #[inline]
+    pub fn try_insert(&mut self, s: &str) -> Result<(), EditError> {
+        let len = self.buf.len();
+        self.buf.try_insert(s, self.pos)?;
+        // the string may have been truncated to fit the maximum length
+        self.pos += self.buf.len() - len;
+        Ok(())
+    }
+
+    /// Same as [`EditCursor::insert_char`] but returns an error instead of returning false,
+    /// panicking or aborting
+    ///
+    /// See [`EditCursor::try_insert`].
+    #[inline]
+    pub fn try_insert_char(&mut self, c: char) -> Result<(), EditError> {
+        self.try_insert(c.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Removes the grapheme cluster before the cursor
+    ///
+    /// Returns false if the cursor is at the start of the buffer or the grapheme cluster is
+    /// protected.
+    #[inline]
+    pub fn backspace(&mut self) -> bool {
+        let prev = self.buf.prev_grapheme_boundary(self.pos);
+        self.remove_before(prev)
+    }
+
+    /// Removes the grapheme cluster after the cursor
+    ///
+    /// Returns false if the cursor is at the end of the buffer or the grapheme cluster is
+    /// protected.
+    #[inline]
+    pub fn delete(&mut self) -> bool {
+        let next = self.buf.next_grapheme_boundary(self.pos);
+        self.remove_after(next)
+    }
+
+    /// Removes the text between the start of the previous word and the cursor
+    ///
+    /// Returns false if the cursor is at the start of the buffer or the text touches a protected
+    /// region.
+    #[inline]
+    pub fn backspace_word(&mut self) -> bool {
+        let prev = self.prev_word_start();
+        self.remove_before(prev)
+    }
+
+    /// Removes the text between the cursor and the end of the next word
+    ///
+    /// Returns false if the cursor is at the end of the buffer or the text touches a protected
+    /// region.
+    #[inline]
+    pub fn delete_word(&mut self) -> bool {
+        let next = self.next_word_end();
+        self.remove_after(next)
+    }
+
+    /// Moves the cursor one grapheme cluster to the left
+    ///
+    /// Returns false if the cursor is at the start of the buffer.
+    #[inline]
+    pub fn move_left(&mut self) -> bool {
+        self.move_to(self.buf.prev_grapheme_boundary(self.pos))
+    }
+
+    /// Moves the cursor one grapheme cluster to the right
+    ///
+    /// Returns false if the cursor is at the end of the buffer.
+    #[inline]
+    pub fn move_right(&mut self) -> bool {
+        self.move_to(self.buf.next_grapheme_boundary(self.pos))
+    }
+
+    /// Moves the cursor to the start of the previous word
+    ///
+    /// Returns false if the cursor is at the start of the buffer.
+    #[inline]
+    pub fn move_word_left(&mut self) -> bool {
+        self.move_to(self.prev_word_start())
+    }
+
+    /// Moves the cursor to the end of the next word
+    ///
+    /// Returns false if the cursor is at the end of the buffer.
+    #[inline]
+    pub fn move_word_right(&mut self) -> bool {
+        self.move_to(self.next_word_end())
+    }
+
+    /// Moves the cursor to the start of the current line
+    #[inline]
+    pub fn move_line_start(&mut self) {
+        self.pos = self.buf.line_start(self.pos);
+    }
+
+    /// Moves the cursor to the end of the current line, before its line break
+    #[inline]
+    pub fn move_line_end(&mut self) {
+        let end = self.buf.line_end(self.pos);
+        // a `\r\n` line break should be skipped as a whole
+        self.pos = match end.checked_sub(1) {
+            Some(i) if i >= self.pos && self.buf.buf.get(i) == Some(&b'\r') => i,
+            _ => end,
+        };
+    }
+
+    /// Moves the cursor to the start of the buffer
+    #[inline]
+    pub fn move_doc_start(&mut self) {
+        self.pos = 0;
+    }
+
+    /// Moves the cursor to the end of the buffer
+    #[inline]
+    pub fn move_doc_end(&mut self) {
+        self.pos = self.buf.len();
+    }
+
+    /// Start of the previous word, or the start of the buffer if there are no words before
+    #[inline]
+    fn prev_word_start(&self) -> Option<usize> {
+        let start = self.buf.prev_word_start(self.pos, self.word_mode);
+        start.or_else(|| (self.pos != 0).then_some(0))
+    }
+
+    /// End of the next word, or the end of the buffer if there are no words after
+    #[inline]
+    fn next_word_end(&self) -> Option<usize> {
+        let len = self.buf.len();
+        let end = self.buf.next_word_end(self.pos, self.word_mode);
+        end.or_else(|| (self.pos != len).then_some(len))
+    }
+
+    /// Removes the text between the provided position and the cursor, and moves the cursor to it
+    ///
+    /// Returns false if there is no position or the text touches a protected region.
+    #[inline]
+    fn remove_before(&mut self, prev: Option<usize>) -> bool {
+        match prev {
+            Some(prev) if self.buf.check_protected(prev..self.pos).is_ok() => {
+                self.buf.remove(prev..self.pos);
+                self.pos = prev;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Removes the text between the cursor and the provided position
+    ///
+    /// Returns false if there is no position or the text touches a protected region.
+    #[inline]
+    fn remove_after(&mut self, next: Option<usize>) -> bool {
+        match next {
+            Some(next) if self.buf.check_protected(self.pos..next).is_ok() => {
+                self.buf.remove(self.pos..next);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    #[inline(always)]
+    fn move_to(&mut self, to: Option<usize>) -> bool {
+        to.inspect(|to| self.pos = *to).is_some()
+    }
+}
+
+impl<G: ContextGrower<str>, A: Allocator> GrowingGapString<G, A> {
+    /// Returns an [`EditCursor`] at the provided byte position
+    ///
+    /// # Panics
+    /// If the position is greater than [`GrowingGapString::len`] or is not on a char boundary.
+    #[inline]
+    pub fn edit_cursor(&mut self, at: usize) -> EditCursor<'_, G, A> {
+        let mut cursor = EditCursor::new(self, 0);
+        cursor.set_position(at);
+        cursor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use rstest_reuse::apply;
+
+    use crate::{error::EditError, limit::MaxLen, testing::*};
+
+    use super::{GrowingGapString, WordMode};
+
+    #[apply(grower_template)]
+    fn typing(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        let mut cursor = s_buf.edit_cursor(0);
+        for c in "Hello, wörld!".chars() {
+            cursor.insert_char(c);
+            // the gap should always stay at the cursor
+            let [start, _] = cursor.as_gap_string().get_parts();
+            assert_eq!(start.len(), cursor.position());
+        }
+
+        assert!(cursor.backspace());
+        assert!(cursor.backspace());
+        assert!(cursor.backspace());
+        let [start, _] = cursor.as_gap_string().get_parts();
+        assert_eq!(start, "Hello, wör");
+
+        cursor.move_left();
+        cursor.move_left();
+        assert!(cursor.delete());
+        assert!(cursor.move_word_right());
+        assert_eq!(cursor.position(), 9);
+        cursor.insert_char('d');
+        assert_eq!(s_buf.get(..).unwrap(), "Hello, wrd");
+    }
+
+    #[apply(grower_template)]
+    fn motion(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert("foo bar_baz\r\n  qüx.", 0);
+        let mut cursor = s_buf.edit_cursor(0);
+
+        assert!(!cursor.move_left());
+        assert!(!cursor.backspace());
+        assert!(cursor.move_word_right());
+        assert_eq!(cursor.position(), 3);
+        assert!(cursor.move_word_right());
+        assert_eq!(cursor.position(), 11);
+        cursor.move_line_end();
+        assert_eq!(cursor.position(), 11);
+        assert!(cursor.move_word_right());
+        assert_eq!(cursor.position(), 19);
+        assert!(cursor.move_word_left());
+        assert_eq!(cursor.position(), 15);
+        cursor.move_line_start();
+        assert_eq!(cursor.position(), 13);
+        cursor.move_line_end();
+        assert_eq!(cursor.position(), 20);
+        assert!(cursor.move_left());
+        assert!(cursor.move_left());
+        assert_eq!(cursor.position(), 18);
+        assert!(cursor.move_word_left());
+        assert_eq!(cursor.position(), 15);
+        assert!(cursor.move_word_left());
+        assert_eq!(cursor.position(), 4);
+        cursor.move_doc_end();
+        assert!(!cursor.move_right());
+        assert!(!cursor.delete());
+        cursor.move_doc_start();
+        assert_eq!(cursor.position(), 0);
+    }
+
+    #[apply(grower_template)]
+    fn word_editing(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        let mut cursor = s_buf.edit_cursor(0);
+        cursor.insert("foo.bar(baz) qux");
+        assert!(cursor.backspace_word());
+        assert_eq!(cursor.as_gap_string().get(..).unwrap(), "foo.bar(baz) ");
+        assert!(cursor.backspace_word());
+        assert_eq!(cursor.as_gap_string().get(..).unwrap(), "foo.bar(");
+
+        cursor.set_word_mode(WordMode::programmer());
+        assert!(cursor.backspace_word());
+        assert_eq!(cursor.as_gap_string().get(..).unwrap(), "foo.bar");
+        cursor.move_doc_start();
+        assert!(cursor.delete_word());
+        assert_eq!(cursor.as_gap_string().get(..).unwrap(), ".bar");
+        assert!(cursor.delete_word());
+        assert!(cursor.delete_word());
+        assert!(!cursor.delete_word());
+        assert!(cursor.as_gap_string().is_empty());
+    }
+
+    #[apply(grower_template)]
+    fn grapheme_editing(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        let mut cursor = s_buf.edit_cursor(0);
+        cursor.insert("ae\u{301}👨\u{200d}👩\u{200d}👧b");
+        assert!(cursor.move_left());
+        assert!(cursor.backspace());
+        assert_eq!(cursor.as_gap_string().get(..).unwrap(), "ae\u{301}b");
+        assert!(cursor.move_left());
+        assert_eq!(cursor.position(), 1);
+        assert!(cursor.delete());
+        assert_eq!(cursor.as_gap_string().get(..).unwrap(), "ab");
+    }
+
+    #[apply(grower_template)]
+    fn try_insert(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert("> ", 0);
+        s_buf.protect(0..2).unwrap();
+        s_buf.set_max_len(Some(MaxLen::reject(6)));
+
+        let mut cursor = s_buf.edit_cursor(1);
+        assert_eq!(
+            cursor.try_insert("x"),
+            Err(EditError::Protected { start: 0, end: 2 })
+        );
+        assert_eq!(
+            cursor.try_insert_char('x'),
+            Err(EditError::Protected { start: 0, end: 2 })
+        );
+        assert_eq!(cursor.position(), 1);
+
+        cursor.set_position(2);
+        cursor.try_insert("ab").unwrap();
+        cursor.try_insert_char('ü').unwrap();
+        assert_eq!(cursor.position(), 6);
+        assert_eq!(
+            cursor.try_insert("c"),
+            Err(EditError::CapacityExceeded {
+                needed: 7,
+                capacity: 6
+            })
+        );
+        assert_eq!(
+            cursor.try_insert_char('c'),
+            Err(EditError::CapacityExceeded {
+                needed: 7,
+                capacity: 6
+            })
+        );
+        assert!(!cursor.insert("c"));
+        assert!(!cursor.insert_char('c'));
+        assert_eq!(cursor.position(), 6);
+        assert_eq!(s_buf.get(..).unwrap(), "> abü");
+
+        // truncated inserts move the cursor by what was inserted
+        s_buf.set_max_len(Some(MaxLen::truncate(8)));
+        let mut cursor = s_buf.edit_cursor(6);
+        cursor.try_insert("cde").unwrap();
+        assert_eq!(cursor.position(), 8);
+        assert!(!cursor.insert("e"));
+        assert!(cursor.try_insert("e").is_ok());
+        assert_eq!(cursor.position(), 8);
+        assert_eq!(s_buf.get(..).unwrap(), "> abücd");
+    }
+
+    #[apply(grower_template)]
+    #[should_panic]
+    fn edit_cursor_panics(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert("ü", 0);
+        s_buf.edit_cursor(1);
+    }
+}
@@ -0,0 +1,871 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::{any::Any, fmt, iter::FusedIterator, ops::Range, slice};
+
+use crate::{allocator::Allocator, grower::ContextGrower};
+
+use super::{GrowingGapString, InputEdit};
+
+/// The number of decorations a block holds before it is split in two
+const BLOCK_LEN: usize = 64;
+
+/// Identifies a decoration added with [`Decorations::insert`]
+///
+/// Ids of removed decorations are never returned by [`Decorations::get`] again, even once their
+/// storage is reused by a new decoration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DecorationId {
+    slot: usize,
+    generation: usize,
+}
+
+/// A byte range attached to a string along with a value
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Decoration<T> {
+    pub id: DecorationId,
+    pub range: Range<usize>,
+    pub value: T,
+}
+
+/// A collection of ranges with values that follows the edits made to a string
+///
+/// Useful for syntax highlighting spans, diagnostics, search results and anything else that
+/// annotates parts of a [`GrowingGapString`]. Instead of recomputing the ranges after every edit,
+/// attach them to the string with [`GrowingGapString::attach_decorations`] so they follow each
+/// edit made to it. Decorations kept outside of the string can instead be given each
+/// [`InputEdit`] with [`Decorations::apply_edit`].
+///
+/// On an edit, ranges after it are shifted, ranges containing it grow or shrink, and ranges that
+/// are fully replaced collapse to an empty range after the inserted text. Text inserted right at
+/// the start or the end of a range is not included in it. Collapsed ranges are kept, they can be
+/// removed with [`Decorations::retain`].
+///
+/// # Complexity
+/// Decorations are kept sorted by their start in blocks of up to 64 decorations, and each block
+/// stores an offset added to all of its ranges along with their greatest end. With `n`
+/// decorations and `b` blocks:
+/// - [`Decorations::apply_edit`] only rewrites the blocks with a range reaching the edit, and
+///   shifts the blocks after it by changing their offset, which is `O(b)` plus `O(64)` per
+///   rewritten block.
+/// - [`Decorations::insert`] and [`Decorations::remove`] take `O(log b + 64)`, and `O(b)` when a
+///   block is split or emptied.
+/// - [`Decorations::get`] takes `O(1)`.
+/// - [`Decorations::overlapping`] skips every block whose ranges all end before the requested
+///   range without visiting its decorations, so a single long range only makes its own block
+///   visited.
+pub struct Decorations<T> {
+    /// The decorations indexed by the slot of their id
+    slots: Vec<Slot<T>>,
+    /// The slots of removed decorations that can be reused
+    free_slots: Vec<usize>,
+    /// The blocks indexed by [`Slot::block`], only the ones in `order` are in use
+    blocks: Vec<Block>,
+    /// The blocks in use sorted by the start of their decorations, they are never empty
+    order: Vec<usize>,
+    /// The blocks that are not in use and can be reused
+    free_blocks: Vec<usize>,
+    len: usize,
+}
+
+#[derive(Clone)]
+struct Slot<T> {
+    generation: usize,
+    /// The range relative to the offset of its block
+    range: Range<usize>,
+    block: usize,
+    /// [`None`] if the slot is not in use
+    value: Option<T>,
+}
+
+#[derive(Clone, Default)]
+struct Block {
+    /// The slots of the decorations sorted by their start
+    slots: Vec<usize>,
+    /// Added to the ranges of the decorations, wrapping around to shift them towards 0
+    offset: usize,
+    /// The greatest end of the ranges relative to the offset
+    max_end: usize,
+}
+
+impl<T: Clone> Clone for Decorations<T> {
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            free_slots: self.free_slots.clone(),
+            blocks: self.blocks.clone(),
+            order: self.order.clone(),
+            free_blocks: self.free_blocks.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for Decorations<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Decorations<T> {
+    /// Initialize an empty [`Decorations`]
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free_slots: Vec::new(),
+            blocks: Vec::new(),
+            order: Vec::new(),
+            free_blocks: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of decorations
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no decorations
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds a decoration over the provided byte range
+    ///
+    /// # Panics
+    /// If the range start is greater than its end.
+    pub fn insert(&mut self, range: Range<usize>, value: T) -> DecorationId {
+        assert!(
+            range.start <= range.end,
+            "range start should never be greater than its end"
+        );
+
+        // the last block starting at or before the range, decorations with the same start are
+        // kept in the order they were added
+        let pos = self
+            .order
+            .partition_point(|b| self.first_start(*b) <= range.start)
+            .saturating_sub(1);
+        let b = match self.order.get(pos) {
+            Some(b) => *b,
+            None => {
+                let b = self.new_block();
+                self.order.push(b);
+                b
+            }
+        };
+        // positions relative to an offset could wrap around while comparing them
+        self.normalize(b);
+
+        let id = self.new_slot(range.clone(), b, value);
+        let Self { slots, blocks, .. } = self;
+        let block = &mut blocks[b];
+        let i = block
+            .slots
+            .partition_point(|slot| slots[*slot].range.start <= range.start);
+        block.slots.insert(i, id.slot);
+        block.max_end = block.max_end.max(range.end);
+        if block.slots.len() > BLOCK_LEN {
+            self.split(pos);
+        }
+
+        self.len += 1;
+        id
+    }
+
+    /// Returns the decoration with the provided id
+    #[inline]
+    pub fn get(&self, id: DecorationId) -> Option<Decoration<&T>> {
+        self.slots
+            .get(id.slot)
+            .filter(|slot| slot.generation == id.generation && slot.value.is_some())?;
+        Some(self.decoration(id.slot))
+    }
+
+    /// Removes the decoration with the provided id and returns it
+    pub fn remove(&mut self, id: DecorationId) -> Option<Decoration<T>> {
+        let range = self.get(id)?.range;
+        let b = self.slots[id.slot].block;
+        let block = &mut self.blocks[b];
+        let i = block
+            .slots
+            .iter()
+            .position(|slot| *slot == id.slot)
+            .expect("decorations are always in the slots of their block");
+        block.slots.remove(i);
+        if block.slots.is_empty() {
+            let pos = self.order.iter().position(|o| *o == b).unwrap();
+            self.order.remove(pos);
+            self.free_block(b);
+        } else {
+            self.update_max_end(b);
+        }
+
+        self.len -= 1;
+        let value = self.free_slot(id.slot);
+        Some(Decoration { id, range, value })
+    }
+
+    /// Keeps only the decorations for which the provided function returns true
+    ///
+    /// The decorations are visited sorted by their start.
+    pub fn retain<F: FnMut(Decoration<&T>) -> bool>(&mut self, mut f: F) {
+        let mut pos = 0;
+        while let Some(b) = self.order.get(pos).copied() {
+            let Self {
+                slots,
+                free_slots,
+                blocks,
+                len,
+                ..
+            } = self;
+            let block = &mut blocks[b];
+            let offset = block.offset;
+            block.slots.retain(|i| {
+                let slot = &mut slots[*i];
+                let keep = f(Decoration {
+                    id: DecorationId {
+                        slot: *i,
+                        generation: slot.generation,
+                    },
+                    range: shifted(slot.range.clone(), offset),
+                    value: slot.value.as_ref().unwrap(),
+                });
+                if !keep {
+                    vacate(slot);
+                    free_slots.push(*i);
+                    *len -= 1;
+                }
+                keep
+            });
+
+            if block.slots.is_empty() {
+                self.order.remove(pos);
+                self.free_block(b);
+            } else {
+                self.update_max_end(b);
+                pos += 1;
+            }
+        }
+    }
+
+    /// Removes all decorations
+    pub fn clear(&mut self) {
+        for b in self.order.drain(..) {
+            for i in self.blocks[b].slots.drain(..) {
+                vacate(&mut self.slots[i]);
+                self.free_slots.push(i);
+            }
+            self.free_blocks.push(b);
+        }
+        self.len = 0;
+    }
+
+    /// Returns an iterator over all decorations sorted by their start
+    #[inline]
+    pub fn iter(&self) -> DecorationIter<'_, T> {
+        DecorationIter {
+            decorations: self,
+            blocks: self.order.iter(),
+            slots: [].iter(),
+        }
+    }
+
+    /// Returns an iterator over the decorations overlapping the provided range, sorted by their
+    /// start
+    ///
+    /// Empty decorations are included if they are positioned within the range.
+    pub fn overlapping(&self, r: Range<usize>) -> impl Iterator<Item = Decoration<&T>> + '_ {
+        self.order
+            .iter()
+            .take_while(move |b| self.first_start(**b) < r.end)
+            .filter(move |b| {
+                let block = &self.blocks[**b];
+                block.max_end.wrapping_add(block.offset) >= r.start
+            })
+            .flat_map(move |b| {
+                self.blocks[*b]
+                    .slots
+                    .iter()
+                    .map(|slot| self.decoration(*slot))
+                    .take_while(move |d| d.range.start < r.end)
+            })
+            .filter(move |d| {
+                d.range.end > r.start || (d.range.is_empty() && d.range.start >= r.start)
+            })
+    }
+
+    /// Adjusts the ranges to an edit made to the string
+    #[inline]
+    pub fn apply_edit(&mut self, edit: &InputEdit) {
+        self.shift(edit.start_byte, edit.old_end_byte, edit.new_end_byte);
+    }
+
+    /// Same as [`Decorations::apply_edit`] but only takes the byte positions of the edit
+    pub(super) fn shift(&mut self, start: usize, old_end: usize, new_end: usize) {
+        let map_start = |p: usize| {
+            if p < start {
+                p
+            } else if p < old_end {
+                new_end
+            } else {
+                p - old_end + new_end
+            }
+        };
+        let map_end = |p: usize| {
+            if p <= start {
+                p
+            } else if p < old_end {
+                start
+            } else {
+                p - old_end + new_end
+            }
+        };
+
+        for pos in 0..self.order.len() {
+            let b = self.order[pos];
+            let block = &self.blocks[b];
+            // ranges ending before the edit are never affected
+            if block.max_end.wrapping_add(block.offset) < start {
+                continue;
+            }
+            // ranges starting after the edit are all shifted by the same amount
+            if self.first_start(b) >= old_end {
+                let block = &mut self.blocks[b];
+                block.offset = block.offset.wrapping_add(new_end.wrapping_sub(old_end));
+                continue;
+            }
+
+            self.normalize(b);
+            let Self { slots, blocks, .. } = self;
+            for slot in &blocks[b].slots {
+                let range = &mut slots[*slot].range;
+                let range_start = map_start(range.start);
+                *range = range_start..map_end(range.end).max(range_start);
+            }
+            // mapping the starts keeps them in order, only the ends have to be recomputed
+            self.update_max_end(b);
+        }
+    }
+
+    /// Returns the decoration stored in the provided slot
+    #[inline]
+    fn decoration(&self, i: usize) -> Decoration<&T> {
+        let slot = &self.slots[i];
+        Decoration {
+            id: DecorationId {
+                slot: i,
+                generation: slot.generation,
+            },
+            range: shifted(slot.range.clone(), self.blocks[slot.block].offset),
+            value: slot.value.as_ref().unwrap(),
+        }
+    }
+
+    /// Returns the start of the first decoration of a block in use
+    #[inline]
+    fn first_start(&self, b: usize) -> usize {
+        let block = &self.blocks[b];
+        self.slots[block.slots[0]]
+            .range
+            .start
+            .wrapping_add(block.offset)
+    }
+
+    /// Adds the offset of a block to its ranges and resets it
+    fn normalize(&mut self, b: usize) {
+        let block = &mut self.blocks[b];
+        if block.offset == 0 {
+            return;
+        }
+
+        for slot in &block.slots {
+            let range = &mut self.slots[*slot].range;
+            *range = shifted(range.clone(), block.offset);
+        }
+        block.max_end = block.max_end.wrapping_add(block.offset);
+        block.offset = 0;
+    }
+
+    /// Recomputes the greatest end of a block
+    #[inline]
+    fn update_max_end(&mut self, b: usize) {
+        let block = &mut self.blocks[b];
+        block.max_end = block
+            .slots
+            .iter()
+            .map(|slot| self.slots[*slot].range.end)
+            .max()
+            .unwrap_or(0);
+    }
+
+    /// Moves the second half of the block at the provided position of `order` to a new block
+    /// right after it
+    fn split(&mut self, pos: usize) {
+        let b = self.order[pos];
+        let new = self.new_block();
+        let moved = self.blocks[b].slots.split_off(BLOCK_LEN / 2);
+        for slot in &moved {
+            self.slots[*slot].block = new;
+        }
+        self.blocks[new] = Block {
+            slots: moved,
+            offset: self.blocks[b].offset,
+            max_end: 0,
+        };
+        self.update_max_end(b);
+        self.update_max_end(new);
+        self.order.insert(pos + 1, new);
+    }
+
+    /// Returns an empty block that is not in use yet
+    #[inline]
+    fn new_block(&mut self) -> usize {
+        self.free_blocks.pop().unwrap_or_else(|| {
+            self.blocks.push(Block::default());
+            self.blocks.len() - 1
+        })
+    }
+
+    /// Marks a block that was removed from `order` as reusable
+    #[inline]
+    fn free_block(&mut self, b: usize) {
+        self.blocks[b] = Block::default();
+        self.free_blocks.push(b);
+    }
+
+    /// Stores a decoration in a free slot and returns its id
+    #[inline]
+    fn new_slot(&mut self, range: Range<usize>, block: usize, value: T) -> DecorationId {
+        let i = self.free_slots.pop().unwrap_or_else(|| {
+            self.slots.push(Slot {
+                generation: 0,
+                range: 0..0,
+                block,
+                value: None,
+            });
+            self.slots.len() - 1
+        });
+
+        let slot = &mut self.slots[i];
+        slot.range = range;
+        slot.block = block;
+        slot.value = Some(value);
+        DecorationId {
+            slot: i,
+            generation: slot.generation,
+        }
+    }
+
+    /// Takes the value out of a slot and makes it reusable
+    #[inline]
+    fn free_slot(&mut self, i: usize) -> T {
+        self.free_slots.push(i);
+        vacate(&mut self.slots[i]).unwrap()
+    }
+}
+
+/// Takes the value out of a slot and invalidates the ids pointing to it
+#[inline]
+fn vacate<T>(slot: &mut Slot<T>) -> Option<T> {
+    slot.generation = slot.generation.wrapping_add(1);
+    slot.value.take()
+}
+
+/// Adds a wrapping offset to both ends of a range
+#[inline(always)]
+fn shifted(r: Range<usize>, offset: usize) -> Range<usize> {
+    r.start.wrapping_add(offset)..r.end.wrapping_add(offset)
+}
+
+/// Identifies decorations attached with [`GrowingGapString::attach_decorations`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LayerId(usize);
+
+/// A [`Decorations`] attached to a string with its value type erased
+trait Layer: Any {
+    fn shift(&mut self, start: usize, old_end: usize, new_end: usize);
+
+    fn clone_layer(&self) -> Box<dyn Layer>;
+}
+
+impl<T: Clone + 'static> Layer for Decorations<T> {
+    #[inline(always)]
+    fn shift(&mut self, start: usize, old_end: usize, new_end: usize) {
+        Decorations::shift(self, start, old_end, new_end);
+    }
+
+    #[inline]
+    fn clone_layer(&self) -> Box<dyn Layer> {
+        Box::new(self.clone())
+    }
+}
+
+/// The decorations attached to a [`GrowingGapString`]
+#[derive(Default)]
+pub(super) struct Layers {
+    list: Vec<(LayerId, Box<dyn Layer>)>,
+    next_id: usize,
+}
+
+impl Clone for Layers {
+    fn clone(&self) -> Self {
+        Self {
+            list: self
+                .list
+                .iter()
+                .map(|(id, layer)| (*id, layer.clone_layer()))
+                .collect(),
+            next_id: self.next_id,
+        }
+    }
+}
+
+impl Layers {
+    /// Adjusts the attached decorations after replacing the provided range with `inserted` bytes
+    #[inline]
+    pub(super) fn shift(&mut self, r: Range<usize>, inserted: usize) {
+        for (_, layer) in &mut self.list {
+            layer.shift(r.start, r.end, r.start + inserted);
+        }
+    }
+
+    /// Returns the index of the layer with the provided id if it holds values of type `T`
+    #[inline]
+    fn position<T: 'static>(&self, id: LayerId) -> Option<usize> {
+        self.list.iter().position(|(other, layer)| {
+            let layer: &dyn Any = &**layer;
+            *other == id && layer.is::<Decorations<T>>()
+        })
+    }
+}
+
+impl<G: ContextGrower<str>, A: Allocator> GrowingGapString<G, A> {
+    /// Attaches decorations to the string so they follow every edit made to it
+    ///
+    /// The ranges are adjusted after each [`GrowingGapString::insert`],
+    /// [`GrowingGapString::remove`] and [`GrowingGapString::replace_range`] including their
+    /// `try_*` variants and edits made through an [`EditCursor`](super::EditCursor), before the
+    /// observers are called. Changes made in place through mutable string slices are not tracked.
+    ///
+    /// Any number of layers can be attached, each with its own value type. They are cloned along
+    /// with the string.
+    pub fn attach_decorations<T: Clone + 'static>(
+        &mut self,
+        decorations: Decorations<T>,
+    ) -> LayerId {
+        let layers = &mut self.extras_mut().layers;
+        let id = LayerId(layers.next_id);
+        layers.next_id += 1;
+        layers.list.push((id, Box::new(decorations)));
+        id
+    }
+
+    /// Returns the decorations of an attached layer
+    ///
+    /// Returns [`None`] if the layer was detached or its values are not of type `T`.
+    #[inline]
+    pub fn decorations<T: 'static>(&self, id: LayerId) -> Option<&Decorations<T>> {
+        let layers = &self.extras.as_ref()?.layers;
+        let layer: &dyn Any = &*layers.list[layers.position::<T>(id)?].1;
+        layer.downcast_ref()
+    }
+
+    /// Same as [`GrowingGapString::decorations`] but returns a mutable reference, to add or
+    /// remove decorations
+    #[inline]
+    pub fn decorations_mut<T: 'static>(&mut self, id: LayerId) -> Option<&mut Decorations<T>> {
+        let layers = &mut self.extras.as_mut()?.layers;
+        let i = layers.position::<T>(id)?;
+        let layer: &mut dyn Any = &mut *layers.list[i].1;
+        layer.downcast_mut()
+    }
+
+    /// Detaches a layer and returns its decorations, which stop following the edits
+    ///
+    /// Returns [`None`] and keeps the layer attached if its values are not of type `T`.
+    pub fn detach_decorations<T: 'static>(&mut self, id: LayerId) -> Option<Decorations<T>> {
+        let layers = &mut self.extras.as_mut()?.layers;
+        let i = layers.position::<T>(id)?;
+        let layer: Box<dyn Any> = layers.list.remove(i).1;
+        layer.downcast().ok().map(|decorations| *decorations)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Decorations<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// An iterator over the decorations of a [`Decorations`] sorted by their start
+///
+/// See [`Decorations::iter`].
+#[derive(Clone)]
+pub struct DecorationIter<'a, T> {
+    decorations: &'a Decorations<T>,
+    blocks: slice::Iter<'a, usize>,
+    slots: slice::Iter<'a, usize>,
+}
+
+impl<'a, T> Iterator for DecorationIter<'a, T> {
+    type Item = Decoration<&'a T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(slot) = self.slots.next() {
+                return Some(self.decorations.decoration(*slot));
+            }
+            self.slots = self.decorations.blocks[*self.blocks.next()?].slots.iter();
+        }
+    }
+}
+
+impl<T> FusedIterator for DecorationIter<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a Decorations<T> {
+    type Item = Decoration<&'a T>;
+    type IntoIter = DecorationIter<'a, T>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::ops::Range;
+
+    use rstest::rstest;
+    use rstest_reuse::apply;
+
+    use crate::{gapstr::GrowingGapString, testing::*};
+
+    use super::Decorations;
+
+    fn ranges<T>(decorations: &Decorations<T>) -> Vec<(usize, usize)> {
+        decorations
+            .iter()
+            .map(|d| (d.range.start, d.range.end))
+            .collect()
+    }
+
+    #[apply(grower_template)]
+    fn follows_edits(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert("let foo = bar(baz);", 0);
+
+        let mut decorations = Decorations::new();
+        decorations.insert(0..3, "keyword");
+        decorations.insert(4..7, "variable");
+        decorations.insert(10..18, "call");
+        decorations.insert(14..17, "argument");
+        let id = s_buf.attach_decorations(decorations);
+        let layer =
+            |s_buf: &GrowingGapString<TestGrower>| ranges(s_buf.decorations::<&str>(id).unwrap());
+
+        // insertions inside a range grow it, at its boundaries they do not
+        s_buf.insert("_bar", 7);
+        assert_eq!(s_buf.get(..).unwrap(), "let foo_bar = bar(baz);");
+        assert_eq!(layer(&s_buf), [(0, 3), (4, 7), (14, 22), (18, 21)]);
+        s_buf.insert("qux", 19);
+        assert_eq!(layer(&s_buf), [(0, 3), (4, 7), (14, 25), (18, 24)]);
+
+        // removals shrink ranges, replacing a whole range collapses it after the new text
+        s_buf.replace_range(18..24, "x");
+        assert_eq!(s_buf.get(..).unwrap(), "let foo_bar = bar(x);");
+        assert_eq!(layer(&s_buf), [(0, 3), (4, 7), (14, 20), (19, 19)]);
+        s_buf.remove(2..5);
+        assert_eq!(s_buf.get(..).unwrap(), "leoo_bar = bar(x);");
+        assert_eq!(layer(&s_buf), [(0, 2), (2, 4), (11, 17), (16, 16)]);
+
+        // edits through a cursor are tracked too
+        let mut cursor = s_buf.edit_cursor(0);
+        assert!(cursor.insert("  "));
+        assert_eq!(layer(&s_buf), [(2, 4), (4, 6), (13, 19), (18, 18)]);
+
+        let decorations = s_buf.decorations_mut::<&str>(id).unwrap();
+        decorations.retain(|d| !d.range.is_empty());
+        assert_eq!(
+            decorations.iter().map(|d| *d.value).collect::<Vec<_>>(),
+            ["keyword", "variable", "call"]
+        );
+    }
+
+    #[apply(grower_template)]
+    fn layers(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert("hello world", 0);
+
+        let mut words = Decorations::new();
+        words.insert(0..5, "hello");
+        words.insert(6..11, "world");
+        let words = s_buf.attach_decorations(words);
+        let mut marks = Decorations::new();
+        marks.insert(6..6, 'w');
+        let marks = s_buf.attach_decorations(marks);
+        assert_ne!(words, marks);
+
+        // a layer is only returned for its own value type
+        assert!(s_buf.decorations::<char>(words).is_none());
+        assert!(s_buf.detach_decorations::<char>(words).is_none());
+
+        // clones keep their own copy of the layers
+        let mut clone = s_buf.clone();
+        s_buf.insert(", big", 5);
+        assert_eq!(
+            ranges(s_buf.decorations::<&str>(words).unwrap()),
+            [(0, 5), (11, 16)]
+        );
+        assert_eq!(
+            ranges(s_buf.decorations::<char>(marks).unwrap()),
+            [(11, 11)]
+        );
+        clone.remove(0..6);
+        assert_eq!(
+            ranges(clone.decorations::<&str>(words).unwrap()),
+            [(0, 0), (0, 5)]
+        );
+
+        // detached decorations stop following the edits and their id goes stale
+        let detached = s_buf.detach_decorations::<&str>(words).unwrap();
+        s_buf.insert("!", 0);
+        assert_eq!(ranges(&detached), [(0, 5), (11, 16)]);
+        assert!(s_buf.decorations::<&str>(words).is_none());
+        assert!(s_buf.detach_decorations::<&str>(words).is_none());
+        assert_eq!(
+            ranges(s_buf.decorations::<char>(marks).unwrap()),
+            [(12, 12)]
+        );
+    }
+
+    #[test]
+    fn overlapping() {
+        let mut decorations = Decorations::new();
+        let long = decorations.insert(0..100, 'a');
+        decorations.insert(10..20, 'b');
+        decorations.insert(15..15, 'c');
+        decorations.insert(30..40, 'd');
+        decorations.insert(50..60, 'e');
+
+        let values =
+            |d: &Decorations<char>, r| d.overlapping(r).map(|d| d.value).collect::<String>();
+        assert_eq!(values(&decorations, 0..5), "a");
+        assert_eq!(values(&decorations, 12..35), "abcd");
+        assert_eq!(values(&decorations, 20..30), "a");
+        assert_eq!(values(&decorations, 15..16), "abc");
+        assert_eq!(values(&decorations, 100..200), "");
+
+        // without the long decoration the earlier ones are skipped
+        assert_eq!(decorations.remove(long).unwrap().range, 0..100);
+        assert_eq!(decorations.get(long), None);
+        assert_eq!(values(&decorations, 35..55), "de");
+        assert_eq!(values(&decorations, 20..30), "");
+        assert_eq!(decorations.len(), 4);
+
+        decorations.clear();
+        assert!(decorations.is_empty());
+        assert_eq!(values(&decorations, 0..100), "");
+    }
+
+    #[test]
+    fn model() {
+        // a naive list of (id, range, value) kept in insertion order
+        let mut model = Vec::new();
+        let mut decorations = Decorations::new();
+        let mut seed = 7u64;
+        let mut next = |n: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % n
+        };
+        let mut len = 1000;
+
+        let check = |decorations: &Decorations<usize>, model: &Vec<(_, Range<usize>, usize)>| {
+            let mut actual = decorations
+                .iter()
+                .map(|d| (d.id, d.range, *d.value))
+                .collect::<Vec<_>>();
+            assert!(actual.is_sorted_by_key(|(_, r, _)| r.start));
+            // the order of decorations with the same start is unspecified
+            actual.sort_by_key(|(_, _, value)| *value);
+            let mut expected = model.clone();
+            expected.sort_by_key(|(_, _, value)| *value);
+            assert_eq!(actual, expected);
+            assert_eq!(decorations.len(), model.len());
+        };
+
+        for value in 0..600 {
+            match next(4) {
+                0 | 1 => {
+                    let start = next(len + 1);
+                    let max = if next(10) == 0 { len } else { 20 };
+                    let end = start + next(max + 1).min(len - start);
+                    let id = decorations.insert(start..end, value);
+                    model.push((id, start..end, value));
+                }
+                2 if !model.is_empty() => {
+                    let (id, range, value) = model.remove(next(model.len()));
+                    let removed = decorations.remove(id).unwrap();
+                    assert_eq!((removed.range, removed.value), (range, value));
+                    assert!(decorations.get(id).is_none());
+                    assert!(decorations.remove(id).is_none());
+                }
+                _ => {
+                    let start = next(len + 1);
+                    let old_end = start + next(len - start + 1).min(30);
+                    let new_end = start + next(30);
+                    decorations.shift(start, old_end, new_end);
+                    len = len - (old_end - start) + (new_end - start);
+                    for (_, range, _) in &mut model {
+                        let map = |p: usize, is_end: bool| {
+                            if p < start || (is_end && p == start) {
+                                p
+                            } else if p < old_end {
+                                if is_end {
+                                    start
+                                } else {
+                                    new_end
+                                }
+                            } else {
+                                p - old_end + new_end
+                            }
+                        };
+                        let range_start = map(range.start, false);
+                        *range = range_start..map(range.end, true).max(range_start);
+                    }
+                }
+            }
+            check(&decorations, &model);
+
+            let start = next(len + 1);
+            let r = start..start + next(100);
+            let expected = model
+                .iter()
+                .filter(|(_, d, _)| {
+                    d.start < r.end && (d.end > r.start || (d.is_empty() && d.start >= r.start))
+                })
+                .map(|(_, _, value)| *value);
+            let mut expected = expected.collect::<Vec<_>>();
+            let mut actual = decorations
+                .overlapping(r)
+                .map(|d| *d.value)
+                .collect::<Vec<_>>();
+            expected.sort_unstable();
+            actual.sort_unstable();
+            assert_eq!(actual, expected);
+        }
+
+        decorations.retain(|d| d.value % 2 == 0);
+        model.retain(|(_, _, value)| value % 2 == 0);
+        check(&decorations, &model);
+        decorations.clear();
+        assert!(model
+            .iter()
+            .all(|(id, _, _)| decorations.get(*id).is_none()));
+    }
+}
@@ -0,0 +1,228 @@
+use core::iter::FusedIterator;
+
+use unicode_segmentation::{GraphemeCursor, GraphemeIncomplete};
+
+use crate::{allocator::Allocator, grower::ContextGrower, view::GapStr};
+
+use super::GrowingGapString;
+
+impl<G: ContextGrower<str>, A: Allocator> GrowingGapString<G, A> {
+    /// Returns true if the provided position is on an extended grapheme cluster boundary
+    ///
+    /// Returns false if the position is out of bounds or is not on a char boundary.
+    #[inline]
+    pub fn is_grapheme_boundary(&self, at: usize) -> bool {
+        self.get(..).unwrap().is_grapheme_boundary(at)
+    }
+
+    /// Returns the position of the next extended grapheme cluster boundary after the provided
+    /// position
+    ///
+    /// Returns [`None`] if the position is at the end of the string.
+    ///
+    /// # Panics
+    /// If the position is greater than [`GrowingGapString::len`] or is not on a char boundary.
+    #[inline]
+    pub fn next_grapheme_boundary(&self, at: usize) -> Option<usize> {
+        self.get(..).unwrap().next_grapheme_boundary(at)
+    }
+
+    /// Returns the position of the previous extended grapheme cluster boundary before the
+    /// provided position
+    ///
+    /// Returns [`None`] if the position is at the start of the string.
+    ///
+    /// # Panics
+    /// If the position is greater than [`GrowingGapString::len`] or is not on a char boundary.
+    #[inline]
+    pub fn prev_grapheme_boundary(&self, at: usize) -> Option<usize> {
+        self.get(..).unwrap().prev_grapheme_boundary(at)
+    }
+
+    /// Returns an iterator over the extended grapheme clusters of the string
+    ///
+    /// Clusters that straddle the gap are returned as a single [`GapStr`].
+    #[inline]
+    pub fn graphemes(&self) -> Graphemes<'_> {
+        self.get(..).unwrap().graphemes()
+    }
+}
+
+impl<'a> GapStr<'a> {
+    /// Returns true if the provided position is on an extended grapheme cluster boundary
+    ///
+    /// Returns false if the position is out of bounds or is not on a char boundary.
+    pub fn is_grapheme_boundary(&self, at: usize) -> bool {
+        if at == 0 || at == self.len() {
+            return true;
+        }
+
+        at < self.len()
+            && self.is_char_boundary(at)
+            && self.segment(at, |c, chunk, chunk_start| {
+                c.is_boundary(chunk, chunk_start)
+            })
+    }
+
+    /// Returns the position of the next extended grapheme cluster boundary after the provided
+    /// position
+    ///
+    /// Returns [`None`] if the position is at the end of the string.
+    ///
+    /// # Panics
+    /// If the position is greater than [`GapStr::len`] or is not on a char boundary.
+    pub fn next_grapheme_boundary(&self, at: usize) -> Option<usize> {
+        self.segment(at, |c, chunk, chunk_start| {
+            c.next_boundary(chunk, chunk_start)
+        })
+    }
+
+    /// Returns the position of the previous extended grapheme cluster boundary before the
+    /// provided position
+    ///
+    /// Returns [`None`] if the position is at the start of the string.
+    ///
+    /// # Panics
+    /// If the position is greater than [`GapStr::len`] or is not on a char boundary.
+    pub fn prev_grapheme_boundary(&self, at: usize) -> Option<usize> {
+        self.segment(at, |c, chunk, chunk_start| {
+            c.prev_boundary(chunk, chunk_start)
+        })
+    }
+
+    /// Returns an iterator over the extended grapheme clusters
+    #[inline]
+    pub fn graphemes(&self) -> Graphemes<'a> {
+        Graphemes {
+            text: *self,
+            front: 0,
+            back: self.len(),
+        }
+    }
+
+    /// Runs a [`GraphemeCursor`] query, feeding it the side of the gap it asks for
+    fn segment<R>(
+        &self,
+        at: usize,
+        mut f: impl FnMut(&mut GraphemeCursor, &str, usize) -> Result<R, GraphemeIncomplete>,
+    ) -> R {
+        assert!(
+            self.is_char_boundary(at),
+            "position should be in bounds and on a char boundary"
+        );
+        let [start, end] = self.as_parts();
+        let mut cursor = GraphemeCursor::new(at, self.len(), true);
+        let (mut chunk, mut chunk_start) = if at < start.len() {
+            (start, 0)
+        } else {
+            (end, start.len())
+        };
+
+        loop {
+            match f(&mut cursor, chunk, chunk_start) {
+                Ok(r) => return r,
+                Err(GraphemeIncomplete::PreContext(n)) => match n.checked_sub(start.len()) {
+                    None | Some(0) => cursor.provide_context(&start[..n], 0),
+                    Some(i) => cursor.provide_context(&end[..i], start.len()),
+                },
+                Err(GraphemeIncomplete::NextChunk) => (chunk, chunk_start) = (end, start.len()),
+                Err(GraphemeIncomplete::PrevChunk) => (chunk, chunk_start) = (start, 0),
+                Err(GraphemeIncomplete::InvalidOffset) => {
+                    unreachable!("the chunk containing the offset is always provided")
+                }
+            }
+        }
+    }
+}
+
+/// An iterator over the extended grapheme clusters of a [`GapStr`]
+#[derive(Clone, Debug)]
+pub struct Graphemes<'a> {
+    text: GapStr<'a>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a> Iterator for Graphemes<'a> {
+    type Item = GapStr<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+
+        let start = self.front;
+        self.front = self.text.next_grapheme_boundary(start)?;
+        self.text.get(start..self.front)
+    }
+}
+
+impl DoubleEndedIterator for Graphemes<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+
+        let end = self.back;
+        self.back = self.text.prev_grapheme_boundary(end)?;
+        self.text.get(self.back..end)
+    }
+}
+
+impl FusedIterator for Graphemes<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use rstest_reuse::apply;
+
+    use crate::testing::*;
+
+    use super::GrowingGapString;
+
+    const FAMILY: &str = "👨\u{200d}👩\u{200d}👧";
+
+    #[apply(grower_template)]
+    fn boundaries_across_gap(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        let text = format!("ae\u{301}{FAMILY}🇹🇷b");
+        s_buf.insert(&text, 0);
+
+        let expected = [0, 1, 4, 4 + FAMILY.len(), 12 + FAMILY.len(), text.len()];
+        // split the string on every char boundary and check if the results stay the same
+        for (gap, _) in text.char_indices().chain([(text.len(), ' ')]) {
+            s_buf.buf.move_gap_start_to(gap);
+            for at in (0..=text.len()).filter(|i| text.is_char_boundary(*i)) {
+                assert_eq!(s_buf.is_grapheme_boundary(at), expected.contains(&at));
+                let next = expected.iter().copied().find(|i| *i > at);
+                assert_eq!(s_buf.next_grapheme_boundary(at), next);
+                let prev = expected.iter().copied().rev().find(|i| *i < at);
+                assert_eq!(s_buf.prev_grapheme_boundary(at), prev);
+            }
+
+            let graphemes: Vec<_> = s_buf.graphemes().map(|g| g.to_string()).collect();
+            assert_eq!(graphemes, ["a", "e\u{301}", FAMILY, "🇹🇷", "b"]);
+            let graphemes: Vec<_> = s_buf.graphemes().rev().map(|g| g.to_string()).collect();
+            assert_eq!(graphemes, ["b", "🇹🇷", FAMILY, "e\u{301}", "a"]);
+        }
+    }
+
+    #[apply(grower_template)]
+    fn empty(g: TestGrower) {
+        let s_buf = GrowingGapString::with_grower(g);
+        assert!(s_buf.is_grapheme_boundary(0));
+        assert_eq!(s_buf.next_grapheme_boundary(0), None);
+        assert_eq!(s_buf.prev_grapheme_boundary(0), None);
+        assert_eq!(s_buf.graphemes().next(), None);
+    }
+
+    #[apply(grower_template)]
+    #[should_panic]
+    fn next_grapheme_boundary_panics(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert("é", 0);
+        s_buf.next_grapheme_boundary(1);
+    }
+}
@@ -0,0 +1,139 @@
+use core::ops::Range;
+
+use crate::{
+    allocator::Allocator,
+    grower::ContextGrower,
+    utils::{count_byte_in, find_byte, rfind_byte},
+    view::GapStr,
+};
+
+use super::GrowingGapString;
+
+impl<G: ContextGrower<str>, A: Allocator> GrowingGapString<G, A> {
+    /// Returns the byte position of the start of the line containing the provided position
+    ///
+    /// Lines are separated by `\n`, a `\r` before the `\n` is treated as part of the line.
+    ///
+    /// # Panics
+    /// If the provided position is greater than [`GrowingGapString::len`].
+    #[inline]
+    pub fn line_start(&self, at: usize) -> usize {
+        self.get(..).unwrap().line_start(at)
+    }
+
+    /// Returns the byte position of the end of the line containing the provided position
+    ///
+    /// The returned position is the position of the `\n` that terminates the line, or
+    /// [`GrowingGapString::len`] for the last line.
+    ///
+    /// # Panics
+    /// If the provided position is greater than [`GrowingGapString::len`].
+    #[inline]
+    pub fn line_end(&self, at: usize) -> usize {
+        self.get(..).unwrap().line_end(at)
+    }
+
+    /// Returns the number of lines
+    ///
+    /// An empty string, and a string ending with a `\n` count the empty last line.
+    #[inline]
+    pub fn line_count(&self) -> usize {
+        self.get(..).unwrap().line_count()
+    }
+
+    /// Returns the zero based line index of the provided position
+    ///
+    /// # Panics
+    /// If the provided position is greater than [`GrowingGapString::len`].
+    #[inline]
+    pub fn line_of(&self, at: usize) -> usize {
+        self.get(..).unwrap().line_of(at)
+    }
+
+    /// Returns the byte range of the provided line, excluding its `\n`
+    ///
+    /// Returns [`None`] if the line does not exist.
+    #[inline]
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        self.get(..).unwrap().line_range(line)
+    }
+}
+
+impl<'a> GapStr<'a> {
+    /// See [`GrowingGapString::line_start`]
+    pub fn line_start(&self, at: usize) -> usize {
+        assert!(self.len() >= at, "position should never be out of bounds");
+        rfind_byte(self.byte_parts(), b'\n', at).map_or(0, |i| i + 1)
+    }
+
+    /// See [`GrowingGapString::line_end`]
+    pub fn line_end(&self, at: usize) -> usize {
+        assert!(self.len() >= at, "position should never be out of bounds");
+        find_byte(self.byte_parts(), b'\n', at).unwrap_or(self.len())
+    }
+
+    /// See [`GrowingGapString::line_count`]
+    pub fn line_count(&self) -> usize {
+        count_byte_in(self.byte_parts(), b'\n', 0..self.len()) + 1
+    }
+
+    /// See [`GrowingGapString::line_of`]
+    pub fn line_of(&self, at: usize) -> usize {
+        assert!(self.len() >= at, "position should never be out of bounds");
+        count_byte_in(self.byte_parts(), b'\n', 0..at)
+    }
+
+    /// See [`GrowingGapString::line_range`]
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        let mut line_start = 0;
+        for _ in 0..line {
+            line_start = find_byte(self.byte_parts(), b'\n', line_start)? + 1;
+        }
+
+        Some(line_start..self.line_end(line_start))
+    }
+
+    #[inline(always)]
+    fn byte_parts(&self) -> [&'a [u8]; 2] {
+        self.as_parts().map(str::as_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use rstest_reuse::apply;
+
+    use crate::testing::*;
+
+    use super::GrowingGapString;
+
+    #[apply(grower_template)]
+    fn lines(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        assert_eq!(s_buf.line_count(), 1);
+        assert_eq!(s_buf.line_range(0), Some(0..0));
+        assert_eq!(s_buf.line_range(1), None);
+
+        s_buf.insert("ab\ncd\n", 0);
+        s_buf.insert("\r\nef", 2);
+        assert_eq!(s_buf.get_parts(), ["ab\r\nef", "\ncd\n"]);
+
+        assert_eq!(s_buf.line_count(), 4);
+        assert_eq!(s_buf.line_range(0), Some(0..3));
+        assert_eq!(s_buf.line_range(1), Some(4..6));
+        assert_eq!(s_buf.line_range(2), Some(7..9));
+        assert_eq!(s_buf.line_range(3), Some(10..10));
+        assert_eq!(s_buf.line_range(4), None);
+
+        assert_eq!(s_buf.line_start(5), 4);
+        assert_eq!(s_buf.line_end(5), 6);
+        assert_eq!(s_buf.line_start(6), 4);
+        assert_eq!(s_buf.line_start(7), 7);
+        assert_eq!(s_buf.line_end(10), 10);
+        assert_eq!(s_buf.line_of(0), 0);
+        assert_eq!(s_buf.line_of(3), 0);
+        assert_eq!(s_buf.line_of(4), 1);
+        assert_eq!(s_buf.line_of(10), 3);
+    }
+}
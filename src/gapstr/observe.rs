@@ -0,0 +1,312 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::ops::Range;
+
+use crate::{
+    allocator::Allocator,
+    grower::ContextGrower,
+    utils::{point_from, point_of},
+};
+
+use super::GrowingGapString;
+
+/// A position in a string as a zero based row, and a byte column within that row
+///
+/// Rows are separated by `\n`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub row: usize,
+    pub column: usize,
+}
+
+impl Point {
+    #[inline(always)]
+    pub const fn new(row: usize, column: usize) -> Self {
+        Self { row, column }
+    }
+
+    /// Returns the point right after `s` if it were inserted at this point
+    #[inline]
+    fn advance(self, s: &str) -> Self {
+        match s.rfind('\n') {
+            Some(i) => Self::new(
+                self.row + s.bytes().filter(|b| *b == b'\n').count(),
+                s.len() - i - 1,
+            ),
+            None => Self::new(self.row, self.column + s.len()),
+        }
+    }
+}
+
+/// Describes an edit made to a [`GrowingGapString`]
+///
+/// This holds the same data as tree-sitter's `InputEdit`, so it can be forwarded to incremental
+/// parsers as is. Byte positions and points before the edit are relative to the old contents,
+/// while the new end is relative to the new contents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InputEdit {
+    pub start_byte: usize,
+    pub old_end_byte: usize,
+    pub new_end_byte: usize,
+    pub start_position: Point,
+    pub old_end_position: Point,
+    pub new_end_position: Point,
+}
+
+/// Identifies an observer added with [`GrowingGapString::subscribe`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ObserverId(usize);
+
+/// A function called with every edit made to a [`GrowingGapString`]
+pub type Observer = Box<dyn FnMut(&InputEdit)>;
+
+/// The observers subscribed to a [`GrowingGapString`]
+#[derive(Default)]
+pub(super) struct Observers {
+    list: Vec<(ObserverId, Observer)>,
+    next_id: usize,
+    /// The byte position and point of the end of the last reported edit
+    ///
+    /// Points of the next edit are computed from it, which only scans the bytes between both
+    /// edits instead of the whole string before them.
+    anchor: (usize, Point),
+}
+
+impl Observers {
+    #[inline(always)]
+    pub(super) const fn new() -> Self {
+        Self {
+            list: Vec::new(),
+            next_id: 0,
+            anchor: (0, Point::new(0, 0)),
+        }
+    }
+
+    #[inline]
+    pub(super) fn notify(&mut self, edit: Option<InputEdit>) {
+        let Some(edit) = edit else {
+            // edits that are not reported move the text after the anchor
+            self.anchor = (0, Point::new(0, 0));
+            return;
+        };
+
+        self.anchor = (edit.new_end_byte, edit.new_end_position);
+        for (_, observer) in &mut self.list {
+            observer(&edit);
+        }
+    }
+}
+
+impl<G: ContextGrower<str>, A: Allocator> GrowingGapString<G, A> {
+    /// Returns the row and column of the provided byte position
+    ///
+    /// # Panics
+    /// If the provided position is greater than [`GrowingGapString::len`].
+    #[inline]
+    pub fn point_at(&self, at: usize) -> Point {
+        assert!(self.len() >= at, "position should never be out of bounds");
+        point_of(self.buf.get_parts(), at)
+    }
+
+    /// Adds an observer that is called after every edit
+    ///
+    /// Observers are called in the order they were added, after each [`GrowingGapString::insert`],
+    /// [`GrowingGapString::remove`] and [`GrowingGapString::replace_range`] including their
+    /// `try_*` variants and edits made through an [`EditCursor`](super::EditCursor). Changes made
+    /// in place through mutable string slices are not reported.
+    ///
+    /// Observers are not cloned along with the string.
+    pub fn subscribe<F>(&mut self, observer: F) -> ObserverId
+    where
+        F: FnMut(&InputEdit) + 'static,
+    {
+        let observers = &mut self.extras_mut().observers;
+        let id = ObserverId(observers.next_id);
+        observers.next_id += 1;
+        observers.list.push((id, Box::new(observer)));
+        id
+    }
+
+    /// Removes an observer
+    ///
+    /// Returns false if the observer was already removed.
+    pub fn unsubscribe(&mut self, id: ObserverId) -> bool {
+        let Some(extras) = &mut self.extras else {
+            return false;
+        };
+        let list = &mut extras.observers.list;
+        match list.iter().position(|(i, _)| *i == id) {
+            Some(i) => {
+                drop(list.remove(i));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the edit replacing the provided range with `s` if there are any observers
+    ///
+    /// Must be called before the edit is made.
+    #[inline]
+    pub(super) fn pending_edit(&self, r: Range<usize>, s: &str) -> Option<InputEdit> {
+        let observers = &self.extras.as_ref()?.observers;
+        if observers.list.is_empty() {
+            return None;
+        }
+
+        // start from the anchor or the start of the string, whichever is closer
+        let parts = self.buf.get_parts();
+        let anchor = observers.anchor;
+        let from = if r.start < anchor.0.abs_diff(r.start) {
+            (0, Point::new(0, 0))
+        } else {
+            anchor
+        };
+        let start_position = point_from(parts, from, r.start);
+        Some(InputEdit {
+            start_byte: r.start,
+            old_end_byte: r.end,
+            new_end_byte: r.start + s.len(),
+            start_position,
+            old_end_position: point_from(parts, (r.start, start_position), r.end),
+            new_end_position: start_position.advance(s),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use rstest::rstest;
+    use rstest_reuse::apply;
+
+    use crate::{
+        gapstr::{GapString, GrowingGapString},
+        testing::*,
+    };
+
+    use super::{InputEdit, Point};
+
+    fn edit(bytes: [usize; 3], points: [(usize, usize); 3]) -> InputEdit {
+        let [start, old_end, new_end] = points.map(|(row, column)| Point::new(row, column));
+        InputEdit {
+            start_byte: bytes[0],
+            old_end_byte: bytes[1],
+            new_end_byte: bytes[2],
+            start_position: start,
+            old_end_position: old_end,
+            new_end_position: new_end,
+        }
+    }
+
+    #[apply(grower_template)]
+    fn observers(g: TestGrower) {
+        let edits = Rc::new(RefCell::new(Vec::new()));
+        let mut s_buf = GrowingGapString::with_grower(g);
+        let id = s_buf.subscribe({
+            let edits = Rc::clone(&edits);
+            move |edit| edits.borrow_mut().push(*edit)
+        });
+        let count = Rc::new(RefCell::new(0));
+        s_buf.subscribe({
+            let count = Rc::clone(&count);
+            move |_| *count.borrow_mut() += 1
+        });
+
+        s_buf.insert("fn main() {\n}", 0);
+        s_buf.insert("\n    let x = 1;", 11);
+        s_buf.replace_range(20..25, "y = 2");
+        s_buf.try_remove(11..16).unwrap();
+        assert_eq!(s_buf.get(..).unwrap(), "fn main() {let y = 2;\n}");
+
+        assert_eq!(
+            *edits.borrow(),
+            [
+                edit([0, 0, 13], [(0, 0), (0, 0), (1, 1)]),
+                edit([11, 11, 26], [(0, 11), (0, 11), (1, 14)]),
+                edit([20, 25, 25], [(1, 8), (1, 13), (1, 13)]),
+                edit([11, 16, 11], [(0, 11), (1, 4), (0, 11)]),
+            ]
+        );
+
+        // removed observers are no longer called, clones do not inherit observers
+        assert!(s_buf.unsubscribe(id));
+        assert!(!s_buf.unsubscribe(id));
+        s_buf.insert("!", 0);
+        let mut cloned = s_buf.clone();
+        cloned.insert("?", 0);
+        assert_eq!(edits.borrow().len(), 4);
+        assert_eq!(*count.borrow(), 5);
+    }
+
+    /// Returns the point of a position in a string
+    fn point(s: &str, at: usize) -> Point {
+        let row = s[..at].matches('\n').count();
+        Point::new(row, at - s[..at].rfind('\n').map_or(0, |i| i + 1))
+    }
+
+    #[apply(grower_template)]
+    fn points_follow_edits(g: TestGrower) {
+        const PIECES: [&str; 5] = ["ab", "\n", "c\nd\n", "", "éf"];
+
+        let edits = Rc::new(RefCell::new(Vec::new()));
+        let mut s_buf = GrowingGapString::with_grower(g);
+        let mut model = String::new();
+        let mut seed = 7u64;
+        for round in 0..2 {
+            let id = s_buf.subscribe({
+                let edits = Rc::clone(&edits);
+                move |edit| edits.borrow_mut().push(*edit)
+            });
+            for step in 0..100 {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let floor = |mut i: usize| {
+                    while !model.is_char_boundary(i) {
+                        i -= 1;
+                    }
+                    i
+                };
+                let start = floor((seed >> 33) as usize % (model.len() + 1));
+                let end = floor((start + (seed >> 13) as usize % 6).min(model.len()));
+                let piece = PIECES[(seed >> 40) as usize % PIECES.len()];
+
+                let start_position = point(&model, start);
+                let old_end_position = point(&model, end);
+                s_buf.replace_range(start..end, piece);
+                model.replace_range(start..end, piece);
+                let expected = InputEdit {
+                    start_byte: start,
+                    old_end_byte: end,
+                    new_end_byte: start + piece.len(),
+                    start_position,
+                    old_end_position,
+                    new_end_position: point(&model, start + piece.len()),
+                };
+                assert_eq!(
+                    edits.borrow().last(),
+                    Some(&expected),
+                    "round {round}, step {step}"
+                );
+            }
+
+            // edits made without observers are not reported, the next ones must not rely on
+            // the previous edits
+            assert!(s_buf.unsubscribe(id));
+            s_buf.insert("x\n", 0);
+            model.insert_str(0, "x\n");
+        }
+    }
+
+    #[test]
+    fn point_at() {
+        let s_buf = GapString::from_slices(&["ab\ncd"], 3, &["\n\nef"]);
+        assert_eq!(s_buf.point_at(0), Point::new(0, 0));
+        assert_eq!(s_buf.point_at(2), Point::new(0, 2));
+        assert_eq!(s_buf.point_at(3), Point::new(1, 0));
+        assert_eq!(s_buf.point_at(5), Point::new(1, 2));
+        assert_eq!(s_buf.point_at(7), Point::new(3, 0));
+        assert_eq!(s_buf.point_at(9), Point::new(3, 2));
+    }
+}
@@ -0,0 +1,188 @@
+use core::ops::{Range, RangeBounds};
+
+use crate::{allocator::Allocator, error::EditError, grower::ContextGrower};
+
+use super::{DecorationId, Decorations, GrowingGapString};
+
+/// Identifies a region protected with [`GrowingGapString::protect`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ProtectedId(DecorationId);
+
+impl<G: ContextGrower<str>, A: Allocator> GrowingGapString<G, A> {
+    /// Makes a range of the string read-only
+    ///
+    /// Any [`GrowingGapString::insert`], [`GrowingGapString::remove`] or
+    /// [`GrowingGapString::replace_range`] that touches a protected region fails, the `try_*`
+    /// variants return [`EditError::Protected`] and the others panic. The edits of an
+    /// [`EditCursor`](super::EditCursor) return false instead. Inserting right before or right
+    /// after a region is allowed, and regions shift along with the edits made around them. Changes
+    /// made in place through mutable string slices are not checked.
+    ///
+    /// Regions can overlap. Empty regions only prevent removing the text around them.
+    ///
+    /// # Errors
+    /// If the range is out of bounds or does not lie on a char boundary.
+    pub fn protect<RB: RangeBounds<usize>>(&mut self, r: RB) -> Result<ProtectedId, EditError> {
+        let r = self.check_range(r)?;
+        Ok(ProtectedId(self.extras_mut().protected.insert(r, ())))
+    }
+
+    /// Makes a protected region editable again
+    ///
+    /// Returns false if the region was already unprotected.
+    pub fn unprotect(&mut self, id: ProtectedId) -> bool {
+        self.extras
+            .as_mut()
+            .is_some_and(|extras| extras.protected.remove(id.0).is_some())
+    }
+
+    /// Returns the current range of a protected region
+    #[inline]
+    pub fn protected_range(&self, id: ProtectedId) -> Option<Range<usize>> {
+        let extras = self.extras.as_ref()?;
+        extras.protected.get(id.0).map(|d| d.range)
+    }
+
+    /// Returns the ranges of all protected regions sorted by their start
+    #[inline]
+    pub fn protected_ranges(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        self.extras
+            .iter()
+            .flat_map(|extras| &extras.protected)
+            .map(|d| d.range)
+    }
+
+    /// Returns an error if replacing the provided range would touch a protected region
+    #[inline]
+    pub(super) fn check_protected(&self, r: Range<usize>) -> Result<(), EditError> {
+        let Some(extras) = self.extras.as_ref().filter(|e| !e.protected.is_empty()) else {
+            return Ok(());
+        };
+
+        // a region is touched when the edit overlaps it, for inserts this is when they are
+        // strictly inside of it
+        let touched = extras
+            .protected
+            .overlapping(r.start..r.end.max(r.start + 1))
+            .find(|d| r.start < d.range.end && d.range.start < r.end);
+        match touched {
+            Some(d) => Err(EditError::Protected {
+                start: d.range.start,
+                end: d.range.end,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Same as [`GrowingGapString::check_protected`] but panics instead of returning an error
+    #[inline]
+    pub(super) fn assert_unprotected(&self, r: Range<usize>) {
+        if let Err(err) = self.check_protected(r) {
+            panic!("{err}");
+        }
+    }
+}
+
+/// Shifts the protected regions after an edit replacing `r` with `inserted` bytes
+#[inline(always)]
+pub(super) fn shift(protected: &mut Decorations<()>, r: Range<usize>, inserted: usize) {
+    if !protected.is_empty() {
+        protected.shift(r.start, r.end, r.start + inserted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use rstest_reuse::apply;
+
+    use crate::{error::EditError, gapstr::GrowingGapString, testing::*};
+
+    #[apply(grower_template)]
+    fn protected(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert("> 1+1\n2\n> ", 0);
+        s_buf.protect(0..2).unwrap();
+        let output = s_buf.protect(5..8).unwrap();
+        s_buf.protect(8..10).unwrap();
+
+        // edits inside of a region fail, edits next to it move it
+        assert_eq!(
+            s_buf.try_insert("x", 1),
+            Err(EditError::Protected { start: 0, end: 2 })
+        );
+        s_buf.try_insert("x", 2).unwrap();
+        assert_eq!(
+            s_buf.protected_ranges().collect::<Vec<_>>(),
+            [0..2, 6..9, 9..11]
+        );
+        assert_eq!(
+            s_buf.try_remove(4..7),
+            Err(EditError::Protected { start: 6, end: 9 })
+        );
+        assert_eq!(
+            s_buf.try_replace_range(0..1, "$"),
+            Err(EditError::Protected { start: 0, end: 2 })
+        );
+        s_buf.try_replace_range(2..6, "2*3").unwrap();
+        s_buf.insert("ab", 10);
+        s_buf.try_insert("y", 8).unwrap();
+        assert_eq!(s_buf.get(..).unwrap(), "> 2*3\n2\ny> ab");
+        assert_eq!(
+            s_buf.protected_ranges().collect::<Vec<_>>(),
+            [0..2, 5..8, 9..11]
+        );
+
+        // clones keep the regions
+        let mut cloned = s_buf.clone();
+        assert!(cloned.try_remove(..).is_err());
+        assert!(cloned.unprotect(output));
+        assert!(!cloned.unprotect(output));
+        assert_eq!(cloned.protected_range(output), None);
+        assert_eq!(cloned.remove(5..9), "\n2\ny");
+        assert_eq!(cloned.protected_ranges().collect::<Vec<_>>(), [0..2, 5..7]);
+        assert_eq!(s_buf.protected_range(output), Some(5..8));
+
+        assert_eq!(
+            s_buf.protect(..20),
+            Err(EditError::OutOfBounds { at: 20, len: 13 })
+        );
+    }
+
+    #[apply(grower_template)]
+    fn protected_cursor(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert("> foo bar", 0);
+        s_buf.protect(0..2).unwrap();
+        s_buf.protect(4..5).unwrap();
+
+        // cursor edits touching a region are refused and leave everything unchanged
+        let mut cursor = s_buf.edit_cursor(1);
+        assert!(!cursor.insert("x"));
+        assert!(!cursor.insert_char('x'));
+        assert!(!cursor.backspace());
+        assert!(!cursor.delete());
+        assert_eq!(cursor.position(), 1);
+        cursor.set_position(2);
+        assert!(!cursor.backspace());
+        assert!(!cursor.backspace_word());
+        cursor.set_position(5);
+        assert!(!cursor.backspace());
+        assert!(!cursor.backspace_word());
+        cursor.set_position(3);
+        assert!(!cursor.delete_word());
+        assert_eq!(cursor.position(), 3);
+        assert_eq!(s_buf.get(..).unwrap(), "> foo bar");
+        assert_eq!(s_buf.protected_ranges().collect::<Vec<_>>(), [0..2, 4..5]);
+
+        // edits next to a region are allowed
+        let mut cursor = s_buf.edit_cursor(2);
+        assert!(cursor.delete());
+        assert!(cursor.insert("x"));
+        cursor.set_position(9);
+        assert!(cursor.backspace_word());
+        assert_eq!(cursor.position(), 6);
+        assert_eq!(s_buf.get(..).unwrap(), "> xoo ");
+        assert_eq!(s_buf.protected_ranges().collect::<Vec<_>>(), [0..2, 4..5]);
+    }
+}
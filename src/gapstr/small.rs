@@ -0,0 +1,704 @@
+use alloc::collections::TryReserveError;
+use core::{
+    fmt,
+    mem::ManuallyDrop,
+    ops::{Range, RangeBounds},
+    ptr,
+};
+
+use crate::{
+    allocator::{Allocator, Global},
+    array::ArrayGapString,
+    error::EditError,
+    grower::{ContextGrower, DefaultGrower, GapContext, GapDecision, History, OpKind},
+    raw_gap_buf::RawGapBuf,
+    utils::try_get_range,
+    view::GapStr,
+};
+
+use super::{Graphemes, GrowingGapString, WordMode, Words};
+
+/// A gap buffer string that stores up to `N` bytes inline before spilling to the heap
+///
+/// While the contents fit in `N` bytes no allocation is performed. Once an edit would exceed the
+/// inline capacity the contents are moved to a heap allocated [`GrowingGapString`] using the
+/// provided [`ContextGrower`] and allocator, and the string stays on the heap from then on.
+///
+/// Edits, line, grapheme, word and column queries and the UTF-8 guarantees are the same as
+/// [`GrowingGapString`]. Edit cursors, observers, protected regions, decorations and the maximum
+/// length are only tracked on the heap, use [`SmallGapString::as_heap_mut`] to access them.
+#[derive(Clone)]
+pub struct SmallGapString<
+    const N: usize = 32,
+    G: ContextGrower<str> = DefaultGrower,
+    A: Allocator = Global,
+> {
+    repr: Repr<N, G, A>,
+}
+
+#[derive(Clone)]
+enum Repr<const N: usize, G: ContextGrower<str>, A: Allocator> {
+    Inline(ArrayGapString<N>, G, A),
+    Heap(GrowingGapString<G, A>),
+}
+
+impl<const N: usize, G: ContextGrower<str> + Default> Default for SmallGapString<N, G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, G: ContextGrower<str>> SmallGapString<N, G> {
+    /// Initialize an empty [`SmallGapString`]
+    #[inline]
+    pub fn new() -> Self
+    where
+        G: Default,
+    {
+        Self::with_grower(Default::default())
+    }
+
+    /// Initialize an empty [`SmallGapString`] with a [`ContextGrower`]
+    ///
+    /// The grower is only used once the contents spill to the heap.
+    #[inline]
+    pub const fn with_grower(grower: G) -> Self {
+        Self::with_grower_in(grower, Global)
+    }
+}
+
+impl<const N: usize, G: ContextGrower<str>, A: Allocator> SmallGapString<N, G, A> {
+    /// Initialize an empty [`SmallGapString`] that allocates in the provided allocator once it
+    /// spills to the heap
+    #[inline]
+    pub fn new_in(alloc: A) -> Self
+    where
+        G: Default,
+    {
+        Self::with_grower_in(Default::default(), alloc)
+    }
+
+    /// Initialize an empty [`SmallGapString`] with a [`ContextGrower`] that allocates in the
+    /// provided allocator once it spills to the heap
+    #[inline]
+    pub const fn with_grower_in(grower: G, alloc: A) -> Self {
+        Self {
+            repr: Repr::Inline(ArrayGapString::new(), grower, alloc),
+        }
+    }
+
+    /// Returns true if the contents are still stored inline
+    #[inline(always)]
+    pub fn is_inline(&self) -> bool {
+        matches!(self.repr, Repr::Inline(..))
+    }
+
+    /// Returns the total length excluding the gap
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        match &self.repr {
+            Repr::Inline(s, ..) => s.len(),
+            Repr::Heap(s) => s.len(),
+        }
+    }
+
+    /// Returns the gaps length
+    #[inline(always)]
+    pub fn gap_len(&self) -> usize {
+        match &self.repr {
+            Repr::Inline(s, ..) => s.gap_len(),
+            Repr::Heap(s) => s.gap_len(),
+        }
+    }
+
+    /// Returns true if the buffer is empty
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Same as [`GrowingGapString::get`]
+    #[inline]
+    pub fn get<RB: RangeBounds<usize>>(&self, r: RB) -> Option<GapStr<'_>> {
+        match &self.repr {
+            Repr::Inline(s, ..) => s.get(r),
+            Repr::Heap(s) => s.get(r),
+        }
+    }
+
+    /// Same as [`GrowingGapString::get_slice`]
+    #[inline]
+    pub fn get_slice<RB: RangeBounds<usize>>(&mut self, r: RB) -> Option<&str> {
+        match &mut self.repr {
+            Repr::Inline(s, ..) => s.get_slice(r),
+            Repr::Heap(s) => s.get_slice(r),
+        }
+    }
+
+    /// Returns both sides of the gap buffer
+    #[inline(always)]
+    pub fn get_parts(&self) -> [&str; 2] {
+        match &self.repr {
+            Repr::Inline(s, ..) => s.get_parts(),
+            Repr::Heap(s) => s.get_parts(),
+        }
+    }
+
+    /// Returns a [`GapStr`] over the whole string
+    #[inline(always)]
+    pub fn as_gap_str(&self) -> GapStr<'_> {
+        let [start, end] = self.get_parts();
+        GapStr::new(start, end)
+    }
+
+    /// Same as [`GrowingGapString::chunk_at`]
+    #[inline]
+    pub fn chunk_at(&self, at: usize) -> Option<&str> {
+        let [start, end] = self.get_parts();
+        match at.checked_sub(start.len()) {
+            Some(at) => end.get(at..),
+            None => start.get(at..),
+        }
+    }
+
+    /// Reserves space for at least `additional` more bytes
+    ///
+    /// Spills to the heap if the inline storage cannot hold the additional bytes. Otherwise the
+    /// same as [`GrowingGapString::try_reserve`].
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        match &mut self.repr {
+            Repr::Inline(s, ..) if s.len().saturating_add(additional) <= N => Ok(()),
+            Repr::Inline(..) => self.spill(additional, true),
+            Repr::Heap(s) => s.try_reserve(additional),
+        }
+    }
+
+    /// Insert a string at the provided position
+    ///
+    /// # Panics
+    /// If the provided position is greater than [`SmallGapString::len`] or the position is not
+    /// on a char boundary.
+    pub fn insert(&mut self, s: &str, at: usize) {
+        if let Repr::Inline(inline, ..) = &mut self.repr {
+            match inline.try_insert(s, at) {
+                Err(EditError::CapacityExceeded { .. }) => self.spill_infallible(s.len()),
+                Err(err) => panic!("{err}"),
+                Ok(()) => return,
+            }
+        }
+
+        self.heap().insert(s, at);
+    }
+
+    /// Same as [`SmallGapString::insert`] but returns an error instead of panicking
+    ///
+    /// The string is left unchanged if an error is returned.
+    pub fn try_insert(&mut self, s: &str, at: usize) -> Result<(), EditError> {
+        if let Repr::Inline(inline, ..) = &mut self.repr {
+            match inline.try_insert(s, at) {
+                Err(EditError::CapacityExceeded { .. }) => self.spill(s.len(), true)?,
+                res => return res,
+            }
+        }
+
+        self.heap().try_insert(s, at)
+    }
+
+    /// Same as [`GrowingGapString::remove`]
+    ///
+    /// Removing never moves the contents back inline.
+    ///
+    /// # Panics
+    /// If the provided range is out of bounds or the range start is greater than its end.
+    /// If the range does not lie on a char boundary.
+    pub fn remove<RB: RangeBounds<usize>>(&mut self, r: RB) -> &str {
+        match &mut self.repr {
+            Repr::Inline(s, ..) => s.remove(r),
+            Repr::Heap(s) => s.remove(r),
+        }
+    }
+
+    /// Same as [`SmallGapString::remove`] but returns an error instead of panicking
+    ///
+    /// The string is left unchanged if an error is returned.
+    pub fn try_remove<RB: RangeBounds<usize>>(&mut self, r: RB) -> Result<&str, EditError> {
+        match &mut self.repr {
+            Repr::Inline(s, ..) => s.try_remove(r),
+            Repr::Heap(s) => s.try_remove(r),
+        }
+    }
+
+    /// Same as [`GrowingGapString::replace_range`]
+    ///
+    /// # Panics
+    /// If the provided range is out of bounds or the range start is greater than its end.
+    /// If the range does not lie on a char boundary.
+    pub fn replace_range<RB: RangeBounds<usize>>(&mut self, r: RB, s: &str) {
+        let r = try_get_range(self.len(), r).unwrap_or_else(|err| panic!("{err}"));
+        if let Repr::Inline(inline, ..) = &mut self.repr {
+            match inline.try_replace_range(r.clone(), s) {
+                Err(EditError::CapacityExceeded { .. }) => {
+                    self.spill_infallible(s.len().saturating_sub(r.len()))
+                }
+                Err(err) => panic!("{err}"),
+                Ok(()) => return,
+            }
+        }
+
+        self.heap().replace_range(r, s);
+    }
+
+    /// Same as [`SmallGapString::replace_range`] but returns an error instead of panicking
+    ///
+    /// The string is left unchanged if an error is returned.
+    pub fn try_replace_range<RB: RangeBounds<usize>>(
+        &mut self,
+        r: RB,
+        s: &str,
+    ) -> Result<(), EditError> {
+        let r = try_get_range(self.len(), r)?;
+        if let Repr::Inline(inline, ..) = &mut self.repr {
+            match inline.try_replace_range(r.clone(), s) {
+                Err(EditError::CapacityExceeded { .. }) => {
+                    self.spill(s.len().saturating_sub(r.len()), true)?
+                }
+                res => return res,
+            }
+        }
+
+        self.heap().try_replace_range(r, s)
+    }
+
+    /// Removes all of the text
+    ///
+    /// Clearing never moves the contents back inline.
+    ///
+    /// # Panics
+    /// If the string is on the heap and contains a protected region.
+    #[inline]
+    pub fn clear(&mut self) {
+        match &mut self.repr {
+            Repr::Inline(s, ..) => s.clear(),
+            Repr::Heap(s) => {
+                s.remove(..);
+            }
+        }
+    }
+
+    /// Same as [`GrowingGapString::shrink_gap`]
+    ///
+    /// The inline storage has a fixed capacity, so while the contents are inline only the
+    /// provided value is checked.
+    ///
+    /// # Panics
+    /// If the provided value is greater than [`SmallGapString::gap_len`].
+    pub fn shrink_gap(&mut self, by: usize) {
+        self.try_shrink_gap(by)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    /// Same as [`SmallGapString::shrink_gap`] but returns an error instead of panicking
+    ///
+    /// Returns [`EditError::OutOfBounds`] with the gap length if the provided value is greater
+    /// than [`SmallGapString::gap_len`].
+    pub fn try_shrink_gap(&mut self, by: usize) -> Result<(), EditError> {
+        match &mut self.repr {
+            Repr::Inline(s, ..) if by > s.gap_len() => Err(EditError::OutOfBounds {
+                at: by,
+                len: s.gap_len(),
+            }),
+            Repr::Inline(..) => Ok(()),
+            Repr::Heap(s) => s.try_shrink_gap(by),
+        }
+    }
+
+    /// Moves the contents to the heap if they are still inline and returns the heap allocated
+    /// string
+    ///
+    /// Gives access to [`GrowingGapString::edit_cursor`], [`GrowingGapString::subscribe`],
+    /// [`GrowingGapString::protect`], [`GrowingGapString::attach_decorations`] and
+    /// [`GrowingGapString::set_max_len`], which are not supported inline. The string stays on the
+    /// heap from then on.
+    pub fn as_heap_mut(&mut self) -> &mut GrowingGapString<G, A> {
+        self.spill_infallible(0);
+        self.heap()
+    }
+
+    /// See [`GrowingGapString::line_start`]
+    #[inline]
+    pub fn line_start(&self, at: usize) -> usize {
+        self.as_gap_str().line_start(at)
+    }
+
+    /// See [`GrowingGapString::line_end`]
+    #[inline]
+    pub fn line_end(&self, at: usize) -> usize {
+        self.as_gap_str().line_end(at)
+    }
+
+    /// See [`GrowingGapString::line_count`]
+    #[inline]
+    pub fn line_count(&self) -> usize {
+        self.as_gap_str().line_count()
+    }
+
+    /// See [`GrowingGapString::line_of`]
+    #[inline]
+    pub fn line_of(&self, at: usize) -> usize {
+        self.as_gap_str().line_of(at)
+    }
+
+    /// See [`GrowingGapString::line_range`]
+    #[inline]
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        self.as_gap_str().line_range(line)
+    }
+
+    /// See [`GrowingGapString::is_grapheme_boundary`]
+    #[inline]
+    pub fn is_grapheme_boundary(&self, at: usize) -> bool {
+        self.as_gap_str().is_grapheme_boundary(at)
+    }
+
+    /// See [`GrowingGapString::next_grapheme_boundary`]
+    #[inline]
+    pub fn next_grapheme_boundary(&self, at: usize) -> Option<usize> {
+        self.as_gap_str().next_grapheme_boundary(at)
+    }
+
+    /// See [`GrowingGapString::prev_grapheme_boundary`]
+    #[inline]
+    pub fn prev_grapheme_boundary(&self, at: usize) -> Option<usize> {
+        self.as_gap_str().prev_grapheme_boundary(at)
+    }
+
+    /// See [`GrowingGapString::graphemes`]
+    #[inline]
+    pub fn graphemes(&self) -> Graphemes<'_> {
+        self.as_gap_str().graphemes()
+    }
+
+    /// See [`GrowingGapString::next_word_start`]
+    #[inline]
+    pub fn next_word_start(&self, at: usize, mode: WordMode) -> Option<usize> {
+        self.as_gap_str().next_word_start(at, mode)
+    }
+
+    /// See [`GrowingGapString::next_word_end`]
+    #[inline]
+    pub fn next_word_end(&self, at: usize, mode: WordMode) -> Option<usize> {
+        self.as_gap_str().next_word_end(at, mode)
+    }
+
+    /// See [`GrowingGapString::prev_word_start`]
+    #[inline]
+    pub fn prev_word_start(&self, at: usize, mode: WordMode) -> Option<usize> {
+        self.as_gap_str().prev_word_start(at, mode)
+    }
+
+    /// See [`GrowingGapString::prev_word_end`]
+    #[inline]
+    pub fn prev_word_end(&self, at: usize, mode: WordMode) -> Option<usize> {
+        self.as_gap_str().prev_word_end(at, mode)
+    }
+
+    /// See [`GrowingGapString::words`]
+    #[inline]
+    pub fn words(&self, mode: WordMode) -> Words<'_> {
+        self.as_gap_str().words(mode)
+    }
+
+    /// See [`GrowingGapString::column_of`]
+    #[inline]
+    pub fn column_of(&self, at: usize, tab_width: usize) -> usize {
+        self.as_gap_str().column_of(at, tab_width)
+    }
+
+    /// See [`GrowingGapString::byte_at_column`]
+    #[inline]
+    pub fn byte_at_column(&self, line: usize, column: usize, tab_width: usize) -> Option<usize> {
+        self.as_gap_str().byte_at_column(line, column, tab_width)
+    }
+
+    /// See [`GrowingGapString::byte_at_column_from`]
+    #[inline]
+    pub fn byte_at_column_from(&self, line_start: usize, column: usize, tab_width: usize) -> usize {
+        self.as_gap_str()
+            .byte_at_column_from(line_start, column, tab_width)
+    }
+
+    /// See [`GrowingGapString::line_width`]
+    #[inline]
+    pub fn line_width(&self, line: usize, tab_width: usize) -> Option<usize> {
+        self.as_gap_str().line_width(line, tab_width)
+    }
+
+    /// See [`GrowingGapString::line_width_from`]
+    #[inline]
+    pub fn line_width_from(&self, line_start: usize, tab_width: usize) -> usize {
+        self.as_gap_str().line_width_from(line_start, tab_width)
+    }
+
+    /// Same as [`GrowingGapString::check_invariants`], for whichever storage is in use
+    pub fn check_invariants(&self) {
+        match &self.repr {
+            Repr::Inline(s, ..) => s.check_invariants(),
+            Repr::Heap(s) => s.check_invariants(),
+        }
+    }
+
+    /// Returns the heap allocated string
+    ///
+    /// Must only be called after spilling.
+    #[inline(always)]
+    fn heap(&mut self) -> &mut GrowingGapString<G, A> {
+        match &mut self.repr {
+            Repr::Heap(s) => s,
+            Repr::Inline(..) => unreachable!("the string should have spilled to the heap"),
+        }
+    }
+
+    #[inline]
+    fn spill_infallible(&mut self, additional: usize) {
+        self.spill(additional, false)
+            .unwrap_or_else(|_| unreachable!("infallible reservation never returns an error"));
+    }
+
+    /// Moves the inline contents to the heap with room for `additional` more bytes in the gap
+    ///
+    /// Does nothing if the contents are already on the heap. The string is left unchanged if an
+    /// error is returned.
+    fn spill(&mut self, additional: usize, fallible: bool) -> Result<(), TryReserveError> {
+        let Repr::Inline(inline, grower, alloc) = &mut self.repr else {
+            return Ok(());
+        };
+
+        let [start, end] = inline.get_parts();
+        let decision = grower.decide(&GapContext {
+            kind: OpKind::Reserve,
+            start,
+            end,
+            position: start.len(),
+            requested: additional,
+            removed: 0,
+            gap_len: 0,
+            history: History::new(),
+        });
+        let gap_size = match decision {
+            GapDecision::Grow(by) => by.max(additional),
+            GapDecision::Keep | GapDecision::Shrink(_) => additional,
+        };
+
+        // SAFETY: the allocator is moved into the buffer which is only dropped once the inline
+        // variant has been overwritten, so it is never dropped twice
+        let mut buf = ManuallyDrop::new(RawGapBuf::new_in(unsafe { ptr::read(alloc) }));
+        let by = inline.len().saturating_add(gap_size);
+        if fallible {
+            buf.try_grow_gap(by)?;
+        } else {
+            buf.grow_gap(by);
+        }
+        buf.grow_start_with_slice(start.as_bytes());
+        buf.grow_start_with_slice(end.as_bytes());
+        buf.move_gap_start_to(start.len());
+
+        // SAFETY: the grower is read exactly once and the inline variant is overwritten right
+        // after without being dropped, nothing between the two can panic. The inline storage
+        // only holds bytes and its allocator was moved into the buffer, so skipping its drop does
+        // not leak anything.
+        unsafe {
+            let grower = ptr::read(grower);
+            ptr::write(
+                &mut self.repr,
+                Repr::Heap(GrowingGapString::from_raw(
+                    ManuallyDrop::into_inner(buf),
+                    grower,
+                )),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl<const N: usize, G: ContextGrower<str>, A: Allocator> fmt::Display for SmallGapString<N, G, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [start, end] = self.get_parts();
+        fmt::Display::fmt(&GapStr::new(start, end), f)
+    }
+}
+
+impl<const N: usize, G: ContextGrower<str>, A: Allocator> fmt::Debug for SmallGapString<N, G, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [start, end] = self.get_parts();
+        fmt::Debug::fmt(&GapStr::new(start, end), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use rstest_reuse::apply;
+
+    use crate::{
+        error::EditError,
+        gapstr::{GapString, WordMode},
+        testing::*,
+    };
+
+    use super::SmallGapString;
+
+    #[test]
+    fn size() {
+        // the inline storage fits in the space of the heap string
+        assert_eq!(size_of::<SmallGapString>(), size_of::<GapString>());
+    }
+
+    #[apply(grower_template)]
+    fn insert_spills(g: TestGrower) {
+        let mut s = SmallGapString::<8, _>::with_grower(g);
+        s.insert("Hllo", 0);
+        s.insert("é", 1);
+        assert!(s.is_inline());
+        assert_eq!(s.get_parts(), ["Hé", "llo"]);
+
+        s.insert(", World", 6);
+        assert!(!s.is_inline());
+        assert_eq!(s.get(..).unwrap(), "Héllo, World");
+        s.insert("!", s.len());
+        assert_eq!(s.to_string(), "Héllo, World!");
+        assert_eq!(s.get_slice(1..5), Some("éll"));
+    }
+
+    #[apply(grower_template)]
+    fn remove_replace(g: TestGrower) {
+        let mut s = SmallGapString::<4, _>::with_grower(g);
+        s.insert("abcd", 0);
+        assert_eq!(s.remove(1..3), "bc");
+        s.replace_range(1..1, "é");
+        assert!(s.is_inline());
+        assert_eq!(s.get_slice(..), Some("aéd"));
+
+        s.replace_range(0..1, "Hello ");
+        assert!(!s.is_inline());
+        assert_eq!(s.get(..).unwrap(), "Hello éd");
+        assert_eq!(s.remove(6..), "éd");
+        assert_eq!(format!("{s:?}"), "\"Hello \"");
+    }
+
+    #[apply(grower_template)]
+    fn try_edit(g: TestGrower) {
+        let mut s = SmallGapString::<2, _>::with_grower(g);
+        s.insert("é", 0);
+        assert_eq!(
+            s.try_insert("abc", 1),
+            Err(EditError::NotCharBoundary { at: 1 })
+        );
+        assert_eq!(
+            s.try_insert("abc", 3),
+            Err(EditError::OutOfBounds { at: 3, len: 2 })
+        );
+        assert!(s.is_inline());
+
+        s.try_replace_range(.., "abc").unwrap();
+        assert!(!s.is_inline());
+        assert_eq!(s.try_remove(0..1), Ok("a"));
+        s.try_insert("é", 2).unwrap();
+        assert_eq!(s.get(..).unwrap(), "bcé");
+        assert_eq!(
+            s.try_remove(0..3),
+            Err(EditError::NotCharBoundary { at: 3 })
+        );
+
+        let mut s = SmallGapString::<2, _>::with_grower(g);
+        s.try_reserve(2).unwrap();
+        assert!(s.is_inline());
+        s.try_reserve(3).unwrap();
+        assert!(!s.is_inline());
+        assert!(s.gap_len() >= 3);
+        assert!(s.is_empty());
+    }
+
+    #[apply(grower_template)]
+    fn queries(g: TestGrower) {
+        let mut s = SmallGapString::<16, _>::with_grower(g);
+        s.insert("a\tb中\ne\u{301}x", 0);
+        for spill in [false, true] {
+            if spill {
+                s.insert(" foo_bar", s.len());
+                s.remove(11..);
+            }
+            assert_eq!(s.is_inline(), !spill);
+            s.check_invariants();
+
+            assert_eq!(s.as_gap_str(), "a\tb中\ne\u{301}x");
+            assert_eq!(s.line_count(), 2);
+            assert_eq!(s.line_range(1), Some(7..11));
+            assert_eq!(s.line_of(8), 1);
+            assert_eq!((s.line_start(5), s.line_end(5)), (0, 6));
+            assert!(!s.is_grapheme_boundary(8));
+            assert_eq!(s.next_grapheme_boundary(7), Some(10));
+            assert_eq!(s.prev_grapheme_boundary(10), Some(7));
+            assert_eq!(s.graphemes().count(), 7);
+            assert_eq!(s.next_word_start(0, WordMode::Unicode), Some(2));
+            assert_eq!(s.prev_word_end(11, WordMode::Unicode), Some(6));
+            assert_eq!(s.words(WordMode::Unicode).count(), 4);
+            assert_eq!(s.column_of(6, 4), 7);
+            assert_eq!(s.byte_at_column(0, 5, 4), Some(3));
+            assert_eq!(s.line_width(1, 4), Some(2));
+            assert_eq!(s.byte_at_column_from(0, 5, 4), 3);
+            assert_eq!(s.line_width_from(7, 4), 2);
+            assert_eq!(s.chunk_at(11), Some(""));
+            assert_eq!(s.chunk_at(12), None);
+        }
+    }
+
+    #[apply(grower_template)]
+    fn clear_shrink(g: TestGrower) {
+        let mut s = SmallGapString::<8, _>::with_grower(g);
+        s.insert("abc", 0);
+        assert_eq!(s.chunk_at(1), Some("bc"));
+        assert_eq!(
+            s.try_shrink_gap(6),
+            Err(EditError::OutOfBounds { at: 6, len: 5 })
+        );
+        s.shrink_gap(5);
+        assert_eq!(s.gap_len(), 5);
+        s.clear();
+        assert!(s.is_inline() && s.is_empty());
+
+        s.insert("Hello, World", 0);
+        s.shrink_gap(s.gap_len());
+        assert_eq!(s.gap_len(), 0);
+        s.clear();
+        assert!(!s.is_inline() && s.is_empty());
+        s.check_invariants();
+    }
+
+    #[apply(grower_template)]
+    fn as_heap_mut(g: TestGrower) {
+        let mut s = SmallGapString::<8, _>::with_grower(g);
+        s.insert("abc", 0);
+        let heap = s.as_heap_mut();
+        heap.protect(0..1).unwrap();
+        heap.edit_cursor(3).insert("d");
+        assert!(!s.is_inline());
+        assert_eq!(s.get(..).unwrap(), "abcd");
+        assert_eq!(
+            s.try_remove(0..1),
+            Err(EditError::Protected { start: 0, end: 1 })
+        );
+
+        let mut s = SmallGapString::<8, _>::with_grower(g);
+        s.as_heap_mut().insert("a", 0);
+        assert!(!s.is_inline());
+        s.check_invariants();
+    }
+
+    #[apply(grower_template)]
+    #[should_panic]
+    fn insert_panics(g: TestGrower) {
+        let mut s = SmallGapString::<4, _>::with_grower(g);
+        s.insert("Hi", 3);
+    }
+}
@@ -0,0 +1,296 @@
+use alloc::borrow::Cow;
+use core::{iter::FusedIterator, ops::Range};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::{allocator::Allocator, grower::ContextGrower, view::GapStr};
+
+use super::{Graphemes, GrowingGapString};
+
+impl<G: ContextGrower<str>, A: Allocator> GrowingGapString<G, A> {
+    /// Returns the display column of the provided byte position on its line
+    ///
+    /// Columns are counted per extended grapheme cluster using their East Asian width, so wide
+    /// CJK chars take two columns while combining marks and zero width joiners don't take any.
+    /// Tabs advance to the next multiple of `tab_width`, and other control chars are zero
+    /// width. A position inside of a cluster returns the column of the cluster.
+    ///
+    /// # Panics
+    /// If the position is greater than [`GrowingGapString::len`], or `tab_width` is 0.
+    #[inline]
+    pub fn column_of(&self, at: usize, tab_width: usize) -> usize {
+        self.get(..).unwrap().column_of(at, tab_width)
+    }
+
+    /// Returns the byte position of the provided display column on the provided line
+    ///
+    /// If the column is in the middle of a wide char or a tab, the position of that char is
+    /// returned. If the column is past the end of the line, the position of the line end is
+    /// returned. See [`GrowingGapString::column_of`] for how columns are counted.
+    ///
+    /// The line is found by searching the string from its start, use
+    /// [`GrowingGapString::byte_at_column_from`] when the start of the line is already known.
+    ///
+    /// Returns [`None`] if the line does not exist.
+    ///
+    /// # Panics
+    /// If `tab_width` is 0.
+    #[inline]
+    pub fn byte_at_column(&self, line: usize, column: usize, tab_width: usize) -> Option<usize> {
+        self.get(..)
+            .unwrap()
+            .byte_at_column(line, column, tab_width)
+    }
+
+    /// Same as [`GrowingGapString::byte_at_column`] but takes the byte position of the start of
+    /// the line instead of its index
+    ///
+    /// Finding a line by its index searches the string from its start, while the start of a
+    /// line returned by [`GrowingGapString::line_start`] or [`GrowingGapString::line_range`] can
+    /// be reused to only visit the line itself.
+    ///
+    /// # Panics
+    /// If the position is greater than [`GrowingGapString::len`] or is not the start of a line,
+    /// or `tab_width` is 0.
+    #[inline]
+    pub fn byte_at_column_from(&self, line_start: usize, column: usize, tab_width: usize) -> usize {
+        self.get(..)
+            .unwrap()
+            .byte_at_column_from(line_start, column, tab_width)
+    }
+
+    /// Returns the display width of the provided line
+    ///
+    /// The line is found by searching the string from its start, use
+    /// [`GrowingGapString::line_width_from`] when the start of the line is already known.
+    ///
+    /// Returns [`None`] if the line does not exist.
+    ///
+    /// # Panics
+    /// If `tab_width` is 0.
+    #[inline]
+    pub fn line_width(&self, line: usize, tab_width: usize) -> Option<usize> {
+        self.get(..).unwrap().line_width(line, tab_width)
+    }
+
+    /// Same as [`GrowingGapString::line_width`] but takes the byte position of the start of the
+    /// line instead of its index
+    ///
+    /// See [`GrowingGapString::byte_at_column_from`].
+    ///
+    /// # Panics
+    /// If the position is greater than [`GrowingGapString::len`] or is not the start of a line,
+    /// or `tab_width` is 0.
+    #[inline]
+    pub fn line_width_from(&self, line_start: usize, tab_width: usize) -> usize {
+        self.get(..).unwrap().line_width_from(line_start, tab_width)
+    }
+
+    /// Returns an iterator over the clusters of a line range and the columns they occupy
+    #[inline]
+    pub(crate) fn columns(&self, line: Range<usize>, tab_width: usize) -> Columns<'_> {
+        self.get(..).unwrap().columns(line, tab_width)
+    }
+}
+
+impl<'a> GapStr<'a> {
+    /// See [`GrowingGapString::column_of`]
+    pub fn column_of(&self, at: usize, tab_width: usize) -> usize {
+        let line_start = self.line_start(at);
+        self.columns(line_start..self.line_end(at), tab_width)
+            .take_while(|c| c.bytes.end <= at)
+            .last()
+            .map_or(0, |c| c.columns.end)
+    }
+
+    /// See [`GrowingGapString::byte_at_column`]
+    pub fn byte_at_column(&self, line: usize, column: usize, tab_width: usize) -> Option<usize> {
+        let line = self.line_range(line)?;
+        Some(self.byte_at_column_in(line, column, tab_width))
+    }
+
+    /// See [`GrowingGapString::byte_at_column_from`]
+    pub fn byte_at_column_from(&self, line_start: usize, column: usize, tab_width: usize) -> usize {
+        self.byte_at_column_in(self.line_from(line_start), column, tab_width)
+    }
+
+    /// See [`GrowingGapString::line_width`]
+    pub fn line_width(&self, line: usize, tab_width: usize) -> Option<usize> {
+        let line = self.line_range(line)?;
+        Some(self.line_width_in(line, tab_width))
+    }
+
+    /// See [`GrowingGapString::line_width_from`]
+    pub fn line_width_from(&self, line_start: usize, tab_width: usize) -> usize {
+        self.line_width_in(self.line_from(line_start), tab_width)
+    }
+
+    /// Returns the byte range of the line starting at the provided position
+    #[inline]
+    fn line_from(&self, line_start: usize) -> Range<usize> {
+        assert!(
+            self.line_start(line_start) == line_start,
+            "position should always be the start of a line"
+        );
+        line_start..self.line_end(line_start)
+    }
+
+    #[inline]
+    fn byte_at_column_in(&self, line: Range<usize>, column: usize, tab_width: usize) -> usize {
+        let end = line.end;
+        self.columns(line, tab_width)
+            .find(|c| c.columns.end > column)
+            .map_or(end, |c| c.bytes.start)
+    }
+
+    #[inline]
+    fn line_width_in(&self, line: Range<usize>, tab_width: usize) -> usize {
+        self.columns(line, tab_width)
+            .last()
+            .map_or(0, |c| c.columns.end)
+    }
+
+    /// Returns an iterator over the clusters of a line range and the columns they occupy
+    pub(crate) fn columns(&self, line: Range<usize>, tab_width: usize) -> Columns<'a> {
+        assert!(tab_width > 0, "tab width should never be 0");
+        let text = self.get(line.clone()).unwrap();
+        Columns {
+            graphemes: text.graphemes(),
+            byte: line.start,
+            column: 0,
+            tab_width,
+        }
+    }
+}
+
+/// The byte and column range of a single extended grapheme cluster
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ClusterColumns {
+    pub(crate) bytes: Range<usize>,
+    pub(crate) columns: Range<usize>,
+}
+
+/// An iterator over the extended grapheme clusters of a line and their columns
+#[derive(Clone, Debug)]
+pub(crate) struct Columns<'a> {
+    graphemes: Graphemes<'a>,
+    byte: usize,
+    column: usize,
+    tab_width: usize,
+}
+
+impl Iterator for Columns<'_> {
+    type Item = ClusterColumns;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cluster = self.graphemes.next()?;
+        let bytes = self.byte..self.byte + cluster.len();
+        let start = self.column;
+        self.column = if cluster == "\t" {
+            (start / self.tab_width + 1) * self.tab_width
+        } else {
+            start + cluster_width(cluster)
+        };
+        self.byte = bytes.end;
+
+        Some(ClusterColumns {
+            bytes,
+            columns: start..self.column,
+        })
+    }
+}
+
+impl FusedIterator for Columns<'_> {}
+
+/// Returns the display width of a single extended grapheme cluster
+fn cluster_width(cluster: GapStr<'_>) -> usize {
+    if cluster.chars().next().is_some_and(char::is_control) {
+        return 0;
+    }
+
+    // only a cluster that straddles the gap is copied, which is at most a few bytes
+    let s = match cluster.as_parts() {
+        ["", s] | [s, ""] => Cow::Borrowed(s),
+        [start, end] => Cow::Owned([start, end].concat()),
+    };
+    s.width()
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use rstest_reuse::apply;
+
+    use crate::testing::*;
+
+    use super::GrowingGapString;
+
+    const TEXT: &str = "a\tb中e\u{301}👨\u{200d}👩\u{200d}👧c\r\n\t\tx";
+
+    #[apply(grower_template)]
+    fn columns(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert(TEXT, 0);
+
+        // (byte, column) pairs of the first line with a tab width of 4
+        let first_line = [
+            (0, 0),
+            (1, 1),
+            (2, 4),
+            (3, 5),
+            (6, 7),
+            (9, 8),
+            (27, 10),
+            (28, 11),
+            (29, 11),
+        ];
+        for gap in (0..=TEXT.len()).filter(|i| TEXT.is_char_boundary(*i)) {
+            s_buf.buf.move_gap_start_to(gap);
+            for (byte, column) in first_line {
+                assert_eq!(s_buf.column_of(byte, 4), column);
+            }
+            // inside of a cluster
+            assert_eq!(s_buf.column_of(7, 4), 7);
+            assert_eq!(s_buf.column_of(12, 4), 8);
+
+            assert_eq!(s_buf.byte_at_column(0, 0, 4), Some(0));
+            assert_eq!(s_buf.byte_at_column(0, 2, 4), Some(1));
+            assert_eq!(s_buf.byte_at_column(0, 3, 4), Some(1));
+            assert_eq!(s_buf.byte_at_column(0, 4, 4), Some(2));
+            assert_eq!(s_buf.byte_at_column(0, 6, 4), Some(3));
+            assert_eq!(s_buf.byte_at_column(0, 9, 4), Some(9));
+            assert_eq!(s_buf.byte_at_column(0, 10, 4), Some(27));
+            assert_eq!(s_buf.byte_at_column(0, 100, 4), Some(29));
+            assert_eq!(s_buf.line_width(0, 4), Some(11));
+
+            assert_eq!(s_buf.column_of(32, 8), 16);
+            assert_eq!(s_buf.column_of(32, 3), 6);
+            assert_eq!(s_buf.byte_at_column(1, 7, 8), Some(30));
+            assert_eq!(s_buf.byte_at_column(1, 16, 8), Some(32));
+            assert_eq!(s_buf.byte_at_column(2, 0, 8), None);
+            assert_eq!(s_buf.line_width(1, 2), Some(5));
+
+            // the same queries from the start of the lines
+            assert_eq!(s_buf.byte_at_column_from(0, 10, 4), 27);
+            assert_eq!(s_buf.byte_at_column_from(0, 100, 4), 29);
+            assert_eq!(s_buf.line_width_from(0, 4), 11);
+            assert_eq!(s_buf.byte_at_column_from(30, 16, 8), 32);
+            assert_eq!(s_buf.line_width_from(30, 2), 5);
+        }
+    }
+
+    #[apply(grower_template)]
+    #[should_panic]
+    fn not_line_start_panics(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert(TEXT, 0);
+        s_buf.line_width_from(31, 4);
+    }
+
+    #[apply(grower_template)]
+    #[should_panic]
+    fn zero_tab_width_panics(g: TestGrower) {
+        let s_buf = GrowingGapString::with_grower(g);
+        s_buf.column_of(0, 0);
+    }
+}
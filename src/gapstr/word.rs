@@ -0,0 +1,469 @@
+use alloc::collections::VecDeque;
+use core::{iter::FusedIterator, ops::Range};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    allocator::Allocator,
+    grower::ContextGrower,
+    utils::{find_byte, rfind_byte},
+    view::GapStr,
+};
+
+use super::GrowingGapString;
+
+/// The class of a char when segmenting words with [`WordMode::Classes`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CharClass {
+    Whitespace,
+    Punctuation,
+    Word,
+}
+
+impl CharClass {
+    /// The default "programmer word" classification
+    ///
+    /// Alphanumeric chars and `_` are identifier chars, whitespace is whitespace and everything
+    /// else is punctuation.
+    #[inline]
+    pub fn of(c: char) -> Self {
+        if c.is_alphanumeric() || c == '_' {
+            Self::Word
+        } else if c.is_whitespace() {
+            Self::Whitespace
+        } else {
+            Self::Punctuation
+        }
+    }
+}
+
+/// How word boundaries are determined
+#[derive(Clone, Copy, Debug, Default)]
+pub enum WordMode {
+    /// Word boundaries as defined by UAX #29
+    ///
+    /// Segments that contain at least one alphanumeric char are considered words.
+    #[default]
+    Unicode,
+    /// A word is a run of chars with the same [`CharClass`]
+    ///
+    /// Runs of [`CharClass::Whitespace`] are never considered words, so runs of punctuation are
+    /// words on their own.
+    Classes(fn(char) -> CharClass),
+}
+
+impl WordMode {
+    /// The "programmer word" mode, using [`CharClass::of`] to classify chars
+    #[inline(always)]
+    pub const fn programmer() -> Self {
+        Self::Classes(CharClass::of)
+    }
+}
+
+impl<G: ContextGrower<str>, A: Allocator> GrowingGapString<G, A> {
+    /// Returns the start position of the first word that starts after the provided position
+    ///
+    /// # Panics
+    /// If the position is greater than [`GrowingGapString::len`].
+    #[inline]
+    pub fn next_word_start(&self, at: usize, mode: WordMode) -> Option<usize> {
+        self.get(..).unwrap().next_word_start(at, mode)
+    }
+
+    /// Returns the end position of the first word that ends after the provided position
+    ///
+    /// # Panics
+    /// If the position is greater than [`GrowingGapString::len`].
+    #[inline]
+    pub fn next_word_end(&self, at: usize, mode: WordMode) -> Option<usize> {
+        self.get(..).unwrap().next_word_end(at, mode)
+    }
+
+    /// Returns the start position of the last word that starts before the provided position
+    ///
+    /// # Panics
+    /// If the position is greater than [`GrowingGapString::len`].
+    #[inline]
+    pub fn prev_word_start(&self, at: usize, mode: WordMode) -> Option<usize> {
+        self.get(..).unwrap().prev_word_start(at, mode)
+    }
+
+    /// Returns the end position of the last word that ends before the provided position
+    ///
+    /// # Panics
+    /// If the position is greater than [`GrowingGapString::len`].
+    #[inline]
+    pub fn prev_word_end(&self, at: usize, mode: WordMode) -> Option<usize> {
+        self.get(..).unwrap().prev_word_end(at, mode)
+    }
+
+    /// Returns the range of the segment containing the provided position
+    ///
+    /// The segment can be a word or the whitespace and punctuation between words. This is what
+    /// is selected when double clicking in most editors. Returns an empty range at the end of
+    /// the string.
+    ///
+    /// # Panics
+    /// If the position is greater than [`GrowingGapString::len`].
+    #[inline]
+    pub fn word_range_at(&self, at: usize, mode: WordMode) -> Range<usize> {
+        self.get(..).unwrap().word_range_at(at, mode)
+    }
+
+    /// Returns an iterator over the words and their byte positions
+    ///
+    /// Words that straddle the gap are returned as a single [`GapStr`].
+    #[inline]
+    pub fn words(&self, mode: WordMode) -> Words<'_> {
+        self.get(..).unwrap().words(mode)
+    }
+}
+
+impl<'a> GapStr<'a> {
+    /// See [`GrowingGapString::next_word_start`]
+    pub fn next_word_start(&self, at: usize, mode: WordMode) -> Option<usize> {
+        self.find_segment(at, mode, |r, is_word| is_word && r.start > at)
+            .map(|r| r.start)
+    }
+
+    /// See [`GrowingGapString::next_word_end`]
+    pub fn next_word_end(&self, at: usize, mode: WordMode) -> Option<usize> {
+        self.find_segment(at, mode, |r, is_word| is_word && r.end > at)
+            .map(|r| r.end)
+    }
+
+    /// See [`GrowingGapString::prev_word_start`]
+    pub fn prev_word_start(&self, at: usize, mode: WordMode) -> Option<usize> {
+        self.rfind_segment(at, mode, |r, is_word| is_word && r.start < at)
+            .map(|r| r.start)
+    }
+
+    /// See [`GrowingGapString::prev_word_end`]
+    pub fn prev_word_end(&self, at: usize, mode: WordMode) -> Option<usize> {
+        self.rfind_segment(at, mode, |r, is_word| is_word && r.end < at)
+            .map(|r| r.end)
+    }
+
+    /// See [`GrowingGapString::word_range_at`]
+    pub fn word_range_at(&self, at: usize, mode: WordMode) -> Range<usize> {
+        self.find_segment(at, mode, |r, _| r.end > at)
+            .unwrap_or(at..at)
+    }
+
+    /// Returns an iterator over the words and their byte positions
+    #[inline]
+    pub fn words(&self, mode: WordMode) -> Words<'a> {
+        Words {
+            text: *self,
+            mode,
+            next_line: 0,
+            words: VecDeque::new(),
+        }
+    }
+
+    /// Returns the range of the line containing the provided position, including its `\n`
+    fn line_window(&self, at: usize) -> Range<usize> {
+        let parts = self.as_bytes().as_parts();
+        let start = rfind_byte(parts, b'\n', at).map_or(0, |i| i + 1);
+        let end = find_byte(parts, b'\n', at).map_or(self.len(), |i| i + 1);
+        start..end
+    }
+
+    /// Calls the provided closure with every segment in the window
+    ///
+    /// Word boundaries never cross a line break, as such segmenting a single line at a time
+    /// gives the same results as segmenting the whole string. Only the words next to the gap are
+    /// copied if the line straddles it.
+    fn segments(
+        &self,
+        window: Range<usize>,
+        mode: WordMode,
+        mut f: impl FnMut(Range<usize>, bool),
+    ) {
+        let text = self.get(window.clone()).unwrap();
+        match mode {
+            WordMode::Unicode => match text.as_parts() {
+                ["", s] | [s, ""] => unicode_segments(s, window.start, &mut f),
+                [start, end] => {
+                    // each side segmented on its own only differs near the gap, so the segments
+                    // around it are copied and segmented again with the context of both sides
+                    let seam_start = seam_start(start);
+                    let seam_end = seam_end(end);
+                    for (i, s) in start.split_word_bound_indices() {
+                        if i >= seam_start {
+                            break;
+                        }
+                        unicode_segment(s, window.start + i, &mut f);
+                    }
+                    let seam = [&start[seam_start..], &end[..seam_end]].concat();
+                    unicode_segments(&seam, window.start + seam_start, &mut f);
+                    let end_start = window.start + start.len();
+                    for (i, s) in end.split_word_bound_indices() {
+                        if i >= seam_end {
+                            unicode_segment(s, end_start + i, &mut f);
+                        }
+                    }
+                }
+            },
+            WordMode::Classes(classify) => {
+                let mut run: Option<(usize, CharClass)> = None;
+                for (i, c) in text.char_indices() {
+                    let class = classify(c);
+                    match run {
+                        Some((_, run_class)) if run_class == class => {}
+                        Some((start, run_class)) => {
+                            f(
+                                window.start + start..window.start + i,
+                                run_class != CharClass::Whitespace,
+                            );
+                            run = Some((i, class));
+                        }
+                        None => run = Some((i, class)),
+                    }
+                }
+
+                if let Some((start, run_class)) = run {
+                    f(
+                        window.start + start..window.end,
+                        run_class != CharClass::Whitespace,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Returns the first segment at or after the line of the provided position that matches
+    fn find_segment(
+        &self,
+        at: usize,
+        mode: WordMode,
+        mut pred: impl FnMut(&Range<usize>, bool) -> bool,
+    ) -> Option<Range<usize>> {
+        assert!(self.len() >= at, "position should never be out of bounds");
+        let mut window = self.line_window(at);
+        loop {
+            let mut found = None;
+            self.segments(window.clone(), mode, |r, is_word| {
+                if found.is_none() && pred(&r, is_word) {
+                    found = Some(r);
+                }
+            });
+
+            if found.is_some() || window.end >= self.len() {
+                return found;
+            }
+            window = self.line_window(window.end);
+        }
+    }
+
+    /// Returns the last segment at or before the line of the provided position that matches
+    fn rfind_segment(
+        &self,
+        at: usize,
+        mode: WordMode,
+        mut pred: impl FnMut(&Range<usize>, bool) -> bool,
+    ) -> Option<Range<usize>> {
+        assert!(self.len() >= at, "position should never be out of bounds");
+        let mut window = self.line_window(at);
+        loop {
+            let mut found = None;
+            self.segments(window.clone(), mode, |r, is_word| {
+                if pred(&r, is_word) {
+                    found = Some(r);
+                }
+            });
+
+            if found.is_some() || window.start == 0 {
+                return found;
+            }
+            window = self.line_window(window.start - 1);
+        }
+    }
+}
+
+/// Calls the provided closure with a UAX #29 segment starting at the provided position
+#[inline(always)]
+fn unicode_segment(s: &str, start: usize, f: &mut impl FnMut(Range<usize>, bool)) {
+    f(start..start + s.len(), s.chars().any(char::is_alphanumeric));
+}
+
+/// Calls the provided closure with every UAX #29 segment of a string starting at the provided
+/// position
+fn unicode_segments(s: &str, start: usize, f: &mut impl FnMut(Range<usize>, bool)) {
+    for (i, s) in s.split_word_bound_indices() {
+        unicode_segment(s, start + i, f);
+    }
+}
+
+/// Returns true if the segment is made of regional indicators, whose boundaries depend on every
+/// indicator before them
+#[inline(always)]
+fn is_regional_indicator(s: &str) -> bool {
+    s.starts_with(|c| matches!(c, '\u{1F1E6}'..='\u{1F1FF}'))
+}
+
+/// Returns the start of the segments before the gap that may change with the text after it
+///
+/// The boundaries of a segment can depend on the two chars around it, so the last two segments
+/// are included along with any regional indicators before them.
+fn seam_start(start: &str) -> usize {
+    let mut segments = start.split_word_bound_indices().rev();
+    let mut seam = segments.nth(1).map_or(0, |(i, _)| i);
+    for (i, s) in segments {
+        if !is_regional_indicator(s) {
+            break;
+        }
+        seam = i;
+    }
+    seam
+}
+
+/// Returns the end of the segments after the gap that may change with the text before it
+///
+/// Same as [`seam_start`] but for the first segments after the gap.
+fn seam_end(end: &str) -> usize {
+    let mut segments = end.split_word_bound_indices().skip(2);
+    for (i, s) in &mut segments {
+        if !is_regional_indicator(s) {
+            return i;
+        }
+    }
+    end.len()
+}
+
+/// An iterator over the words of a [`GapStr`], and their byte positions
+#[derive(Clone, Debug)]
+pub struct Words<'a> {
+    text: GapStr<'a>,
+    mode: WordMode,
+    next_line: usize,
+    words: VecDeque<Range<usize>>,
+}
+
+impl<'a> Iterator for Words<'a> {
+    type Item = (usize, GapStr<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.words.is_empty() && self.next_line < self.text.len() {
+            let window = self.text.line_window(self.next_line);
+            self.text.segments(window.clone(), self.mode, |r, is_word| {
+                if is_word {
+                    self.words.push_back(r);
+                }
+            });
+            self.next_line = window.end;
+        }
+
+        let r = self.words.pop_front()?;
+        Some((r.start, self.text.get(r).unwrap()))
+    }
+}
+
+impl FusedIterator for Words<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use rstest_reuse::apply;
+    use unicode_segmentation::UnicodeSegmentation;
+
+    use crate::testing::*;
+
+    use super::{CharClass, GrowingGapString, WordMode};
+
+    const TEXT: &str = "let foo_bar = a.b(\"can't\");\n  x2 += 1.5;";
+
+    #[apply(grower_template)]
+    fn words(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert(TEXT, 0);
+
+        for gap in (0..=TEXT.len()).filter(|i| TEXT.is_char_boundary(*i)) {
+            s_buf.buf.move_gap_start_to(gap);
+            let words: Vec<_> = s_buf
+                .words(WordMode::Unicode)
+                .map(|(i, w)| (i, w.to_string()))
+                .collect();
+            assert_eq!(
+                words,
+                [
+                    (0, "let"),
+                    (4, "foo_bar"),
+                    (14, "a.b"),
+                    (19, "can't"),
+                    (30, "x2"),
+                    (36, "1.5"),
+                ]
+                .map(|(i, s)| (i, s.to_string()))
+            );
+
+            let words: Vec<_> = s_buf
+                .words(WordMode::programmer())
+                .map(|(_, w)| w.to_string())
+                .collect();
+            assert_eq!(
+                words,
+                [
+                    "let", "foo_bar", "=", "a", ".", "b", "(\"", "can", "'", "t", "\");", "x2",
+                    "+=", "1", ".", "5", ";"
+                ]
+            );
+        }
+    }
+
+    #[apply(grower_template)]
+    fn words_around_gap(g: TestGrower) {
+        let text = "🇺🇸🇬🇧🇫🇷a🇩🇪🇮🇹 e\u{301}te\u{301} can't 3.14,15 x\u{200d}y 日本語 a.b.c\u{301}";
+        let expected: Vec<_> = text
+            .split_word_bound_indices()
+            .filter(|(_, w)| w.chars().any(char::is_alphanumeric))
+            .map(|(i, w)| (i, w.to_string()))
+            .collect();
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert(text, 0);
+
+        for gap in (0..=text.len()).filter(|i| text.is_char_boundary(*i)) {
+            s_buf.buf.move_gap_start_to(gap);
+            let words: Vec<_> = s_buf
+                .words(WordMode::Unicode)
+                .map(|(i, w)| (i, w.to_string()))
+                .collect();
+            assert_eq!(words, expected, "gap at {gap}");
+        }
+    }
+
+    #[apply(grower_template)]
+    fn word_motion(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert(TEXT, 0);
+        s_buf.buf.move_gap_start_to(16);
+
+        let unicode = WordMode::Unicode;
+        assert_eq!(s_buf.next_word_start(0, unicode), Some(4));
+        assert_eq!(s_buf.next_word_start(4, unicode), Some(14));
+        assert_eq!(s_buf.next_word_end(14, unicode), Some(17));
+        assert_eq!(s_buf.next_word_start(25, unicode), Some(30));
+        assert_eq!(s_buf.next_word_end(36, unicode), Some(39));
+        assert_eq!(s_buf.next_word_start(36, unicode), None);
+        assert_eq!(s_buf.prev_word_start(30, unicode), Some(19));
+        assert_eq!(s_buf.prev_word_start(31, unicode), Some(30));
+        assert_eq!(s_buf.prev_word_end(30, unicode), Some(24));
+        assert_eq!(s_buf.prev_word_start(0, unicode), None);
+        assert_eq!(s_buf.word_range_at(15, unicode), 14..17);
+        assert_eq!(s_buf.word_range_at(12, unicode), 12..13);
+        assert_eq!(s_buf.word_range_at(TEXT.len(), unicode), 40..40);
+
+        let programmer = WordMode::programmer();
+        assert_eq!(s_buf.next_word_start(14, programmer), Some(15));
+        assert_eq!(s_buf.next_word_end(14, programmer), Some(15));
+        assert_eq!(s_buf.prev_word_start(17, programmer), Some(16));
+        assert_eq!(s_buf.word_range_at(15, programmer), 15..16);
+
+        // treat `.` as an identifier char
+        let dotted = WordMode::Classes(|c| match c {
+            '.' => CharClass::Word,
+            c => CharClass::of(c),
+        });
+        assert_eq!(s_buf.word_range_at(15, dotted), 14..17);
+    }
+}
@@ -0,0 +1,427 @@
+use alloc::vec::Vec;
+use core::{iter::FusedIterator, ops::Range};
+
+use crate::{allocator::Allocator, grower::ContextGrower};
+
+use super::{width::ClusterColumns, GrowingGapString};
+
+/// Soft wrap layout of a [`GrowingGapString`]
+///
+/// Splits every logical line into visual lines that fit into a viewport width. Lines are broken
+/// after whitespace when possible, and in the middle of a word otherwise. Whitespace is allowed
+/// to hang past the viewport width so that a visual line never starts with the whitespace that
+/// separates it from the previous one.
+///
+/// The layout does not borrow the string. After editing the string, call [`SoftWrap::update`]
+/// with the edited range to recompute only the affected lines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SoftWrap {
+    width: usize,
+    tab_width: usize,
+    lines: Vec<WrappedLine>,
+}
+
+/// The layout of a single logical line
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct WrappedLine {
+    /// Length of the line in bytes including its `\n`
+    len: usize,
+    /// Positions relative to the line start where visual lines other than the first one start
+    breaks: Vec<usize>,
+}
+
+impl WrappedLine {
+    #[inline(always)]
+    fn rows(&self) -> usize {
+        self.breaks.len() + 1
+    }
+}
+
+/// A single visual line of a [`SoftWrap`] layout
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VisualLine {
+    /// The logical line this visual line is a part of
+    pub line: usize,
+    /// The index of the visual line in its logical line
+    pub row: usize,
+    /// The byte range of the visual line, excluding the line break
+    pub bytes: Range<usize>,
+}
+
+impl SoftWrap {
+    /// Computes the layout of the whole string
+    ///
+    /// See [`GrowingGapString::column_of`] for how the width of the text is computed.
+    ///
+    /// # Panics
+    /// If `width` or `tab_width` is 0.
+    pub fn new<G: ContextGrower<str>, A: Allocator>(
+        text: &GrowingGapString<G, A>,
+        width: usize,
+        tab_width: usize,
+    ) -> Self {
+        assert!(width > 0, "wrap width should never be 0");
+        let mut wrap = Self {
+            width,
+            tab_width,
+            lines: Vec::new(),
+        };
+        wrap.lines = wrap.wrap_lines(text, 0, text.len());
+        wrap
+    }
+
+    /// Returns the viewport width
+    #[inline(always)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the number of logical lines
+    #[inline(always)]
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns the number of visual lines
+    #[inline]
+    pub fn visual_line_count(&self) -> usize {
+        self.lines.iter().map(WrappedLine::rows).sum()
+    }
+
+    /// Returns an iterator over the visual lines of the provided logical line range
+    ///
+    /// # Panics
+    /// If the range is out of bounds.
+    pub fn visual_lines(&self, lines: Range<usize>) -> VisualLines<'_> {
+        let offset = self.lines[..lines.start].iter().map(|l| l.len).sum();
+        VisualLines {
+            lines: self.lines[lines.clone()].iter(),
+            line: lines.start,
+            row: 0,
+            offset,
+        }
+    }
+
+    /// Returns the visual line index and the column in that visual line of a byte position
+    ///
+    /// # Panics
+    /// If the position is greater than [`GrowingGapString::len`].
+    pub fn to_visual<G: ContextGrower<str>, A: Allocator>(
+        &self,
+        text: &GrowingGapString<G, A>,
+        at: usize,
+    ) -> (usize, usize) {
+        let (line, line_start) = self.line_of(at);
+        let mut visual_row = self.lines[..line]
+            .iter()
+            .map(WrappedLine::rows)
+            .sum::<usize>();
+
+        let breaks = &self.lines[line].breaks;
+        let row = breaks.partition_point(|b| line_start + b <= at);
+        visual_row += row;
+        let row_start = line_start + row.checked_sub(1).map_or(0, |i| breaks[i]);
+
+        let column = text.column_of(at, self.tab_width) - text.column_of(row_start, self.tab_width);
+        (visual_row, column)
+    }
+
+    /// Returns the byte position of a column in the provided visual line
+    ///
+    /// If the column is past the end of the visual line, the position of the last cluster in the
+    /// visual line is returned, or the line end for the last visual line of a logical line.
+    ///
+    /// Returns [`None`] if the visual line does not exist.
+    pub fn to_byte<G: ContextGrower<str>, A: Allocator>(
+        &self,
+        text: &GrowingGapString<G, A>,
+        visual_row: usize,
+        column: usize,
+    ) -> Option<usize> {
+        let mut rows = visual_row;
+        let mut line_start = 0;
+        let (line, row) = self.lines.iter().enumerate().find_map(|(i, l)| {
+            if rows < l.rows() {
+                return Some((i, rows));
+            }
+            rows -= l.rows();
+            line_start += l.len;
+            None
+        })?;
+
+        let wrapped = &self.lines[line];
+        let row_start = line_start + row.checked_sub(1).map_or(0, |i| wrapped.breaks[i]);
+        let line_end = text.line_end(line_start);
+        let row_end = wrapped.breaks.get(row).map_or(line_end, |b| line_start + b);
+        let mut columns = text
+            .columns(line_start..line_end, self.tab_width)
+            .skip_while(|c| c.bytes.start < row_start)
+            .take_while(|c| c.bytes.start < row_end)
+            .peekable();
+        let start_column = columns.peek().map_or(0, |c| c.columns.start);
+
+        let mut last = None;
+        for c in columns {
+            if c.columns.end > start_column + column {
+                return Some(c.bytes.start);
+            }
+            last = Some(c.bytes.start);
+        }
+
+        if row_end == line_end {
+            Some(line_end)
+        } else {
+            Some(last.unwrap_or(row_start))
+        }
+    }
+
+    /// Updates the layout after an edit
+    ///
+    /// `start..old_end` is the range that was replaced, and `start..new_end` is the range of the
+    /// new text. Only the logical lines touched by the edit are recomputed.
+    ///
+    /// # Panics
+    /// If the range does not match the string.
+    pub fn update<G: ContextGrower<str>, A: Allocator>(
+        &mut self,
+        text: &GrowingGapString<G, A>,
+        start: usize,
+        old_end: usize,
+        new_end: usize,
+    ) {
+        assert!(start <= old_end && start <= new_end && new_end <= text.len());
+        let (first, first_start) = self.line_of(start);
+        let (last, _) = self.line_of(old_end);
+
+        let new_lines = self.wrap_lines(text, first_start, new_end);
+        self.lines.splice(first..=last, new_lines);
+    }
+
+    /// Sets the viewport width and recomputes the whole layout
+    ///
+    /// # Panics
+    /// If `width` is 0.
+    pub fn set_width<G: ContextGrower<str>, A: Allocator>(
+        &mut self,
+        text: &GrowingGapString<G, A>,
+        width: usize,
+    ) {
+        *self = Self::new(text, width, self.tab_width);
+    }
+
+    /// Returns the logical line containing the byte position, and the start of that line
+    fn line_of(&self, at: usize) -> (usize, usize) {
+        let mut line_start = 0;
+        for (i, l) in self.lines.iter().enumerate() {
+            if at < line_start + l.len || i == self.lines.len() - 1 {
+                assert!(
+                    line_start + l.len >= at,
+                    "position should never be out of bounds"
+                );
+                return (i, line_start);
+            }
+            line_start += l.len;
+        }
+
+        unreachable!("there is always at least a single line")
+    }
+
+    /// Wraps the lines starting at `from` until the line that contains `to`
+    fn wrap_lines<G: ContextGrower<str>, A: Allocator>(
+        &self,
+        text: &GrowingGapString<G, A>,
+        mut from: usize,
+        to: usize,
+    ) -> Vec<WrappedLine> {
+        let mut lines = Vec::new();
+        loop {
+            let end = text.line_end(from);
+            let clusters: Vec<_> = text.columns(from..end, self.tab_width).collect();
+            let breaks = self.break_line(text, &clusters, from);
+            let has_line_break = end < text.len();
+            lines.push(WrappedLine {
+                len: end - from + usize::from(has_line_break),
+                breaks,
+            });
+
+            if !has_line_break || end >= to {
+                return lines;
+            }
+            from = end + 1;
+        }
+    }
+
+    /// Returns the positions relative to the line start where the visual lines start
+    fn break_line<G: ContextGrower<str>, A: Allocator>(
+        &self,
+        text: &GrowingGapString<G, A>,
+        clusters: &[ClusterColumns],
+        line_start: usize,
+    ) -> Vec<usize> {
+        let is_whitespace = |c: &ClusterColumns| {
+            text.get(c.bytes.clone())
+                .unwrap()
+                .chars()
+                .all(char::is_whitespace)
+        };
+
+        let mut breaks = Vec::new();
+        let mut row_start = 0;
+        // the last cluster index in the current row that follows whitespace
+        let mut opportunity = None;
+        let mut i = 0;
+        while i < clusters.len() {
+            let c = &clusters[i];
+            let row_column = clusters[row_start].columns.start;
+            let whitespace = is_whitespace(c);
+            if !whitespace && i > row_start && c.columns.end - row_column > self.width {
+                // prefer breaking after whitespace, otherwise break in the middle of the word
+                let at = opportunity.take().unwrap_or(i);
+                breaks.push(clusters[at].bytes.start - line_start);
+                row_start = at;
+                i = at;
+                continue;
+            }
+
+            if whitespace {
+                opportunity = clusters.get(i + 1).map(|_| i + 1);
+            }
+            i += 1;
+        }
+
+        breaks
+    }
+}
+
+/// An iterator over the visual lines of a [`SoftWrap`] layout
+#[derive(Clone, Debug)]
+pub struct VisualLines<'a> {
+    lines: core::slice::Iter<'a, WrappedLine>,
+    line: usize,
+    row: usize,
+    offset: usize,
+}
+
+impl Iterator for VisualLines<'_> {
+    type Item = VisualLine;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let wrapped = self.lines.as_slice().first()?;
+        let row = self.row;
+        let start = row.checked_sub(1).map_or(0, |i| wrapped.breaks[i]);
+        let content_len = match self.lines.as_slice().get(1) {
+            // every line except the last one has a line break
+            Some(_) => wrapped.len - 1,
+            None => wrapped.len,
+        };
+        let end = wrapped.breaks.get(row).copied().unwrap_or(content_len);
+        let item = VisualLine {
+            line: self.line,
+            row,
+            bytes: self.offset + start..self.offset + end,
+        };
+
+        if row + 1 < wrapped.rows() {
+            self.row += 1;
+        } else {
+            self.lines.next();
+            self.line += 1;
+            self.row = 0;
+            self.offset += wrapped.len;
+        }
+
+        Some(item)
+    }
+}
+
+impl FusedIterator for VisualLines<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use rstest_reuse::apply;
+
+    use crate::testing::*;
+
+    use super::{GrowingGapString, SoftWrap};
+
+    fn rows<G: crate::grower::ContextGrower<str>>(
+        wrap: &SoftWrap,
+        s_buf: &GrowingGapString<G>,
+    ) -> Vec<(usize, usize, String)> {
+        wrap.visual_lines(0..wrap.line_count())
+            .map(|l| (l.line, l.row, s_buf.get(l.bytes).unwrap().to_string()))
+            .collect()
+    }
+
+    #[apply(grower_template)]
+    fn wrap(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert("hello world foo\nabcdefghij\n\n中文中文", 0);
+        let wrap = SoftWrap::new(&s_buf, 6, 4);
+
+        assert_eq!(
+            rows(&wrap, &s_buf),
+            [
+                (0, 0, "hello "),
+                (0, 1, "world "),
+                (0, 2, "foo"),
+                (1, 0, "abcdef"),
+                (1, 1, "ghij"),
+                (2, 0, ""),
+                (3, 0, "中文中"),
+                (3, 1, "文"),
+            ]
+            .map(|(l, r, s)| (l, r, s.to_string()))
+        );
+        assert_eq!(wrap.visual_line_count(), 8);
+        assert_eq!(wrap.visual_lines(1..2).count(), 2);
+        assert_eq!(wrap.visual_lines(3..4).next().unwrap().bytes, 28..37);
+
+        assert_eq!(wrap.to_visual(&s_buf, 0), (0, 0));
+        assert_eq!(wrap.to_visual(&s_buf, 5), (0, 5));
+        assert_eq!(wrap.to_visual(&s_buf, 6), (1, 0));
+        assert_eq!(wrap.to_visual(&s_buf, 15), (2, 3));
+        assert_eq!(wrap.to_visual(&s_buf, 24), (4, 2));
+        assert_eq!(wrap.to_visual(&s_buf, 27), (5, 0));
+        assert_eq!(wrap.to_visual(&s_buf, 40), (7, 2));
+
+        assert_eq!(wrap.to_byte(&s_buf, 0, 0), Some(0));
+        assert_eq!(wrap.to_byte(&s_buf, 1, 2), Some(8));
+        // past the end of a visual line that is not the last one
+        assert_eq!(wrap.to_byte(&s_buf, 1, 10), Some(11));
+        assert_eq!(wrap.to_byte(&s_buf, 2, 10), Some(15));
+        assert_eq!(wrap.to_byte(&s_buf, 5, 3), Some(27));
+        assert_eq!(wrap.to_byte(&s_buf, 6, 3), Some(31));
+        assert_eq!(wrap.to_byte(&s_buf, 8, 0), None);
+
+        for (i, _) in s_buf.get(..).unwrap().char_indices() {
+            let (row, column) = wrap.to_visual(&s_buf, i);
+            assert_eq!(wrap.to_byte(&s_buf, row, column), Some(i));
+        }
+    }
+
+    #[apply(grower_template)]
+    fn update(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert("hello world foo\nabcdefghij\n\nxyz", 0);
+        let mut wrap = SoftWrap::new(&s_buf, 6, 4);
+
+        // (start, old end, new text)
+        let edits = [
+            (0, 0, "a "),
+            (3, 8, ""),
+            (10, 10, "\nnew line\nbar"),
+            (20, 35, "x"),
+            (0, 5, "\n\n"),
+            (0, s_buf.len(), ""),
+            (0, 0, "tail\n"),
+        ];
+        for (start, old_end, s) in edits {
+            let old_end = old_end.min(s_buf.len());
+            s_buf.replace_range(start..old_end, s);
+            wrap.update(&s_buf, start, old_end, start + s.len());
+            assert_eq!(wrap, SoftWrap::new(&s_buf, 6, 4));
+        }
+    }
+}
@@ -1,133 +1,298 @@
+#[cfg(target_has_atomic = "ptr")]
+mod budget;
+mod strategies;
+
+#[cfg(target_has_atomic = "ptr")]
+pub use budget::{BudgetStats, SharedBudget};
+pub use strategies::{Adaptive, Doubling, Fixed, NeverShrink, Proportional};
+
 pub trait Grower<T: ?Sized> {
     fn base_gap_size(&mut self, start: &T, end: &T) -> usize;
     fn max_gap_size(&mut self, start: &T, end: &T) -> usize;
-}
 
-#[derive(Clone, Copy, Default, Debug)]
-pub struct DefaultGrower;
-impl<T> Grower<[T]> for DefaultGrower {
-    #[inline(always)]
-    fn base_gap_size(&mut self, _: &[T], _: &[T]) -> usize {
-        4096
+    /// Returns how much the gap should be shrunk by before a removal
+    ///
+    /// This is called by every removing operation of the gap buffers. Returning 0 keeps the gap
+    /// as is, values greater than the gap length are clamped to it.
+    ///
+    /// By default the gap is shrunk to [`Grower::base_gap_size`] (capped by
+    /// [`Grower::max_gap_size`]) as soon as it is larger than [`Grower::max_gap_size`]. See
+    /// [`Hysteresis`] to avoid reallocating when edits alternate around that threshold.
+    #[inline]
+    fn shrink_gap_by(&mut self, start: &T, end: &T, gap_len: usize) -> usize {
+        let max = self.max_gap_size(start, end);
+        if gap_len > max {
+            gap_len - self.base_gap_size(start, end).min(max)
+        } else {
+            0
+        }
     }
 
+    /// Called with the context of every operation before any other method
+    ///
+    /// This allows stateful growers to learn from the edits performed on a buffer, see
+    /// [`Adaptive`]. Does nothing by default.
     #[inline(always)]
-    fn max_gap_size(&mut self, start: &[T], end: &[T]) -> usize {
-        ((start.len() + end.len()) / 100 * 5).min(self.base_gap_size(start, end))
+    fn record_operation(&mut self, cx: &GapContext<'_, T>) {
+        let _ = cx;
     }
 }
 
-impl Grower<str> for DefaultGrower {
+/// The kind of operation a [`GapContext`] is created for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OpKind {
+    /// Items are inserted at the position
+    Insert,
+    /// Items are removed starting at the position
+    Remove,
+    /// A range starting at the position is replaced with other items
+    Replace,
+    /// Space is explicitly reserved in the gap
+    Reserve,
+}
+
+/// Recent operations performed on a buffer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct History {
+    /// The kind and position of the previous operation
+    pub last: Option<(OpKind, usize)>,
+    /// How many operations in a row had the same kind as the previous one
+    pub streak: usize,
+}
+
+impl History {
+    /// Initialize an empty [`History`]
     #[inline(always)]
-    fn base_gap_size(&mut self, start: &str, end: &str) -> usize {
-        self.base_gap_size(start.as_bytes(), end.as_bytes())
+    pub const fn new() -> Self {
+        Self {
+            last: None,
+            streak: 0,
+        }
     }
 
-    #[inline(always)]
-    fn max_gap_size(&mut self, start: &str, end: &str) -> usize {
-        self.max_gap_size(start.as_bytes(), end.as_bytes())
+    /// Records an operation
+    #[inline]
+    pub fn record(&mut self, kind: OpKind, position: usize) {
+        self.streak = match self.last {
+            Some((last, _)) if last == kind => self.streak.saturating_add(1),
+            _ => 1,
+        };
+        self.last = Some((kind, position));
     }
 }
 
-#[cfg(test)]
-pub(crate) mod test_utils {
-    use rstest_reuse::template;
+/// The state of a buffer and the operation about to be performed on it
+#[derive(Clone, Copy, Debug)]
+pub struct GapContext<'a, T: ?Sized> {
+    /// The kind of the operation
+    pub kind: OpKind,
+    /// The items before the gap
+    pub start: &'a T,
+    /// The items after the gap
+    pub end: &'a T,
+    /// The position the operation is performed at
+    pub position: usize,
+    /// The number of items the gap must be able to hold for the operation
+    ///
+    /// For replacements this is the number of items added on top of the replaced range.
+    pub requested: usize,
+    /// The number of items removed by the operation
+    ///
+    /// For replacements this is the number of replaced items that are not replaced by new ones.
+    pub removed: usize,
+    /// The current length of the gap
+    pub gap_len: usize,
+    /// The operations performed before this one
+    pub history: History,
+}
 
-    pub(crate) use super::{DefaultGrower, Grower};
+/// What to do with the gap before performing an operation
+///
+/// Regardless of the decision the buffers always grow the gap enough to fit
+/// [`GapContext::requested`] items, and never shrink it below that.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GapDecision {
+    /// Leave the gap as is
+    Keep,
+    /// Grow the gap by the provided value
+    Grow(usize),
+    /// Shrink the gap by the provided value
+    Shrink(usize),
+}
 
-    #[derive(Clone, Copy, Debug, Default)]
-    pub(crate) struct TinyGrower;
+/// A [`Grower`] that decides using the full context of an operation
+///
+/// Unlike [`Grower`] this can tell apart the kinds of operations, where they happen, how large
+/// they are and what happened before them. Every [`Grower`] is also a [`ContextGrower`], where
+/// inserts that do not fit grow the gap by [`Grower::base_gap_size`] (capped by
+/// [`Grower::max_gap_size`]) on top of the requested size, and removals shrink the gap as
+/// decided by [`Grower::shrink_gap_by`].
+pub trait ContextGrower<T: ?Sized> {
+    fn decide(&mut self, cx: &GapContext<'_, T>) -> GapDecision;
+}
 
-    impl<T> Grower<[T]> for TinyGrower {
-        fn base_gap_size(&mut self, _: &[T], _: &[T]) -> usize {
-            1
+impl<T: ?Sized, G: Grower<T>> ContextGrower<T> for G {
+    #[inline]
+    fn decide(&mut self, cx: &GapContext<'_, T>) -> GapDecision {
+        self.record_operation(cx);
+        if cx.requested > cx.gap_len {
+            let base = self.base_gap_size(cx.start, cx.end);
+            let max = self.max_gap_size(cx.start, cx.end);
+            GapDecision::Grow(base.min(max).saturating_add(cx.requested))
+        } else if cx.removed > 0 {
+            match self.shrink_gap_by(cx.start, cx.end, cx.gap_len) {
+                0 => GapDecision::Keep,
+                by => GapDecision::Shrink(by),
+            }
+        } else {
+            GapDecision::Keep
         }
+    }
+}
+
+/// Wraps a [`Grower`] to delay shrinking the gap
+///
+/// The gap is only shrunk once it is larger than `factor` times [`Grower::max_gap_size`], and
+/// at least `min_interval` removals have been made since the last shrink. Growing is left to the
+/// wrapped [`Grower`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Hysteresis<G> {
+    grower: G,
+    factor: usize,
+    min_interval: usize,
+    since_shrink: usize,
+}
 
-        fn max_gap_size(&mut self, _: &[T], _: &[T]) -> usize {
-            1
+impl<G> Hysteresis<G> {
+    /// Wrap a [`Grower`] with the provided shrink threshold factor and minimum interval
+    ///
+    /// A factor of 0 or 1 shrinks at the same threshold as the wrapped [`Grower`].
+    #[inline]
+    pub const fn new(grower: G, factor: usize, min_interval: usize) -> Self {
+        Self {
+            grower,
+            factor,
+            min_interval,
+            since_shrink: 0,
         }
     }
 
-    impl Grower<str> for TinyGrower {
-        fn base_gap_size(&mut self, _: &str, _: &str) -> usize {
-            1
-        }
+    /// Returns a reference to the wrapped [`Grower`]
+    #[inline(always)]
+    pub const fn grower(&self) -> &G {
+        &self.grower
+    }
+}
 
-        fn max_gap_size(&mut self, _: &str, _: &str) -> usize {
-            1
-        }
+impl<T: ?Sized, G: Grower<T>> Grower<T> for Hysteresis<G> {
+    #[inline(always)]
+    fn base_gap_size(&mut self, start: &T, end: &T) -> usize {
+        self.grower.base_gap_size(start, end)
     }
 
-    #[derive(Clone, Copy, Debug, Default)]
-    pub(crate) struct FuzzyGrower;
+    #[inline(always)]
+    fn max_gap_size(&mut self, start: &T, end: &T) -> usize {
+        self.grower.max_gap_size(start, end)
+    }
 
-    impl<T> Grower<[T]> for FuzzyGrower {
-        fn base_gap_size(&mut self, _: &[T], _: &[T]) -> usize {
-            rand::random::<u8>() as usize
+    fn shrink_gap_by(&mut self, start: &T, end: &T, gap_len: usize) -> usize {
+        self.since_shrink = self.since_shrink.saturating_add(1);
+        let max = self.grower.max_gap_size(start, end);
+        if self.since_shrink <= self.min_interval
+            || gap_len <= max.saturating_mul(self.factor.max(1))
+        {
+            return 0;
         }
 
-        fn max_gap_size(&mut self, _: &[T], _: &[T]) -> usize {
-            rand::random::<u8>() as usize
-        }
+        self.since_shrink = 0;
+        gap_len - self.grower.base_gap_size(start, end).min(max)
     }
 
-    impl Grower<str> for FuzzyGrower {
-        fn base_gap_size(&mut self, _: &str, _: &str) -> usize {
-            rand::random::<u8>() as usize
-        }
+    #[inline(always)]
+    fn record_operation(&mut self, cx: &GapContext<'_, T>) {
+        self.grower.record_operation(cx);
+    }
+}
 
-        fn max_gap_size(&mut self, _: &str, _: &str) -> usize {
-            rand::random::<u8>() as usize
-        }
+#[derive(Clone, Copy, Default, Debug)]
+pub struct DefaultGrower;
+impl<T> Grower<[T]> for DefaultGrower {
+    #[inline(always)]
+    fn base_gap_size(&mut self, _: &[T], _: &[T]) -> usize {
+        4096
     }
 
-    #[derive(Clone, Copy, Debug)]
-    pub(crate) enum TestGrower {
-        Default(DefaultGrower),
-        Tiny(TinyGrower),
-        Fuzzy(FuzzyGrower),
+    #[inline(always)]
+    fn max_gap_size(&mut self, start: &[T], end: &[T]) -> usize {
+        ((start.len() + end.len()) / 100 * 5).min(self.base_gap_size(start, end))
     }
+}
 
-    impl<T> Grower<[T]> for TestGrower {
-        fn max_gap_size(&mut self, start: &[T], end: &[T]) -> usize {
-            match self {
-                Self::Default(d) => d.max_gap_size(start, end),
-                Self::Tiny(t) => t.max_gap_size(start, end),
-                Self::Fuzzy(f) => f.max_gap_size(start, end),
-            }
-        }
+impl Grower<str> for DefaultGrower {
+    #[inline(always)]
+    fn base_gap_size(&mut self, start: &str, end: &str) -> usize {
+        self.base_gap_size(start.as_bytes(), end.as_bytes())
+    }
 
-        fn base_gap_size(&mut self, start: &[T], end: &[T]) -> usize {
-            match self {
-                Self::Default(d) => d.base_gap_size(start, end),
-                Self::Tiny(t) => t.base_gap_size(start, end),
-                Self::Fuzzy(f) => f.base_gap_size(start, end),
-            }
-        }
+    #[inline(always)]
+    fn max_gap_size(&mut self, start: &str, end: &str) -> usize {
+        self.max_gap_size(start.as_bytes(), end.as_bytes())
     }
+}
 
-    impl Grower<str> for TestGrower {
-        fn max_gap_size(&mut self, start: &str, end: &str) -> usize {
-            match self {
-                Self::Default(d) => d.max_gap_size(start, end),
-                Self::Tiny(t) => t.max_gap_size(start, end),
-                Self::Fuzzy(f) => f.max_gap_size(start, end),
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use crate::{gap_buf::GrowingGapBuf, gapstr::GrowingGapString};
 
-        fn base_gap_size(&mut self, start: &str, end: &str) -> usize {
-            match self {
-                Self::Default(d) => d.base_gap_size(start, end),
-                Self::Tiny(t) => t.base_gap_size(start, end),
-                Self::Fuzzy(f) => f.base_gap_size(start, end),
-            }
-        }
+    use crate::testing::TinyGrower;
+
+    use super::{DefaultGrower, Grower, Hysteresis};
+
+    #[test]
+    fn default_shrink() {
+        let mut g = DefaultGrower;
+        let start = [0u8; 1000];
+        assert_eq!(g.shrink_gap_by(start.as_slice(), &[], 50), 0);
+        assert_eq!(g.shrink_gap_by(start.as_slice(), &[], 51), 1);
+        assert_eq!(g.shrink_gap_by("", "", 10), 10);
     }
 
-    #[template]
-    #[rstest]
-    #[case::default(TestGrower::Default(DefaultGrower))]
-    #[case::tiny(TestGrower::Tiny(TinyGrower))]
-    #[case::fuzzy(TestGrower::Fuzzy(FuzzyGrower))]
-    pub fn grower_template(#[case] g: TestGrower) {}
+    #[test]
+    fn hysteresis() {
+        let mut g = Hysteresis::new(TinyGrower, 4, 2);
+        let shrink = |g: &mut Hysteresis<TinyGrower>, gap_len| g.shrink_gap_by("", "", gap_len);
+
+        // within the interval
+        assert_eq!(shrink(&mut g, 10), 0);
+        assert_eq!(shrink(&mut g, 10), 0);
+        // below the threshold
+        assert_eq!(shrink(&mut g, 4), 0);
+        assert_eq!(shrink(&mut g, 5), 4);
+        // the interval restarts after shrinking
+        assert_eq!(shrink(&mut g, 5), 0);
+        assert_eq!(shrink(&mut g, 5), 0);
+        assert_eq!(shrink(&mut g, 5), 4);
+    }
+
+    #[test]
+    fn hysteresis_buffers() {
+        let mut s_buf = GrowingGapString::with_grower(Hysteresis::new(TinyGrower, 3, 0));
+        s_buf.insert("Hello World", 0);
+        s_buf.grow_gap(3 - s_buf.gap_len());
+        s_buf.remove(0..1);
+        assert_eq!(s_buf.gap_len(), 4);
+        s_buf.replace_range(0..2, "l");
+        assert_eq!(s_buf.gap_len(), 2);
+
+        let mut buf = GrowingGapBuf::with_grower(Hysteresis::new(TinyGrower, 2, 1));
+        buf.insert_many(0..10, 0);
+        buf.shrink_gap(buf.gap_len());
+        buf.try_grow_gap(5).unwrap();
+        drop(buf.drain(0..1));
+        assert_eq!(buf.gap_len(), 6);
+        drop(buf.drain(0..1));
+        assert_eq!(buf.gap_len(), 2);
+        assert_eq!(buf.remove(0), 2);
+        assert_eq!(buf.get_range(..).unwrap(), [3, 4, 5, 6, 7, 8, 9]);
+    }
 }
@@ -0,0 +1,269 @@
+use alloc::sync::Arc;
+use core::{
+    mem::size_of,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use super::{ContextGrower, DefaultGrower, GapContext, GapDecision};
+
+/// The accounting shared by every [`SharedBudget`] handle
+#[derive(Debug)]
+struct Pool {
+    budget: usize,
+    used: AtomicUsize,
+    peak: AtomicUsize,
+    refused: AtomicUsize,
+    shrunk: AtomicUsize,
+}
+
+/// Usage statistics of a [`SharedBudget`] pool
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BudgetStats {
+    /// The maximum number of bytes all gaps may take up together
+    pub budget: usize,
+    /// The number of bytes currently taken up by gaps
+    pub used: usize,
+    /// The highest value [`BudgetStats::used`] has reached
+    pub peak: usize,
+    /// The number of live handles to the pool
+    pub handles: usize,
+    /// The number of times a gap was grown less than the wrapped grower decided
+    pub refused: usize,
+    /// The number of times a gap was shrunk because the pool was over budget
+    pub shrunk: usize,
+}
+
+/// Caps the total gap memory of every buffer sharing the same pool
+///
+/// Each buffer is given its own handle by cloning a [`SharedBudget`], all clones account their
+/// gaps in the same pool. The wrapped [`ContextGrower`] decides as usual, but a gap is never
+/// grown past what is left in the budget and gaps are shrunk on their next edit while the pool is
+/// over budget. Gaps are still always grown enough to perform an edit, so the budget can be
+/// exceeded by removals or by edits larger than what is left.
+///
+/// The usage of a buffer is updated on each of its edits and released when the buffer is
+/// dropped. Usage is counted in bytes, for [`GrowingGapBuf`](crate::gap_buf::GrowingGapBuf)'s
+/// this is the gap length multiplied by the size of its items. Handles can be used from multiple
+/// threads, in which case the accounting is best effort.
+#[derive(Debug)]
+pub struct SharedBudget<G = DefaultGrower> {
+    grower: G,
+    pool: Arc<Pool>,
+    /// The bytes accounted in the pool by this handle
+    tracked: usize,
+}
+
+impl<G> SharedBudget<G> {
+    /// Initialize a handle to a new pool with the provided budget in bytes
+    #[inline]
+    pub fn new(budget: usize, grower: G) -> Self {
+        Self {
+            grower,
+            pool: Arc::new(Pool {
+                budget,
+                used: AtomicUsize::new(0),
+                peak: AtomicUsize::new(0),
+                refused: AtomicUsize::new(0),
+                shrunk: AtomicUsize::new(0),
+            }),
+            tracked: 0,
+        }
+    }
+
+    /// Returns a new handle to the same pool using another grower
+    #[inline]
+    pub fn with_grower<G2>(&self, grower: G2) -> SharedBudget<G2> {
+        SharedBudget {
+            grower,
+            pool: Arc::clone(&self.pool),
+            tracked: 0,
+        }
+    }
+
+    /// Returns the current usage statistics of the pool
+    pub fn stats(&self) -> BudgetStats {
+        let pool = &self.pool;
+        BudgetStats {
+            budget: pool.budget,
+            used: pool.used.load(Ordering::Relaxed),
+            peak: pool.peak.load(Ordering::Relaxed),
+            handles: Arc::strong_count(pool),
+            refused: pool.refused.load(Ordering::Relaxed),
+            shrunk: pool.shrunk.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns a reference to the wrapped grower
+    #[inline(always)]
+    pub const fn grower(&self) -> &G {
+        &self.grower
+    }
+
+    /// Replaces the bytes accounted by this handle and returns the usage of the whole pool
+    fn track(&mut self, bytes: usize) -> usize {
+        let pool = &self.pool;
+        let used = if bytes >= self.tracked {
+            let diff = bytes - self.tracked;
+            pool.used.fetch_add(diff, Ordering::Relaxed) + diff
+        } else {
+            let diff = self.tracked - bytes;
+            pool.used.fetch_sub(diff, Ordering::Relaxed) - diff
+        };
+        pool.peak.fetch_max(used, Ordering::Relaxed);
+        self.tracked = bytes;
+        used
+    }
+
+    /// Limits the decision of the wrapped grower to the budget
+    fn limit<T: ?Sized>(
+        &mut self,
+        cx: &GapContext<'_, T>,
+        decision: GapDecision,
+        size: usize,
+    ) -> GapDecision {
+        // gaps can be resized outside of edits, resync before deciding
+        let used = self.track(cx.gap_len.saturating_mul(size));
+        let others = used - self.tracked;
+
+        let gap_len = cx.gap_len;
+        let planned = match decision {
+            GapDecision::Keep => gap_len.max(cx.requested),
+            GapDecision::Grow(by) => {
+                gap_len.saturating_add(by.max(cx.requested.saturating_sub(gap_len)))
+            }
+            GapDecision::Shrink(by) => gap_len - by.min(gap_len.saturating_sub(cx.requested)),
+        };
+
+        let available = self.pool.budget.saturating_sub(others) / size.max(1);
+        let target = if planned > available {
+            let target = cx.requested.max(available);
+            if planned > gap_len && target < planned {
+                self.pool.refused.fetch_add(1, Ordering::Relaxed);
+            } else if target < gap_len {
+                self.pool.shrunk.fetch_add(1, Ordering::Relaxed);
+            }
+            target
+        } else {
+            planned
+        };
+
+        // account for the gap as it will be after the edit
+        self.track(
+            (target - cx.requested)
+                .saturating_add(cx.removed)
+                .saturating_mul(size),
+        );
+        match target.cmp(&gap_len) {
+            core::cmp::Ordering::Greater => GapDecision::Grow(target - gap_len),
+            core::cmp::Ordering::Less => GapDecision::Shrink(gap_len - target),
+            core::cmp::Ordering::Equal => GapDecision::Keep,
+        }
+    }
+}
+
+impl<G: Default> Default for SharedBudget<G> {
+    /// A pool with a budget of 64 MiB
+    #[inline]
+    fn default() -> Self {
+        Self::new(64 << 20, G::default())
+    }
+}
+
+impl<G: Clone> Clone for SharedBudget<G> {
+    /// Returns a new handle to the same pool
+    ///
+    /// The new handle does not account for anything until it is used.
+    #[inline]
+    fn clone(&self) -> Self {
+        self.with_grower(self.grower.clone())
+    }
+}
+
+impl<G> Drop for SharedBudget<G> {
+    fn drop(&mut self) {
+        self.pool.used.fetch_sub(self.tracked, Ordering::Relaxed);
+    }
+}
+
+impl<G: ContextGrower<str>> ContextGrower<str> for SharedBudget<G> {
+    #[inline]
+    fn decide(&mut self, cx: &GapContext<'_, str>) -> GapDecision {
+        let decision = self.grower.decide(cx);
+        self.limit(cx, decision, 1)
+    }
+}
+
+impl<T, G: ContextGrower<[T]>> ContextGrower<[T]> for SharedBudget<G> {
+    #[inline]
+    fn decide(&mut self, cx: &GapContext<'_, [T]>) -> GapDecision {
+        let decision = self.grower.decide(cx);
+        self.limit(cx, decision, size_of::<T>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{gap_buf::GrowingGapBuf, gapstr::GrowingGapString, grower::Fixed};
+
+    use super::{BudgetStats, SharedBudget};
+
+    #[test]
+    fn shared_budget() {
+        let budget = SharedBudget::new(150, Fixed(100));
+        let mut a = GrowingGapString::with_grower(budget.clone());
+        let mut b = GrowingGapString::with_grower(budget.clone());
+
+        a.insert("Hello", 0);
+        assert_eq!(a.gap_len(), 100);
+        // only what is left in the budget is given
+        b.insert("Hi", 0);
+        assert_eq!(b.gap_len(), 48);
+        assert_eq!(
+            budget.stats(),
+            BudgetStats {
+                budget: 150,
+                used: 148,
+                peak: 148,
+                handles: 3,
+                refused: 1,
+                shrunk: 0,
+            }
+        );
+
+        // removed bytes join the gap
+        a.remove(0..1);
+        assert_eq!(budget.stats().used, 149);
+        drop(b);
+        assert_eq!(budget.stats().used, 101);
+        assert_eq!(budget.stats().handles, 2);
+
+        // edits larger than the budget are still performed
+        a.insert(&"x".repeat(200), 0);
+        assert_eq!(a.gap_len(), 0);
+        assert_eq!(budget.stats().refused, 2);
+        a.remove(0..200);
+        assert_eq!(budget.stats().used, 200);
+
+        // gaps are shrunk while over budget
+        a.insert("y", 0);
+        assert_eq!(a.gap_len(), 149);
+        assert_eq!(a.get(..).unwrap(), "yello");
+        let stats = budget.stats();
+        assert_eq!((stats.used, stats.peak, stats.shrunk), (149, 200, 1));
+
+        drop(a);
+        assert_eq!(budget.stats().used, 0);
+    }
+
+    #[test]
+    fn gap_buf() {
+        let budget = SharedBudget::new(64, Fixed(100));
+        let mut buf = GrowingGapBuf::with_grower(budget.with_grower(Fixed(100)));
+        buf.insert_many(0..4u32, 0);
+        // the budget is in bytes, the gap is grown to 16 items on the first insert
+        assert_eq!(buf.gap_len(), 12);
+        assert_eq!(budget.stats().used, 48);
+        drop(buf.drain(..));
+        assert_eq!(budget.stats().used, 64);
+    }
+}
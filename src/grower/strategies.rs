@@ -0,0 +1,417 @@
+use super::{GapContext, Grower, OpKind};
+
+#[inline(always)]
+fn total_len<T>(start: &[T], end: &[T]) -> usize {
+    start.len() + end.len()
+}
+
+/// Implements [`Grower<str>`] by forwarding to the [`Grower<[u8]>`] implementation
+macro_rules! impl_str_grower {
+    ($ty:ty) => {
+        impl Grower<str> for $ty {
+            #[inline(always)]
+            fn base_gap_size(&mut self, start: &str, end: &str) -> usize {
+                self.base_gap_size(start.as_bytes(), end.as_bytes())
+            }
+
+            #[inline(always)]
+            fn max_gap_size(&mut self, start: &str, end: &str) -> usize {
+                self.max_gap_size(start.as_bytes(), end.as_bytes())
+            }
+        }
+    };
+}
+
+/// Grows the gap by a fixed number of items
+///
+/// The gap is shrunk back to the same size once it is larger than it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fixed(pub usize);
+
+impl<T> Grower<[T]> for Fixed {
+    #[inline(always)]
+    fn base_gap_size(&mut self, _: &[T], _: &[T]) -> usize {
+        self.0
+    }
+
+    #[inline(always)]
+    fn max_gap_size(&mut self, _: &[T], _: &[T]) -> usize {
+        self.0
+    }
+}
+
+impl_str_grower!(Fixed);
+
+/// Grows the gap by the length of the buffer, doubling its capacity on every growth
+///
+/// The growth is at least `min` and at most `cap` items. Once the buffer is larger than `cap`
+/// the capacity grows linearly instead. The gap is shrunk once it is larger than what a growth
+/// would add.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Doubling {
+    min: usize,
+    cap: usize,
+}
+
+impl Doubling {
+    /// Initialize a [`Doubling`] grower with the provided minimum and maximum growth
+    #[inline(always)]
+    pub const fn new(min: usize, cap: usize) -> Self {
+        Self { min, cap }
+    }
+}
+
+impl Default for Doubling {
+    /// Grows by at least 64 items and at most 1 MiB worth of items
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(64, 1 << 20)
+    }
+}
+
+impl<T> Grower<[T]> for Doubling {
+    #[inline(always)]
+    fn base_gap_size(&mut self, start: &[T], end: &[T]) -> usize {
+        total_len(start, end).max(self.min).min(self.cap)
+    }
+
+    #[inline(always)]
+    fn max_gap_size(&mut self, start: &[T], end: &[T]) -> usize {
+        self.base_gap_size(start, end)
+    }
+}
+
+impl_str_grower!(Doubling);
+
+/// Grows the gap by a percentage of the buffer's length
+///
+/// The growth is at least `min` items. The gap is shrunk once it is larger than what a growth
+/// would add.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Proportional {
+    percent: usize,
+    min: usize,
+}
+
+impl Proportional {
+    /// Initialize a [`Proportional`] grower with the provided percentage and minimum growth
+    #[inline(always)]
+    pub const fn new(percent: usize, min: usize) -> Self {
+        Self { percent, min }
+    }
+}
+
+impl<T> Grower<[T]> for Proportional {
+    #[inline(always)]
+    fn base_gap_size(&mut self, start: &[T], end: &[T]) -> usize {
+        (total_len(start, end).saturating_mul(self.percent) / 100).max(self.min)
+    }
+
+    #[inline(always)]
+    fn max_gap_size(&mut self, start: &[T], end: &[T]) -> usize {
+        self.base_gap_size(start, end)
+    }
+}
+
+impl_str_grower!(Proportional);
+
+/// Wraps a [`Grower`] to never shrink the gap
+///
+/// Growing is left to the wrapped [`Grower`]. Useful for buffers that are edited heavily and
+/// would otherwise reallocate whenever a large removal is followed by insertions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NeverShrink<G>(pub G);
+
+impl<T: ?Sized, G: Grower<T>> Grower<T> for NeverShrink<G> {
+    #[inline(always)]
+    fn base_gap_size(&mut self, start: &T, end: &T) -> usize {
+        self.0.base_gap_size(start, end)
+    }
+
+    #[inline(always)]
+    fn max_gap_size(&mut self, _: &T, _: &T) -> usize {
+        usize::MAX
+    }
+
+    #[inline(always)]
+    fn shrink_gap_by(&mut self, _: &T, _: &T, _: usize) -> usize {
+        0
+    }
+
+    #[inline(always)]
+    fn record_operation(&mut self, cx: &GapContext<'_, T>) {
+        self.0.record_operation(cx);
+    }
+}
+
+/// Grows the gap by a multiple of the typical insertion size
+///
+/// The typical insertion size is a moving average of the number of items added by recent inserts
+/// and replacements, so a buffer receiving large pastes grows in large steps while one receiving
+/// keystrokes stays small. The growth is clamped between `min` and `max` items, and the gap is
+/// shrunk once it is larger than twice the growth.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Adaptive {
+    min: usize,
+    max: usize,
+    multiple: usize,
+    /// The moving average multiplied by [`Adaptive::WEIGHT`] to avoid losing precision
+    scaled: usize,
+    samples: usize,
+}
+
+impl Adaptive {
+    /// The number of recent insertions that make up most of the moving average
+    const WEIGHT: usize = 8;
+
+    /// Initialize an [`Adaptive`] grower
+    ///
+    /// The gap is grown by `multiple` times the typical insertion size, clamped between `min`
+    /// and `max` items.
+    #[inline(always)]
+    pub const fn new(min: usize, max: usize, multiple: usize) -> Self {
+        Self {
+            min,
+            max,
+            multiple,
+            scaled: 0,
+            samples: 0,
+        }
+    }
+
+    /// Returns the learned typical insertion size, or 0 if nothing was inserted yet
+    #[inline(always)]
+    pub const fn typical_insert(&self) -> usize {
+        self.scaled / Self::WEIGHT
+    }
+
+    /// Returns the number of insertions learned from
+    #[inline(always)]
+    pub const fn samples(&self) -> usize {
+        self.samples
+    }
+
+    #[inline]
+    fn learn(&mut self, kind: OpKind, requested: usize) {
+        if !matches!(kind, OpKind::Insert | OpKind::Replace) || requested == 0 {
+            return;
+        }
+
+        let sample = requested.saturating_mul(Self::WEIGHT);
+        self.scaled = if self.samples == 0 {
+            sample
+        } else {
+            (self.scaled - self.scaled / Self::WEIGHT).saturating_add(requested)
+        };
+        self.samples = self.samples.saturating_add(1);
+    }
+}
+
+impl Default for Adaptive {
+    /// Grows by 16 times the typical insertion size, between 64 items and 1 MiB worth of items
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(64, 1 << 20, 16)
+    }
+}
+
+impl<T> Grower<[T]> for Adaptive {
+    #[inline(always)]
+    fn base_gap_size(&mut self, _: &[T], _: &[T]) -> usize {
+        self.typical_insert()
+            .saturating_mul(self.multiple)
+            .max(self.min)
+            .min(self.max)
+    }
+
+    #[inline(always)]
+    fn max_gap_size(&mut self, start: &[T], end: &[T]) -> usize {
+        self.base_gap_size(start, end).saturating_mul(2)
+    }
+
+    #[inline(always)]
+    fn record_operation(&mut self, cx: &GapContext<'_, [T]>) {
+        self.learn(cx.kind, cx.requested);
+    }
+}
+
+impl Grower<str> for Adaptive {
+    #[inline(always)]
+    fn base_gap_size(&mut self, start: &str, end: &str) -> usize {
+        self.base_gap_size(start.as_bytes(), end.as_bytes())
+    }
+
+    #[inline(always)]
+    fn max_gap_size(&mut self, start: &str, end: &str) -> usize {
+        self.max_gap_size(start.as_bytes(), end.as_bytes())
+    }
+
+    #[inline(always)]
+    fn record_operation(&mut self, cx: &GapContext<'_, str>) {
+        self.learn(cx.kind, cx.requested);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        gap_buf::GrowingGapBuf,
+        gapstr::GrowingGapString,
+        grower::{ContextGrower, Grower},
+    };
+
+    use super::{Adaptive, Doubling, Fixed, NeverShrink, Proportional};
+
+    /// Appends `chunk` `n` times and returns how many times the string was reallocated
+    fn reallocs<G: ContextGrower<str>>(g: G, chunk: &str, n: usize) -> usize {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        let mut capacity = 0;
+        let mut count = 0;
+        for _ in 0..n {
+            s_buf.insert(chunk, s_buf.len());
+            let new = s_buf.len() + s_buf.gap_len();
+            if new != capacity {
+                capacity = new;
+                count += 1;
+            }
+        }
+
+        assert_eq!(s_buf.len(), chunk.len() * n);
+        count
+    }
+
+    /// Appends `chunk` and removes it again in two halves `n` times and returns how many times
+    /// the string was reallocated
+    fn churn_reallocs<G: ContextGrower<str>>(g: G, chunk: &str, n: usize) -> usize {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.insert("a", 0);
+        let mut capacity = s_buf.len() + s_buf.gap_len();
+        let mut count = 0;
+        for i in 0..n * 3 {
+            match i % 3 {
+                0 => s_buf.insert(chunk, 1),
+                1 => {
+                    s_buf.remove(1..1 + chunk.len() / 2);
+                }
+                _ => {
+                    s_buf.remove(1..);
+                }
+            }
+            let new = s_buf.len() + s_buf.gap_len();
+            if new != capacity {
+                capacity = new;
+                count += 1;
+            }
+        }
+
+        assert_eq!(s_buf.len(), 1);
+        count
+    }
+
+    #[test]
+    fn fixed() {
+        // every growth fits the inserted byte and 64 more
+        assert_eq!(reallocs(Fixed(64), "a", 1000), 16);
+        assert_eq!(reallocs(Fixed(0), "a", 1000), 1000);
+        assert_eq!(Fixed(64).max_gap_size("", ""), 64);
+    }
+
+    #[test]
+    fn doubling() {
+        // the capacity roughly doubles so the count is logarithmic
+        assert_eq!(reallocs(Doubling::new(16, usize::MAX), "a", 1000), 7);
+        assert_eq!(reallocs(Doubling::new(16, usize::MAX), "a", 100_000), 14);
+        // past the cap the capacity grows linearly
+        assert_eq!(reallocs(Doubling::new(16, 1000), "a", 100_000), 106);
+
+        let mut g = Doubling::new(16, 1000);
+        assert_eq!(g.base_gap_size([0u8; 4].as_slice(), &[0; 4]), 16);
+        assert_eq!(g.base_gap_size([0u8; 400].as_slice(), &[0; 400]), 800);
+        assert_eq!(g.base_gap_size([0u8; 4000].as_slice(), &[0; 4000]), 1000);
+    }
+
+    #[test]
+    fn proportional() {
+        assert_eq!(reallocs(Proportional::new(50, 16), "a", 1000), 11);
+        assert_eq!(reallocs(Proportional::new(50, 16), "a", 100_000), 22);
+        // smaller percentages trade reallocations for less wasted space
+        assert_eq!(reallocs(Proportional::new(10, 16), "a", 100_000), 77);
+    }
+
+    #[test]
+    fn never_shrink() {
+        let mut s_buf = GrowingGapString::with_grower(NeverShrink(Fixed(4)));
+        s_buf.insert(&"a".repeat(100), 0);
+        assert_eq!(s_buf.gap_len(), 4);
+        s_buf.remove(10..);
+        assert_eq!(s_buf.gap_len(), 94);
+        s_buf.replace_range(0..5, "");
+        assert_eq!(s_buf.gap_len(), 99);
+        assert_eq!(s_buf.len(), 5);
+
+        // the same removals with the plain grower shrink the gap back to its size
+        let mut s_buf = GrowingGapString::with_grower(Fixed(4));
+        s_buf.insert(&"a".repeat(100), 0);
+        s_buf.remove(10..);
+        assert_eq!(s_buf.gap_len(), 94);
+        s_buf.remove(0..5);
+        assert_eq!(s_buf.gap_len(), 9);
+
+        // the gap grown by the first insert is reused, while the plain grower reallocates on
+        // every edit
+        assert_eq!(
+            churn_reallocs(NeverShrink(Fixed(4)), &"a".repeat(20), 1000),
+            1
+        );
+        assert_eq!(churn_reallocs(Fixed(4), &"a".repeat(20), 1000), 3000);
+        assert_eq!(reallocs(NeverShrink(Fixed(64)), "a", 1000), 16);
+    }
+
+    #[test]
+    fn adaptive() {
+        let mut g = Adaptive::new(4, 1 << 20, 4);
+        // keystrokes keep the gap small
+        for _ in 0..100 {
+            g.learn(super::OpKind::Insert, 1);
+        }
+        assert_eq!(g.typical_insert(), 1);
+        assert_eq!(g.base_gap_size("", ""), 4);
+
+        // the average follows larger pastes
+        for _ in 0..100 {
+            g.learn(super::OpKind::Insert, 1000);
+        }
+        assert_eq!(g.typical_insert(), 1000);
+        assert_eq!(g.base_gap_size("", ""), 4000);
+        // removals are not learned from
+        g.learn(super::OpKind::Remove, 5);
+        assert_eq!(g.samples(), 200);
+
+        // pastes reallocate as often as keystrokes with a fixed grower of the same minimum
+        assert_eq!(reallocs(Adaptive::new(4, 1 << 20, 4), "a", 1000), 200);
+        assert_eq!(reallocs(Fixed(4), "a", 1000), 200);
+        assert_eq!(
+            reallocs(Adaptive::new(4, 1 << 20, 4), &"a".repeat(1000), 1000),
+            200
+        );
+        assert_eq!(reallocs(Fixed(4), &"a".repeat(1000), 1000), 997);
+    }
+
+    #[test]
+    fn gap_buf() {
+        let mut buf = GrowingGapBuf::with_grower(Doubling::new(4, usize::MAX));
+        let mut capacity = 0;
+        let mut count = 0;
+        for i in 0..1000 {
+            buf.insert(buf.len(), i);
+            let new = buf.len() + buf.gap_len();
+            if new != capacity {
+                capacity = new;
+                count += 1;
+            }
+        }
+
+        assert_eq!(count, 9);
+        assert_eq!(buf.get_range(..).unwrap(), (0..1000).collect::<Vec<_>>());
+    }
+}
@@ -1,8 +1,31 @@
+#![cfg_attr(not(test), no_std)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+
+extern crate alloc;
+// allows the exported test template to refer to this crate by name from within it
+#[cfg(any(test, feature = "testing"))]
+extern crate self as gapper;
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod allocator;
+pub mod array;
+pub mod error;
 pub mod gap_buf;
 pub mod gapstr;
 pub mod grower;
+pub mod limit;
 mod raw_gap_buf;
+#[cfg(feature = "stats")]
+pub mod stats;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 mod utils;
+pub mod view;
 
+pub use array::{ArrayGapBuf, ArrayGapString};
+pub use error::EditError;
 pub use gap_buf::GapBuf;
-pub use gapstr::GapString;
+pub use gapstr::{GapString, SmallGapString};
+pub use limit::{MaxLen, OverflowPolicy};
+pub use view::{GapSlice, GapStr};
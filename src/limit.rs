@@ -0,0 +1,233 @@
+//! Maximum length enforcement for the growing buffers
+//!
+//! A [`MaxLen`] can be set on a [`GrowingGapBuf`](crate::gap_buf::GrowingGapBuf) or a
+//! [`GrowingGapString`](crate::gapstr::GrowingGapString) with their `set_max_len` method. Every
+//! edit that would grow the buffer past it is handled according to its [`OverflowPolicy`], and the
+//! gap is never grown past it by the [`ContextGrower`](crate::grower::ContextGrower).
+
+use crate::{error::EditError, grower::GapDecision};
+
+/// What to do with an edit that would grow a buffer past its [`MaxLen`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OverflowPolicy {
+    /// The edit fails, the `try_*` methods return [`EditError::CapacityExceeded`] and the others
+    /// panic
+    #[default]
+    Reject,
+    /// Only the part of the inserted items that fits is inserted, the rest is discarded
+    Truncate,
+}
+
+/// The maximum length of a buffer along with what to do with edits that exceed it
+///
+/// The length is in items for [`GrowingGapBuf`](crate::gap_buf::GrowingGapBuf) and in bytes for
+/// [`GrowingGapString`](crate::gapstr::GrowingGapString).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MaxLen {
+    pub len: usize,
+    pub policy: OverflowPolicy,
+}
+
+impl MaxLen {
+    #[inline(always)]
+    pub const fn new(len: usize, policy: OverflowPolicy) -> Self {
+        Self { len, policy }
+    }
+
+    /// A maximum length that rejects edits exceeding it
+    #[inline(always)]
+    pub const fn reject(len: usize) -> Self {
+        Self::new(len, OverflowPolicy::Reject)
+    }
+
+    /// A maximum length that truncates edits exceeding it
+    #[inline(always)]
+    pub const fn truncate(len: usize) -> Self {
+        Self::new(len, OverflowPolicy::Truncate)
+    }
+
+    /// Returns how many of the `inserted` items can be inserted in place of `removed` items in a
+    /// buffer of length `len`
+    ///
+    /// Edits that do not grow the buffer are always allowed, even if it is already longer than
+    /// the maximum length.
+    pub(crate) fn fit(
+        self,
+        len: usize,
+        removed: usize,
+        inserted: usize,
+    ) -> Result<usize, EditError> {
+        let available = self.len.saturating_sub(len - removed);
+        if inserted <= available || inserted <= removed {
+            return Ok(inserted);
+        }
+
+        match self.policy {
+            OverflowPolicy::Reject => Err(EditError::CapacityExceeded {
+                needed: len - removed + inserted,
+                capacity: self.len,
+            }),
+            OverflowPolicy::Truncate => Ok(available.max(removed)),
+        }
+    }
+
+    /// Returns the number of items that can be reserved in a buffer of length `len`
+    #[inline(always)]
+    pub(crate) fn reservable(self, len: usize, additional: usize) -> usize {
+        additional.min(self.len.saturating_sub(len))
+    }
+
+    /// Limits a decision so the capacity of a buffer with the provided length and gap length is
+    /// not grown past the maximum length
+    ///
+    /// The gap is still grown enough for the edit, which is enforced by the buffer.
+    #[inline]
+    pub(crate) fn cap(self, decision: GapDecision, len: usize, gap_len: usize) -> GapDecision {
+        match decision {
+            GapDecision::Grow(by) => {
+                GapDecision::Grow(by.min(self.len.saturating_sub(len.saturating_add(gap_len))))
+            }
+            decision => decision,
+        }
+    }
+
+    /// Returns how much the gap of a buffer with the provided length has to shrink for its
+    /// capacity to fit the maximum length
+    #[inline(always)]
+    pub(crate) fn excess_gap(self, len: usize, gap_len: usize) -> usize {
+        len.saturating_add(gap_len)
+            .saturating_sub(self.len.max(len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use rstest_reuse::apply;
+
+    use crate::{error::EditError, gap_buf::GrowingGapBuf, gapstr::GrowingGapString, testing::*};
+
+    use super::MaxLen;
+
+    #[apply(grower_template)]
+    fn string_max_len(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.set_max_len(Some(MaxLen::reject(8)));
+        s_buf.insert("Hello", 0);
+        assert_eq!(
+            s_buf.try_insert(" World", 5),
+            Err(EditError::CapacityExceeded {
+                needed: 11,
+                capacity: 8
+            })
+        );
+        assert_eq!(
+            s_buf.try_replace_range(0..1, "Oh, h"),
+            Err(EditError::CapacityExceeded {
+                needed: 9,
+                capacity: 8
+            })
+        );
+        s_buf.replace_range(.., "Bye");
+        assert!(s_buf.len() + s_buf.gap_len() <= 8);
+
+        // truncated strings are cut at a char boundary
+        s_buf.set_max_len(Some(MaxLen::truncate(8)));
+        s_buf.insert("éé€", 3);
+        assert_eq!(s_buf.get(..).unwrap(), "Byeéé");
+        s_buf.try_insert("ab", 0).unwrap();
+        s_buf.insert("c", 8);
+        s_buf.replace_range(0..1, "xyz");
+        assert_eq!(s_buf.get(..).unwrap(), "xByeéé");
+        let mut cursor = s_buf.edit_cursor(1);
+        assert!(!cursor.insert("d"));
+        assert_eq!(cursor.position(), 1);
+        assert!(s_buf.len() + s_buf.gap_len() <= 8);
+
+        // lowering the limit shrinks the gap, edits that do not grow the string are allowed
+        s_buf.set_max_len(Some(MaxLen::reject(4)));
+        assert_eq!(s_buf.gap_len(), 0);
+        s_buf.replace_range(0..2, "zz");
+        s_buf.remove(4..6);
+        assert_eq!(s_buf.get(..).unwrap(), "zzyeé");
+        assert!(s_buf.try_insert("q", 0).is_err());
+        // removed bytes join the gap, but reserving does not grow it past the limit
+        let gap_len = s_buf.gap_len();
+        s_buf.try_reserve(16).unwrap();
+        assert_eq!(s_buf.gap_len(), gap_len);
+
+        s_buf.set_max_len(None);
+        s_buf.insert("q", 0);
+        assert_eq!(s_buf.max_len(), None);
+    }
+
+    #[apply(grower_template)]
+    #[should_panic]
+    fn string_max_len_panics(g: TestGrower) {
+        let mut s_buf = GrowingGapString::with_grower(g);
+        s_buf.set_max_len(Some(MaxLen::reject(4)));
+        s_buf.insert("Hello", 0);
+    }
+
+    #[apply(grower_template)]
+    fn buf_max_len(g: TestGrower) {
+        let mut buf = GrowingGapBuf::with_grower(g);
+        buf.set_max_len(Some(MaxLen::reject(6)));
+        buf.insert_many(0..4u32, 0);
+        // too many items are rejected before touching the buffer
+        let [start, end] = buf.get_parts();
+        let (start, end, gap_len) = (start.len(), end.len(), buf.gap_len());
+        assert_eq!(
+            buf.try_insert_many(10..13, 2),
+            Err(EditError::CapacityExceeded {
+                needed: 7,
+                capacity: 6
+            })
+        );
+        let [new_start, new_end] = buf.get_parts();
+        assert_eq!((new_start.len(), new_end.len()), (start, end));
+        assert_eq!(buf.gap_len(), gap_len);
+        // without a lower bound they are removed once they overflow, and only a minimum is
+        // reported
+        assert_eq!(
+            buf.try_insert_many((10..20).filter(|_| true), 2),
+            Err(EditError::CapacityExceeded {
+                needed: 7,
+                capacity: 6
+            })
+        );
+        let [new_start, new_end] = buf.get_parts();
+        assert_eq!((new_start.len(), new_end.len()), (start, end));
+        assert_eq!(buf.gap_len(), gap_len);
+        assert_eq!(buf.get_range(..).unwrap(), [0, 1, 2, 3]);
+        buf.try_insert(4, 4).unwrap();
+        buf.insert(5, 5);
+        assert_eq!(
+            buf.try_insert(0, 6),
+            Err(EditError::CapacityExceeded {
+                needed: 7,
+                capacity: 6
+            })
+        );
+        assert!(buf.len() + buf.gap_len() <= 6);
+
+        buf.set_max_len(Some(MaxLen::truncate(8)));
+        buf.insert_many(10..20, 1);
+        assert_eq!(buf.get_range(..).unwrap(), [0, 10, 11, 1, 2, 3, 4, 5]);
+        buf.insert(0, 6);
+        let mut cursor = buf.cursor_mut_at(1);
+        cursor.insert_before(7);
+        cursor.insert_after(8);
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(buf.len(), 8);
+        assert!(buf.len() + buf.gap_len() <= 8);
+    }
+
+    #[apply(grower_template)]
+    #[should_panic]
+    fn buf_max_len_panics(g: TestGrower) {
+        let mut buf = GrowingGapBuf::with_grower(g);
+        buf.set_max_len(Some(MaxLen::reject(4)));
+        buf.insert_many(0..5u32, 0);
+    }
+}
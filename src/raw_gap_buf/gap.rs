@@ -0,0 +1,116 @@
+//! Gap movement shared by the heap allocated and inline gap buffers
+//!
+//! Every element copy done by the gap buffers goes through [`copy`], which allows the tests to
+//! verify how many times elements are copied by an operation.
+
+use core::{ops::Range, ptr::NonNull};
+
+#[cfg(test)]
+std::thread_local! {
+    static COPIES: core::cell::Cell<usize> = const { core::cell::Cell::new(0) };
+}
+
+/// Returns the number of elements copied on the current thread since the last call
+#[cfg(test)]
+pub(crate) fn take_copies() -> usize {
+    COPIES.with(|c| c.replace(0))
+}
+
+/// Copies `count` items from `src` to `dst`
+///
+/// # Safety
+/// Same as [`NonNull::copy_to`].
+#[inline(always)]
+unsafe fn copy<T>(src: NonNull<T>, dst: NonNull<T>, count: usize, overlapping: bool) {
+    #[cfg(test)]
+    COPIES.with(|c| c.set(c.get() + count));
+
+    if overlapping {
+        src.copy_to(dst, count);
+    } else {
+        src.copy_to_nonoverlapping(dst, count);
+    }
+}
+
+/// Moves the items between the current gap start and the provided position to the other side of
+/// the gap
+///
+/// After calling this, the first `to` items are before the gap and the remaining items are after
+/// it. The caller is responsible for updating the lengths of both sides.
+///
+/// # Safety
+/// `ptr` must point to a buffer with `start_len` initialized items, followed by `gap_len`
+/// possibly uninitialized items, followed by `len - start_len` initialized items. `to` must not
+/// be greater than the total number of initialized items.
+#[inline(always)]
+pub(crate) unsafe fn move_gap<T>(ptr: NonNull<T>, start_len: usize, gap_len: usize, to: usize) {
+    if size_of::<T>() == 0 || gap_len == 0 || start_len == to {
+        return;
+    }
+
+    // move gap left: the items before the gap and after `to` are moved to the end of the gap
+    // move gap right: the items at the start of the end slice are moved to the start of the gap
+    let (src, dst, count) = if to < start_len {
+        let count = start_len - to;
+        (ptr.add(to), ptr.add(to + gap_len), count)
+    } else {
+        let count = to - start_len;
+        (ptr.add(start_len + gap_len), ptr.add(start_len), count)
+    };
+
+    // the source and destination only overlap when moving more items than the gap can hold
+    copy(src, dst, count, count > gap_len);
+}
+
+/// Copies the items of a gap buffer into a new buffer with a gap of `new_gap_len` starting at
+/// `at`
+///
+/// Every item is copied exactly once, directly to its final position.
+///
+/// # Safety
+/// `src` must point to a buffer with `start_len` initialized items, followed by `gap_len`
+/// possibly uninitialized items, followed by `end_len` initialized items. `dst` must be valid for
+/// writes of `start_len + new_gap_len + end_len` items and must not overlap with `src`. `at` must
+/// not be greater than `start_len + end_len`.
+#[inline]
+pub(crate) unsafe fn copy_with_gap<T>(
+    src: NonNull<T>,
+    [start_len, gap_len, end_len]: [usize; 3],
+    dst: NonNull<T>,
+    new_gap_len: usize,
+    at: usize,
+) {
+    let end = src.add(start_len + gap_len);
+    // items after `at` are offset by the new gap
+    let after = dst.add(at + new_gap_len);
+    if at <= start_len {
+        copy(src, dst, at, false);
+        copy(src.add(at), after, start_len - at, false);
+        copy(end, after.add(start_len - at), end_len, false);
+    } else {
+        let moved = at - start_len;
+        copy(src, dst, start_len, false);
+        copy(end, dst.add(start_len), moved, false);
+        copy(end.add(moved), after, end_len - moved, false);
+    }
+}
+
+/// Returns the position to move the gap start to in order to move it out of the provided range
+///
+/// The side of the range that requires fewer copies is picked. Returns [`None`] if the gap is
+/// already out of the range.
+#[inline(always)]
+pub(crate) const fn gap_position_out_of(start_len: usize, r: Range<usize>) -> Option<usize> {
+    if r.start > start_len || r.end <= start_len {
+        return None;
+    }
+
+    // determine the minimum copies needed to move the gap out of the range
+    let to_right_copy = start_len - r.start;
+    let to_left_copy = r.end - start_len;
+    if to_right_copy > to_left_copy {
+        Some(r.end)
+    } else {
+        Some(r.start)
+    }
+}
@@ -1,2 +1,7 @@
+mod gap;
 mod raw;
+pub(crate) use gap::{gap_position_out_of, move_gap};
 pub(crate) use raw::RawGapBuf;
+
+#[cfg(test)]
+pub(crate) use gap::take_copies;
@@ -1,20 +1,31 @@
-use std::{
+use alloc::{boxed::Box, collections::TryReserveError};
+use core::{
     mem::{size_of, MaybeUninit},
     num::NonZeroUsize,
     ops::Range,
     ptr::NonNull,
 };
 
-use crate::utils::{get_range, is_get_single};
+use super::gap::{copy_with_gap, gap_position_out_of, move_gap};
+#[cfg(feature = "stats")]
+use crate::stats::{BufStats, EventHook, Recorder};
+use crate::{
+    allocator::{self, Allocator, Global},
+    grower::GapDecision,
+    utils::{get_range, is_get_single},
+};
 
 /// Similar to RawVec used in the standard library, this is our inner struct
 ///
-/// Internally uses a boxed slice to allocate and deallocate. Once the allocator API is stabilized
-/// this should be changed to use an allocator instead.
-pub(crate) struct RawGapBuf<T> {
+/// Internally uses boxed slices to allocate and deallocate. With the `allocator_api` feature the
+/// boxed slices are allocated in the provided allocator, otherwise [`Global`] is always used.
+pub(crate) struct RawGapBuf<T, A: Allocator = Global> {
     /// Using NonNull for Null pointer optimization
     start: NonNull<[T]>,
     end: NonNull<[T]>,
+    alloc: A,
+    #[cfg(feature = "stats")]
+    recorder: Recorder,
 }
 
 impl<T> Default for RawGapBuf<T> {
@@ -31,18 +42,9 @@ const _: () = assert!(
 );
 
 impl<T> RawGapBuf<T> {
-    const IS_ZST: bool = size_of::<T>() == 0;
-
     #[inline(always)]
     pub const fn new() -> Self {
-        // SAFETY: ZST's are skipped during the deallocation of a Box, as such creating a dangling slice
-        // pointer with a length of 0 allows us to make this function const
-        let ptr = NonNull::slice_from_raw_parts(NonNull::dangling(), 0);
-        Self {
-            // use the same dangling pointer, otherwise gap size calculation might get messed up
-            start: ptr,
-            end: ptr,
-        }
+        Self::new_in(Global)
     }
 
     #[inline]
@@ -53,7 +55,8 @@ impl<T> RawGapBuf<T> {
         end: [T; E],
     ) -> Self {
         let total_len = start.len() + end.len() + gap_size;
-        let alloc_box = Box::leak(Box::<[T]>::new_uninit_slice(total_len));
+        // SAFETY: the allocation is exclusively owned by the new buffer
+        let alloc_box = unsafe { allocator::allocate::<T, _>(total_len, &Global).as_mut() };
         for (i, item) in start.into_iter().enumerate() {
             alloc_box[i].write(item);
         }
@@ -71,6 +74,9 @@ impl<T> RawGapBuf<T> {
                 unsafe { ptr.cast::<T>().add(S + gap_size) },
                 E,
             ),
+            alloc: Global,
+            #[cfg(feature = "stats")]
+            recorder: Recorder::with_capacity(total_len),
         }
     }
 
@@ -91,10 +97,18 @@ impl<T> RawGapBuf<T> {
             return Self {
                 start: NonNull::slice_from_raw_parts(dangling, start_len),
                 end: NonNull::slice_from_raw_parts(dangling, end_len),
+                alloc: Global,
+                #[cfg(feature = "stats")]
+                recorder: Recorder::new(),
             };
         }
 
-        let alloc_ptr = Box::leak(Box::<[T]>::new_uninit_slice(total_len));
+        // SAFETY: the allocation is exclusively owned by the new buffer
+        let alloc_ptr = unsafe { allocator::allocate::<T, _>(total_len, &Global).as_mut() };
+        #[cfg(feature = "stats")]
+        let mut recorder = Recorder::new();
+        #[cfg(feature = "stats")]
+        recorder.realloc(0, total_len, size_of::<T>());
 
         let mut i = 0;
         let mut offset = 0;
@@ -127,9 +141,38 @@ impl<T> RawGapBuf<T> {
                 NonNull::from(&alloc_ptr[start_len + gap_size..]).cast::<T>(),
                 end_len,
             ),
+            alloc: Global,
+            #[cfg(feature = "stats")]
+            recorder,
+        }
+    }
+}
+
+impl<T, A: Allocator> RawGapBuf<T, A> {
+    const IS_ZST: bool = size_of::<T>() == 0;
+
+    /// Initialize an empty [`RawGapBuf`] that allocates in the provided allocator
+    #[inline(always)]
+    pub const fn new_in(alloc: A) -> Self {
+        // SAFETY: ZST's are skipped during the deallocation of a Box, as such creating a dangling slice
+        // pointer with a length of 0 allows us to make this function const
+        let ptr = NonNull::slice_from_raw_parts(NonNull::dangling(), 0);
+        Self {
+            // use the same dangling pointer, otherwise gap size calculation might get messed up
+            start: ptr,
+            end: ptr,
+            alloc,
+            #[cfg(feature = "stats")]
+            recorder: Recorder::new(),
         }
     }
 
+    /// Returns a reference to the underlying allocator
+    #[inline(always)]
+    pub const fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
     #[inline(always)]
     pub const fn get_parts(&self) -> [&[T]; 2] {
         let (start, _, end) = self.as_slices();
@@ -220,7 +263,7 @@ impl<T> RawGapBuf<T> {
         debug_assert!(is_get_single(start_len, r.start, r.end));
         let [start, end] = self.get_parts_mut();
         if r.start >= start_len {
-            Some(&mut end[r.start - start_len..r.len()])
+            Some(&mut end[r.start - start_len..r.end - start_len])
         } else {
             Some(&mut start[r.start..r.end])
         }
@@ -299,11 +342,6 @@ impl<T> RawGapBuf<T> {
         self.start.cast()
     }
 
-    #[inline(always)]
-    pub const fn start_ptr_mut(&mut self) -> NonNull<T> {
-        self.start.cast()
-    }
-
     #[inline(always)]
     pub const fn start(&self) -> NonNull<[T]> {
         self.start
@@ -480,6 +518,30 @@ impl<T> RawGapBuf<T> {
         unsafe { self.shrink_start(by).as_mut() }
     }
 
+    /// Shrink the end slice by the provided value
+    ///
+    /// Returns the shrunken portion of the end slice as a pointer.
+    /// Caller must deal with T's drop code. To avoid memory leaks.
+    ///
+    /// # Panics
+    ///
+    /// End length should always be greater than or equal to the argument.
+    #[inline(always)]
+    pub fn shrink_end(&mut self, by: usize) -> NonNull<[T]> {
+        let end_len = self.end_len();
+
+        // ensure shrinking the slice does not point out of bounds
+        assert!(
+            end_len >= by,
+            "cannot shrink end slice when shrink value is more than its length"
+        );
+        let old_end = self.end_ptr();
+        // SAFETY: we have checked that the end slice has at least `by` items above
+        let t_ptr = unsafe { self.end_ptr().add(by) };
+        self.end = NonNull::slice_from_raw_parts(t_ptr, end_len - by);
+        NonNull::slice_from_raw_parts(old_end, by)
+    }
+
     /// Shifts the gap by the provided value
     ///
     /// # Safety
@@ -533,79 +595,14 @@ impl<T> RawGapBuf<T> {
             return;
         }
 
-        // this code is pretty ugly, but results in pretty clean assembly with relatively little
-        // branching
-        //
-        // TODO: should benchmark if this is even worth it
         let start_len = self.start_len();
-        let (start, spare, end) = self.as_slices_mut();
-        let gap_len = spare.len();
-        let shift: isize;
-        // a tagged scope is used to reduce the branch count
-        // the shift operation always happens, but src and dst cannot be overlapping
-        // if they are overlapping exit the scope after copying, and just shift the slices.
-        'ov: {
-            let src;
-            let dst;
-            let copy_count;
-            // move gap left
-            if to < start_len && start_len - to <= gap_len {
-                copy_count = start_len - to;
-                shift = -(copy_count as isize);
-                src = NonNull::from(&start[start_len - copy_count..]).cast::<T>();
-                dst = NonNull::from(&mut spare[gap_len - copy_count..]).cast::<MaybeUninit<T>>();
-            }
-            // move gap right
-            else if to > start_len && to - start_len <= gap_len {
-                copy_count = to - start_len;
-                src = NonNull::from(&end[0..copy_count]).cast::<T>();
-                dst = NonNull::from(&mut spare[0..copy_count]).cast::<MaybeUninit<T>>();
-                shift = copy_count as isize;
-            }
-            // noverlapping copy it is
-            else {
-                // move gap right
-                let (src, dst, copy_count) = if to >= self.start_len() {
-                    copy_count = to - self.start_len();
-                    shift = copy_count as isize;
-
-                    (
-                        self.end_ptr(),
-                        self.spare_capacity_ptr().cast::<MaybeUninit<T>>(),
-                        copy_count,
-                    )
-                }
-                // move gap left
-                else {
-                    copy_count = self.start_len() - to;
-                    shift = -(copy_count as isize);
-                    unsafe {
-                        (
-                            self.start_ptr().add(to),
-                            self.start_ptr().add(to + gap_len).cast::<MaybeUninit<T>>(),
-                            copy_count,
-                        )
-                    }
-                };
-
-                let src = src.cast::<MaybeUninit<T>>();
-
-                unsafe { src.copy_to(dst, copy_count) };
-                // the copy is not non overlapping so we did it right above
-                // skip the copy call right below and just shift the gap
-                break 'ov;
-            }
-
-            let src = src.cast::<MaybeUninit<T>>();
-            // assertion in case there is a logic error above
-            assert!(
-                unsafe { src.offset_from(dst).unsigned_abs() >= copy_count },
-                "attempted to copy overlapping pointers"
-            );
-            unsafe { src.copy_to_nonoverlapping(dst, copy_count) };
+        // SAFETY: the buffer has start_len items, the gap, and then end_len items
+        unsafe {
+            move_gap(self.start_ptr(), start_len, self.gap_len(), to);
+            self.shift_gap(to as isize - start_len as isize);
         }
-
-        unsafe { self.shift_gap(shift) };
+        #[cfg(feature = "stats")]
+        self.recorder.gap_moved(start_len, to, size_of::<T>());
     }
 
     /// Move the gap out of a range
@@ -618,19 +615,9 @@ impl<T> RawGapBuf<T> {
     #[inline(always)]
     pub fn move_gap_out_of(&mut self, r: Range<usize>) {
         assert!(self.total_len() >= r.end && r.start <= r.end);
-        if r.start > self.start_len() || r.end <= self.start_len() {
-            return;
+        if let Some(to) = gap_position_out_of(self.start_len(), r) {
+            self.move_gap_start_to(to);
         }
-
-        // determine the minimum copies needed to move the gap out of the range
-        let to_right_copy = self.start_len() - r.start;
-        let to_left_copy = r.end - self.start_len();
-        let move_to = if to_right_copy > to_left_copy {
-            r.end
-        } else {
-            r.start
-        };
-        self.move_gap_start_to(move_to);
     }
 
     /// Call the drop code for the stored T
@@ -661,55 +648,122 @@ impl<T> RawGapBuf<T> {
 
     /// Reallocate the buffer and position the gap start at the provided position
     pub(crate) fn grow_gap_at(&mut self, by: usize, at: usize) {
+        self.realloc_gap_at(by, at, false)
+            .unwrap_or_else(|_| unreachable!("infallible reservation never returns an error"));
+    }
+
+    /// Same as [`RawGapBuf::grow_gap_at`] but returns an error instead of aborting if the
+    /// allocation fails
+    ///
+    /// The buffer is left unchanged if an error is returned.
+    pub(crate) fn try_grow_gap_at(&mut self, by: usize, at: usize) -> Result<(), TryReserveError> {
+        self.realloc_gap_at(by, at, true)
+    }
+
+    /// Same as [`RawGapBuf::grow_gap`] but returns an error if the allocation fails
+    pub(crate) fn try_grow_gap(&mut self, by: usize) -> Result<(), TryReserveError> {
+        self.try_grow_gap_at(by, self.start_len())
+    }
+
+    /// Applies a [`GapDecision`] while making sure the gap can hold `requested` items afterwards
+    ///
+    /// If the gap has to grow, the gap start is positioned at `at`. The buffer is left unchanged
+    /// if an error is returned.
+    pub(crate) fn apply_decision(
+        &mut self,
+        decision: GapDecision,
+        requested: usize,
+        at: usize,
+        fallible: bool,
+    ) -> Result<(), TryReserveError> {
+        let gap_len = self.gap_len();
+        match decision {
+            GapDecision::Grow(by) => {
+                let by = by.max(requested.saturating_sub(gap_len));
+                if by > 0 {
+                    self.realloc_gap_at(by, at, fallible)?;
+                }
+            }
+            GapDecision::Shrink(by) => self.shrink_gap(by.min(gap_len.saturating_sub(requested))),
+            GapDecision::Keep if gap_len < requested => {
+                self.realloc_gap_at(requested - gap_len, at, fallible)?
+            }
+            GapDecision::Keep => {}
+        }
+
+        Ok(())
+    }
+
+    /// Grows the gap by the provided value and positions the gap start at `at`
+    ///
+    /// Each element is copied at most once. When the gap is already at the end of the buffer and
+    /// is to stay there, the allocator is asked to extend the buffer which avoids copying
+    /// entirely if it can do so in place. Otherwise a new buffer is allocated and the elements are
+    /// copied directly to their positions around the new gap.
+    #[inline(always)]
+    fn realloc_gap_at(
+        &mut self,
+        by: usize,
+        at: usize,
+        fallible: bool,
+    ) -> Result<(), TryReserveError> {
         assert!(self.len() >= at);
         if Self::IS_ZST {
             // fake the gap grow
-            *self = Self {
-                start: NonNull::slice_from_raw_parts(NonNull::dangling(), at),
-                end: NonNull::slice_from_raw_parts(NonNull::dangling(), self.len() - at),
-            };
-            return;
+            self.start = NonNull::slice_from_raw_parts(NonNull::dangling(), at);
+            self.end = NonNull::slice_from_raw_parts(NonNull::dangling(), self.len() - at);
+            return Ok(());
         }
 
         let start_len = self.start_len();
         let gap_len = self.gap_len();
         let end_len = self.end_len();
-        let mut v = unsafe {
-            Box::from_raw(
-                NonNull::slice_from_raw_parts(
-                    self.start_ptr_mut().cast::<MaybeUninit<T>>(),
-                    self.total_len(),
-                )
-                .as_ptr(),
-            )
-        }
-        .into_vec();
+        let total_len = self.total_len();
 
-        // reserve_exact can overallocate a bit, set the length to an exact value so calling
-        // into_boxed_slice gives us an allocation that is the correct size
-        //
-        // normally it would be better to just fill the vec with uninits but we cant do that here
-        // due to its trait bounds
-        v.reserve_exact(by);
-        unsafe { v.set_len(start_len + gap_len + end_len + by) };
-
-        let b = v.into_boxed_slice();
-        let start_ptr: NonNull<T> = NonNull::from(Box::leak(b)).cast::<T>();
-
-        let end_ptr = unsafe {
-            let old_end = start_ptr.add(start_len + gap_len);
-            let end_ptr = old_end.add(by);
-            old_end.copy_to(end_ptr, end_len);
-            end_ptr
+        let new_ptr = if end_len == 0 && at == start_len {
+            // nothing has to be moved after growing, let the allocator extend in place if it can
+            //
+            // SAFETY: the buffer was allocated with our allocator and has a length of total_len
+            unsafe { allocator::grow(self.start_ptr(), total_len, by, &self.alloc, fallible)? }
+        } else {
+            let new_ptr =
+                allocator::try_allocate(total_len.saturating_add(by), &self.alloc, fallible)?;
+            // SAFETY: the new buffer is large enough for the items and the larger gap, the old
+            // buffer is deallocated without dropping the copied items
+            unsafe {
+                copy_with_gap(
+                    self.start_ptr(),
+                    [start_len, gap_len, end_len],
+                    new_ptr,
+                    gap_len + by,
+                    at,
+                );
+                allocator::deallocate(self.start_ptr(), total_len, &self.alloc);
+            }
+            new_ptr
         };
 
-        self.start = NonNull::slice_from_raw_parts(start_ptr, start_len);
-        self.end = NonNull::slice_from_raw_parts(end_ptr, end_len);
-        self.move_gap_start_to(at);
+        #[cfg(feature = "stats")]
+        self.recorder
+            .realloc(total_len, total_len + by, size_of::<T>());
+
+        // SAFETY: the new buffer holds `at` items, the gap, and then the remaining items
+        unsafe {
+            self.start = NonNull::slice_from_raw_parts(new_ptr, at);
+            self.end = NonNull::slice_from_raw_parts(
+                new_ptr.add(at + gap_len + by),
+                start_len + end_len - at,
+            );
+        }
+        Ok(())
     }
 
     /// Shrink the gap by the provided value
     ///
+    /// Same as [`RawGapBuf::grow_gap_at`], each element is copied at most once. The allocator
+    /// shrinks the buffer when the gap is at the end, otherwise the elements are copied to a new
+    /// buffer with the smaller gap.
+    ///
     /// # Panics
     /// If the provided value is greater than the current gap size.
     pub fn shrink_gap(&mut self, by: usize) {
@@ -727,47 +781,100 @@ impl<T> RawGapBuf<T> {
         let end_len = self.end_len();
         let total_len = start_len + gap_len + end_len;
 
+        // SAFETY: the buffer was allocated with our allocator and has a length of total_len
         unsafe {
-            let gap_ptr = self.start_ptr().add(self.start_len() + gap_len - by);
-
-            // SAFETY: both are valid for enough read and writes
-            self.end_ptr().copy_to(gap_ptr, self.end_len());
-
-            let b = Box::from_raw(
-                NonNull::slice_from_raw_parts(
-                    self.start_ptr_mut().cast::<MaybeUninit<T>>(),
-                    total_len,
-                )
-                .as_ptr(),
-            );
+            let new_ptr = if end_len == 0 {
+                allocator::shrink(self.start_ptr(), total_len, total_len - by, &self.alloc)
+            } else {
+                let new_ptr = allocator::try_allocate(total_len - by, &self.alloc, false)
+                    .unwrap_or_else(|_| {
+                        unreachable!("infallible allocation never returns an error")
+                    });
+                copy_with_gap(
+                    self.start_ptr(),
+                    [start_len, gap_len, end_len],
+                    new_ptr,
+                    gap_len - by,
+                    start_len,
+                );
+                allocator::deallocate(self.start_ptr(), total_len, &self.alloc);
+                new_ptr
+            };
 
-            let mut v = b.into_vec();
+            self.start = NonNull::slice_from_raw_parts(new_ptr, start_len);
+            self.end =
+                NonNull::slice_from_raw_parts(new_ptr.add(start_len + gap_len - by), end_len);
+        }
+        #[cfg(feature = "stats")]
+        self.recorder
+            .realloc(total_len, total_len - by, size_of::<T>());
+    }
 
-            // We don't explicity shrink the buffer as it is already handled in the conversion to a
-            // box slice below.
-            // SAFETY: we are storing uninits anyway
-            v.set_len(total_len - by);
+    /// Returns the counters recorded since the buffer was created or the counters were reset
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub const fn stats(&self) -> BufStats {
+        self.recorder.stats()
+    }
 
-            let buf = Box::leak(v.into_boxed_slice());
+    /// Resets the counters, the peak capacity starts from the current capacity
+    #[cfg(feature = "stats")]
+    #[inline]
+    pub fn reset_stats(&mut self) {
+        self.recorder.reset(self.total_len());
+    }
 
-            let new_ptr = NonNull::from(buf).cast::<T>();
+    /// Sets the function called on every recorded event
+    #[cfg(feature = "stats")]
+    #[inline]
+    pub fn set_event_hook(&mut self, hook: Option<EventHook>) {
+        self.recorder.set_hook(hook);
+    }
 
-            self.start = NonNull::slice_from_raw_parts(new_ptr, start_len);
-            self.end =
-                NonNull::slice_from_raw_parts(new_ptr.add(start_len + gap_len - by), end_len);
+    /// Panics if the pointers of the buffer do not describe a single allocation with the gap
+    /// between both slices
+    pub fn check_invariants(&self) {
+        let start = self.start_ptr().as_ptr() as usize;
+        let end = self.end_ptr().as_ptr() as usize;
+        if Self::IS_ZST {
+            assert!(
+                self.len() <= isize::MAX as usize,
+                "zero sized buffer is too long"
+            );
+            return;
         }
+
+        let size = size_of::<T>();
+        assert!(
+            end >= start + self.start_len() * size,
+            "end slice starts before the start slice ends"
+        );
+        assert_eq!(
+            (end - start) % size,
+            0,
+            "gap is not a multiple of the item size"
+        );
+        assert!(
+            self.total_len()
+                .checked_mul(size)
+                .is_some_and(|bytes| bytes <= isize::MAX as usize),
+            "allocation is larger than isize::MAX bytes"
+        );
     }
 }
 
-impl<T> Clone for RawGapBuf<T>
+impl<T, A> Clone for RawGapBuf<T, A>
 where
     T: Clone,
+    A: Allocator + Clone,
 {
     fn clone(&self) -> Self {
         let start_len = self.start_len();
         let gap_len = self.gap_len();
         let end_len = self.end_len();
-        let mut alloc_box = Box::<[T]>::new_uninit_slice(self.total_len());
+        let alloc = self.alloc.clone();
+        // SAFETY: the allocation is exclusively owned by the new buffer
+        let alloc_box = unsafe { allocator::allocate::<T, A>(self.total_len(), &alloc).as_mut() };
         let [start, end] = self.get_parts();
         for (i, item) in start.iter().enumerate() {
             alloc_box[i].write(item.clone());
@@ -779,20 +886,30 @@ where
         }
         let end_ptr = NonNull::from(end_slice).cast::<T>();
 
-        let leaked = NonNull::from(Box::leak(alloc_box)).cast::<T>();
+        let leaked = NonNull::from(alloc_box).cast::<T>();
+        #[cfg(feature = "stats")]
+        let mut recorder = Recorder::new();
+        #[cfg(feature = "stats")]
+        if !Self::IS_ZST && self.total_len() != 0 {
+            recorder.realloc(0, self.total_len(), size_of::<T>());
+        }
+
         Self {
             start: NonNull::slice_from_raw_parts(leaked, start_len),
             end: NonNull::slice_from_raw_parts(end_ptr, end_len),
+            alloc,
+            #[cfg(feature = "stats")]
+            recorder,
         }
     }
 }
 
-impl<T, A> From<A> for RawGapBuf<T>
+impl<T, S> From<S> for RawGapBuf<T>
 where
-    Box<[T]>: From<A>,
+    Box<[T]>: From<S>,
 {
     #[inline]
-    fn from(value: A) -> Self {
+    fn from(value: S) -> Self {
         let buf: Box<[T]> = Box::from(value);
         let val_len = buf.len();
 
@@ -803,23 +920,19 @@ where
             Self {
                 start: NonNull::slice_from_raw_parts(start_ptr, val_len),
                 end: NonNull::slice_from_raw_parts(start_ptr.add(val_len), 0),
+                alloc: Global,
+                #[cfg(feature = "stats")]
+                recorder: Recorder::with_capacity(val_len),
             }
         }
     }
 }
 
-impl<T> Drop for RawGapBuf<T> {
+impl<T, A: Allocator> Drop for RawGapBuf<T, A> {
     #[inline]
     fn drop(&mut self) {
-        unsafe {
-            drop(Box::<[MaybeUninit<T>]>::from_raw(
-                NonNull::slice_from_raw_parts(
-                    self.start_ptr().cast::<MaybeUninit<T>>(),
-                    self.total_len(),
-                )
-                .as_ptr(),
-            ));
-        }
+        // SAFETY: the buffer was allocated with our allocator and has a length of total_len
+        unsafe { allocator::deallocate(self.start_ptr(), self.total_len(), &self.alloc) };
     }
 }
 
@@ -829,6 +942,7 @@ mod tests {
     use std::ptr::NonNull;
 
     use super::RawGapBuf;
+    use crate::raw_gap_buf::take_copies;
 
     impl<T> RawGapBuf<T> {
         /// Drop's Self, calling the drop code of the stored T
@@ -874,27 +988,6 @@ mod tests {
             let t_ptr = unsafe { self.end_ptr().sub(by) };
             self.end = NonNull::slice_from_raw_parts(t_ptr, end_len + by);
         }
-
-        /// Shrink the end slice by the provided value
-        ///
-        /// # Safety
-        /// Caller must ensure that the values are correctly dropped, the end length >= by, and that
-        /// the pointer has enough provenance.
-        #[allow(unused)]
-        #[inline(always)]
-        pub unsafe fn shrink_end(&mut self, by: usize) -> NonNull<[T]> {
-            let end_len = self.end_len();
-
-            // ensure shrinking the slice does not point out of bounds
-            assert!(
-                end_len >= by,
-                "cannot shrink start slice when shrink value is more than the total length"
-            );
-            let old_end = self.end_ptr();
-            let t_ptr = unsafe { self.end_ptr().add(by) };
-            self.end = NonNull::slice_from_raw_parts(t_ptr, end_len - by);
-            NonNull::slice_from_raw_parts(old_end, by)
-        }
     }
 
     #[test]
@@ -1210,6 +1303,11 @@ mod tests {
             s_buf.get_parts(),
             [[1, 2, 3, 4, 5].as_slice(), [6].as_slice()]
         );
+
+        // ranges fully after the gap that do not start right after it
+        let mut s_buf = RawGapBuf::new_with([1], 2, [2, 3, 4, 5]);
+        assert_eq!(s_buf.get_slice(3..5).unwrap(), &[4, 5]);
+        assert_eq!(s_buf.get_parts(), [[1].as_slice(), [2, 3, 4, 5].as_slice()]);
     }
 
     #[test]
@@ -1300,4 +1398,54 @@ mod tests {
         unsafe { s_buf.drop_t() };
         s_buf.shrink_gap(19);
     }
+
+    #[test]
+    fn grow_gap_at_copies_once() {
+        let mut s_buf = RawGapBuf::new_with([1, 2, 3], 2, [4, 5]);
+        take_copies();
+
+        // every item is copied once directly to its new position
+        for (at, by) in [(1, 3), (4, 2), (0, 1), (5, 4)] {
+            s_buf.grow_gap_at(by, at);
+            assert_eq!(take_copies(), 5);
+            assert_eq!(s_buf.start_len(), at);
+            assert_eq!(s_buf.get_range(0..5).unwrap().concat(), [1, 2, 3, 4, 5]);
+        }
+
+        // the gap is at the end and stays there so the allocator extends the buffer instead
+        let gap_len = s_buf.gap_len();
+        s_buf.grow_gap_at(8, 5);
+        assert_eq!(take_copies(), 0);
+        assert_eq!(s_buf.gap_len(), gap_len + 8);
+        assert_eq!(s_buf.get_parts(), [[1, 2, 3, 4, 5].as_slice(), &[]]);
+    }
+
+    #[test]
+    fn shrink_gap_copies_once() {
+        let mut s_buf = RawGapBuf::new_with([1, 2, 3], 6, [4, 5]);
+        take_copies();
+
+        s_buf.shrink_gap(4);
+        assert_eq!(take_copies(), 5);
+        assert_eq!(s_buf.gap_len(), 2);
+        assert_eq!(s_buf.get_parts(), [[1, 2, 3].as_slice(), &[4, 5]]);
+
+        s_buf.move_gap_start_to(5);
+        take_copies();
+        s_buf.shrink_gap(2);
+        assert_eq!(take_copies(), 0);
+        assert_eq!(s_buf.get_parts(), [[1, 2, 3, 4, 5].as_slice(), &[]]);
+    }
+
+    #[test]
+    fn move_gap_copies() {
+        let mut s_buf = RawGapBuf::new_with([1, 2, 3], 2, [4, 5]);
+        take_copies();
+        s_buf.move_gap_start_to(0);
+        assert_eq!(take_copies(), 3);
+        s_buf.move_gap_start_to(4);
+        assert_eq!(take_copies(), 4);
+        s_buf.move_gap_out_of(3..5);
+        assert_eq!(take_copies(), 1);
+    }
 }
@@ -0,0 +1,220 @@
+//! Allocation and gap movement instrumentation
+//!
+//! Available with the `stats` feature. [`GrowingGapBuf`](crate::gap_buf::GrowingGapBuf) and
+//! [`GrowingGapString`](crate::gapstr::GrowingGapString) count their reallocations and gap
+//! movements, which can be read with their `stats` method. An [`EventHook`] can also be set with
+//! their `set_event_hook` method to feed each event into an application's own metrics.
+
+use alloc::boxed::Box;
+
+/// Counters recorded by a growing gap buffer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BufStats {
+    /// The number of times the buffer was allocated by itself, including growing and shrinking
+    /// the gap
+    pub reallocations: usize,
+    /// The total number of bytes requested from the allocator
+    pub bytes_allocated: usize,
+    /// The total number of bytes copied to move the gap
+    pub bytes_moved: usize,
+    /// The highest capacity the buffer had, in items including the gap
+    pub peak_capacity: usize,
+}
+
+/// An event recorded by a growing gap buffer
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BufEvent {
+    /// The buffer was allocated or reallocated, capacities are in items including the gap
+    Realloc {
+        old_capacity: usize,
+        new_capacity: usize,
+        /// The number of bytes requested from the allocator
+        bytes: usize,
+    },
+    /// The start of the gap was moved
+    GapMoved {
+        from: usize,
+        to: usize,
+        /// The number of bytes copied
+        bytes: usize,
+    },
+}
+
+/// A function called with every event recorded by a buffer
+///
+/// Hooks can capture state, such as a handle to the metrics of the application. Clones of a
+/// buffer start without a hook.
+pub type EventHook = Box<dyn FnMut(&BufEvent)>;
+
+/// The counters and hook stored in each buffer
+#[derive(Default)]
+pub(crate) struct Recorder {
+    stats: BufStats,
+    hook: Option<EventHook>,
+}
+
+impl Recorder {
+    #[inline(always)]
+    pub(crate) const fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Returns a recorder for a buffer that already has the provided capacity
+    #[inline(always)]
+    pub(crate) const fn with_capacity(capacity: usize) -> Self {
+        Self {
+            stats: BufStats {
+                reallocations: 0,
+                bytes_allocated: 0,
+                bytes_moved: 0,
+                peak_capacity: capacity,
+            },
+            hook: None,
+        }
+    }
+
+    /// Resets the counters so they start from the provided capacity, keeping the hook
+    #[inline(always)]
+    pub(crate) fn reset(&mut self, capacity: usize) {
+        self.stats = Self::with_capacity(capacity).stats;
+    }
+
+    #[inline(always)]
+    pub(crate) const fn stats(&self) -> BufStats {
+        self.stats
+    }
+
+    #[inline(always)]
+    pub(crate) fn set_hook(&mut self, hook: Option<EventHook>) {
+        self.hook = hook;
+    }
+
+    pub(crate) fn realloc(&mut self, old_capacity: usize, new_capacity: usize, size: usize) {
+        let bytes = new_capacity.saturating_mul(size);
+        let stats = &mut self.stats;
+        stats.reallocations += 1;
+        stats.bytes_allocated = stats.bytes_allocated.saturating_add(bytes);
+        stats.peak_capacity = stats.peak_capacity.max(new_capacity);
+        self.emit(BufEvent::Realloc {
+            old_capacity,
+            new_capacity,
+            bytes,
+        });
+    }
+
+    pub(crate) fn gap_moved(&mut self, from: usize, to: usize, size: usize) {
+        let bytes = from.abs_diff(to).saturating_mul(size);
+        self.stats.bytes_moved = self.stats.bytes_moved.saturating_add(bytes);
+        self.emit(BufEvent::GapMoved { from, to, bytes });
+    }
+
+    #[inline(always)]
+    fn emit(&mut self, event: BufEvent) {
+        if let Some(hook) = &mut self.hook {
+            hook(&event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{boxed::Box, cell::RefCell, rc::Rc, vec::Vec};
+
+    use crate::{gap_buf::GrowingGapBuf, gapstr::GrowingGapString, grower::Fixed};
+
+    use super::{BufEvent, BufStats, EventHook};
+
+    /// Returns a hook that pushes the events it receives to the returned list
+    fn recorder() -> (EventHook, Rc<RefCell<Vec<BufEvent>>>) {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let hook = Box::new({
+            let events = Rc::clone(&events);
+            move |event: &BufEvent| events.borrow_mut().push(*event)
+        });
+        (hook, events)
+    }
+
+    #[test]
+    fn string_stats() {
+        let (hook, events) = recorder();
+        let mut s_buf = GrowingGapString::with_grower(Fixed(4));
+        s_buf.set_event_hook(Some(hook));
+        s_buf.insert("Hello", 0);
+        s_buf.insert("!", 2);
+        s_buf.insert("World", 0);
+        s_buf.shrink_gap(s_buf.gap_len());
+        assert_eq!(s_buf.get(..).unwrap(), "WorldHe!llo");
+
+        assert_eq!(
+            events.take(),
+            [
+                BufEvent::Realloc {
+                    old_capacity: 0,
+                    new_capacity: 9,
+                    bytes: 9
+                },
+                BufEvent::GapMoved {
+                    from: 5,
+                    to: 2,
+                    bytes: 3
+                },
+                BufEvent::Realloc {
+                    old_capacity: 9,
+                    new_capacity: 18,
+                    bytes: 18
+                },
+                BufEvent::Realloc {
+                    old_capacity: 18,
+                    new_capacity: 11,
+                    bytes: 11
+                },
+            ]
+        );
+        assert_eq!(
+            s_buf.stats(),
+            BufStats {
+                reallocations: 3,
+                bytes_allocated: 38,
+                bytes_moved: 3,
+                peak_capacity: 18,
+            }
+        );
+
+        // clones start counting from scratch without the hook
+        let mut cloned = s_buf.clone();
+        cloned.insert("?", 11);
+        assert_eq!(cloned.stats().reallocations, 2);
+        assert!(events.take().is_empty());
+
+        // resetting the counters keeps the hook
+        s_buf.reset_stats();
+        s_buf.get_slice(..).unwrap();
+        assert_eq!(
+            s_buf.stats(),
+            BufStats {
+                bytes_moved: 5,
+                peak_capacity: 11,
+                ..Default::default()
+            }
+        );
+        assert_eq!(events.take().len(), 1);
+
+        s_buf.set_event_hook(None);
+        s_buf.remove(3..4);
+        assert!(s_buf.stats().bytes_moved > 5);
+        assert!(events.take().is_empty());
+    }
+
+    #[test]
+    fn buf_stats() {
+        let mut buf = GrowingGapBuf::with_grower(Fixed(2));
+        buf.insert_many(0..4u32, 0);
+        buf.insert(0, 4);
+        let stats = buf.stats();
+        // the size hint is used to reserve once, leaving room for the second insert
+        assert_eq!(stats.reallocations, 1);
+        // items are counted in bytes
+        assert_eq!(stats.bytes_moved, 16);
+        assert_eq!(stats.peak_capacity, buf.len() + buf.gap_len());
+    }
+}
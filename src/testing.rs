@@ -0,0 +1,303 @@
+//! Utilities for testing growers and code built on top of the gap buffers
+//!
+//! Available with the `testing` feature. These are the same utilities this crate tests itself
+//! with:
+//! - [`TinyGrower`] and [`FuzzyGrower`] simulate worst cases and random gap sizes.
+//! - [`grower_template`] is an [`rstest_reuse`] template running a test once per [`TestGrower`].
+//!   The crate applying it must depend on `rstest` and `rstest_reuse`.
+//! - [`string_model`] and [`buf_model`] replay random edits against a [`String`] or a [`Vec`]
+//!   and panic as soon as the gap buffer disagrees with them.
+
+use alloc::{format, string::String, vec::Vec};
+use core::ops::Range;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rstest_reuse::template;
+
+use crate::{
+    gap_buf::GrowingGapBuf,
+    gapstr::GrowingGapString,
+    grower::{ContextGrower, DefaultGrower, Grower},
+};
+
+/// A [`Grower`] that always grows by 1 item, in order to simulate worse cases and off by one bugs
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TinyGrower;
+
+impl<T> Grower<[T]> for TinyGrower {
+    fn base_gap_size(&mut self, _: &[T], _: &[T]) -> usize {
+        1
+    }
+
+    fn max_gap_size(&mut self, _: &[T], _: &[T]) -> usize {
+        1
+    }
+}
+
+impl Grower<str> for TinyGrower {
+    fn base_gap_size(&mut self, _: &str, _: &str) -> usize {
+        1
+    }
+
+    fn max_gap_size(&mut self, _: &str, _: &str) -> usize {
+        1
+    }
+}
+
+/// A [`Grower`] that returns a random size on every call in order to find bugs in edge cases
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FuzzyGrower;
+
+impl<T> Grower<[T]> for FuzzyGrower {
+    fn base_gap_size(&mut self, _: &[T], _: &[T]) -> usize {
+        rand::random::<u8>() as usize
+    }
+
+    fn max_gap_size(&mut self, _: &[T], _: &[T]) -> usize {
+        rand::random::<u8>() as usize
+    }
+}
+
+impl Grower<str> for FuzzyGrower {
+    fn base_gap_size(&mut self, _: &str, _: &str) -> usize {
+        rand::random::<u8>() as usize
+    }
+
+    fn max_gap_size(&mut self, _: &str, _: &str) -> usize {
+        rand::random::<u8>() as usize
+    }
+}
+
+/// Any of the growers used by [`grower_template`]
+#[derive(Clone, Copy, Debug)]
+pub enum TestGrower {
+    Default(DefaultGrower),
+    Tiny(TinyGrower),
+    Fuzzy(FuzzyGrower),
+}
+
+impl<T> Grower<[T]> for TestGrower {
+    fn max_gap_size(&mut self, start: &[T], end: &[T]) -> usize {
+        match self {
+            Self::Default(d) => d.max_gap_size(start, end),
+            Self::Tiny(t) => t.max_gap_size(start, end),
+            Self::Fuzzy(f) => f.max_gap_size(start, end),
+        }
+    }
+
+    fn base_gap_size(&mut self, start: &[T], end: &[T]) -> usize {
+        match self {
+            Self::Default(d) => d.base_gap_size(start, end),
+            Self::Tiny(t) => t.base_gap_size(start, end),
+            Self::Fuzzy(f) => f.base_gap_size(start, end),
+        }
+    }
+}
+
+impl Grower<str> for TestGrower {
+    fn max_gap_size(&mut self, start: &str, end: &str) -> usize {
+        match self {
+            Self::Default(d) => d.max_gap_size(start, end),
+            Self::Tiny(t) => t.max_gap_size(start, end),
+            Self::Fuzzy(f) => f.max_gap_size(start, end),
+        }
+    }
+
+    fn base_gap_size(&mut self, start: &str, end: &str) -> usize {
+        match self {
+            Self::Default(d) => d.base_gap_size(start, end),
+            Self::Tiny(t) => t.base_gap_size(start, end),
+            Self::Fuzzy(f) => f.base_gap_size(start, end),
+        }
+    }
+}
+
+/// Runs the test once with each [`TestGrower`] provided as `g`
+///
+/// ```ignore
+/// use gapper::testing::*;
+/// use rstest::rstest;
+/// use rstest_reuse::apply;
+///
+/// #[apply(grower_template)]
+/// fn my_test(g: TestGrower) {}
+/// ```
+#[template]
+#[export]
+#[rstest]
+#[case::default(::gapper::testing::TestGrower::Default(::gapper::grower::DefaultGrower))]
+#[case::tiny(::gapper::testing::TestGrower::Tiny(::gapper::testing::TinyGrower))]
+#[case::fuzzy(::gapper::testing::TestGrower::Fuzzy(::gapper::testing::FuzzyGrower))]
+pub fn grower_template(#[case] g: TestGrower) {}
+
+/// The strings inserted by [`string_model`], multi byte chars are included to catch gaps placed
+/// between char boundaries
+const PIECES: [&str; 8] = ["a", "b", "é", "€", "😀", "\n", "Hello", "Hello World\n"];
+
+/// An edit performed by the model harnesses
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Op<T> {
+    Insert(usize, T),
+    Remove(Range<usize>),
+    Replace(Range<usize>, T),
+    Reserve(usize),
+    ShrinkGap(usize),
+}
+
+/// Returns a random range, or position if `len` is 0, in `0..=len`
+fn random_range(rng: &mut StdRng, len: usize) -> Range<usize> {
+    let start = rng.random_range(0..=len);
+    let end = rng.random_range(start..=len.min(start + 16));
+    start..end
+}
+
+/// Moves the range to the closest char boundaries at or before it
+fn floor_char_boundary(s: &str, r: Range<usize>) -> Range<usize> {
+    let floor = |mut i: usize| {
+        while !s.is_char_boundary(i) {
+            i -= 1;
+        }
+        i
+    };
+    floor(r.start)..floor(r.end)
+}
+
+/// Replays `steps` random edits on a [`GrowingGapString`] using the provided grower and on a
+/// [`String`]
+///
+/// The same `seed` always produces the same edits. After every edit the invariants of the gap
+/// buffer are checked and its contents are compared with the [`String`].
+///
+/// # Panics
+/// If the gap buffer disagrees with the [`String`], the message contains the seed, step and the
+/// edit that caused it.
+pub fn string_model<G: ContextGrower<str>>(grower: G, seed: u64, steps: usize) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut s_buf = GrowingGapString::with_grower(grower);
+    let mut model = String::new();
+    for step in 0..steps {
+        let r = floor_char_boundary(&model, random_range(&mut rng, model.len()));
+        let piece = PIECES[rng.random_range(0..PIECES.len())];
+        let op = match rng.random_range(0..10) {
+            0..4 => Op::Insert(r.start, piece),
+            4..6 => Op::Remove(r),
+            6..8 => Op::Replace(r, piece),
+            8 => Op::Reserve(rng.random_range(0..64)),
+            _ => Op::ShrinkGap(rng.random_range(0..=s_buf.gap_len())),
+        };
+
+        let context = format!("seed {seed}, step {step}: {op:?}");
+        match op {
+            Op::Insert(at, s) => {
+                s_buf.insert(s, at);
+                model.insert_str(at, s);
+            }
+            Op::Remove(r) => {
+                assert_eq!(s_buf.remove(r.clone()), &model[r.clone()], "{context}");
+                model.replace_range(r, "");
+            }
+            Op::Replace(r, s) => {
+                s_buf.replace_range(r.clone(), s);
+                model.replace_range(r, s);
+            }
+            Op::Reserve(additional) => {
+                s_buf.try_reserve(additional).unwrap();
+                assert!(s_buf.gap_len() >= additional, "{context}");
+            }
+            Op::ShrinkGap(by) => s_buf.shrink_gap(by),
+        }
+
+        s_buf.check_invariants();
+        assert_eq!(s_buf.len(), model.len(), "{context}");
+        assert_eq!(s_buf.get(..).unwrap(), model.as_str(), "{context}");
+    }
+}
+
+/// Replays `steps` random edits on a [`GrowingGapBuf`] using the provided grower and on a
+/// [`Vec`]
+///
+/// The same `seed` always produces the same edits. After every edit the invariants of the gap
+/// buffer are checked and its contents are compared with the [`Vec`].
+///
+/// # Panics
+/// If the gap buffer disagrees with the [`Vec`], the message contains the seed, step and the edit
+/// that caused it.
+pub fn buf_model<G: ContextGrower<[u32]>>(grower: G, seed: u64, steps: usize) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut buf = GrowingGapBuf::with_grower(grower);
+    let mut model = Vec::new();
+    for step in 0..steps {
+        let r = random_range(&mut rng, model.len());
+        let items: Vec<u32> = (0..rng.random_range(1..8)).map(|_| rng.random()).collect();
+        let op = match rng.random_range(0..10) {
+            0..4 => Op::Insert(r.start, items),
+            4..6 => Op::Remove(r),
+            6..8 => Op::Replace(r, items),
+            8 => Op::Reserve(rng.random_range(0..64)),
+            _ => Op::ShrinkGap(rng.random_range(0..=buf.gap_len())),
+        };
+
+        let context = format!("seed {seed}, step {step}: {op:?}");
+        match op {
+            Op::Insert(at, items) if items.len() == 1 => {
+                buf.insert(at, items[0]);
+                model.insert(at, items[0]);
+            }
+            Op::Insert(at, items) => {
+                buf.insert_many(items.iter().copied(), at);
+                model.splice(at..at, items);
+            }
+            Op::Remove(r) if r.len() == 1 => {
+                assert_eq!(buf.remove(r.start), model.remove(r.start), "{context}");
+            }
+            Op::Remove(r) => {
+                let removed: Vec<u32> = buf.drain(r.clone()).unwrap().collect();
+                assert_eq!(removed, model.drain(r).collect::<Vec<_>>(), "{context}");
+            }
+            Op::Replace(r, items) => {
+                drop(buf.drain(r.clone()));
+                buf.insert_many(items.iter().copied(), r.start);
+                model.splice(r, items);
+            }
+            Op::Reserve(additional) => {
+                buf.try_reserve(additional).unwrap();
+                assert!(buf.gap_len() >= additional, "{context}");
+            }
+            Op::ShrinkGap(by) => buf.shrink_gap(by),
+        }
+
+        buf.check_invariants();
+        assert_eq!(buf.len(), model.len(), "{context}");
+        assert_eq!(buf.get_range(..).unwrap(), model, "{context}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use rstest_reuse::apply;
+
+    use crate::grower::{Adaptive, Doubling, Fixed, Hysteresis, NeverShrink, SharedBudget};
+
+    use super::*;
+
+    #[apply(grower_template)]
+    fn models(g: TestGrower) {
+        for seed in 0..8 {
+            string_model(g, seed, 500);
+            buf_model(g, seed, 500);
+        }
+    }
+
+    #[test]
+    fn models_with_growers() {
+        string_model(Fixed(3), 0, 1000);
+        string_model(Doubling::new(1, 64), 1, 1000);
+        string_model(Adaptive::default(), 2, 1000);
+        string_model(NeverShrink(TinyGrower), 3, 1000);
+        string_model(Hysteresis::new(Fixed(2), 2, 3), 4, 1000);
+        string_model(SharedBudget::new(16, Fixed(8)), 5, 1000);
+        buf_model(Doubling::new(1, 64), 6, 1000);
+        buf_model(SharedBudget::new(16, Fixed(8)), 7, 1000);
+    }
+}
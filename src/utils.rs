@@ -1,4 +1,6 @@
-use std::ops::{Bound, Range, RangeBounds};
+use core::ops::{Bound, Range, RangeBounds};
+
+use crate::{error::EditError, gapstr::Point};
 
 #[inline(always)]
 pub(crate) fn u8_is_char_boundary(u: u8) -> bool {
@@ -12,6 +14,38 @@ pub(crate) fn is_get_single(gap_start: usize, start: usize, end: usize) -> bool
 
 #[inline(always)]
 pub(crate) fn get_range<RB: RangeBounds<usize>>(max: usize, r: RB) -> Option<Range<usize>> {
+    let Range { start, end } = bounds(max, r);
+
+    #[cold]
+    #[inline(never)]
+    fn ret_none() {}
+
+    if start > end || max < end {
+        ret_none();
+        None
+    } else {
+        Some(start..end)
+    }
+}
+
+/// Same as [`get_range`] but returns the reason the range is invalid
+#[inline]
+pub(crate) fn try_get_range<RB: RangeBounds<usize>>(
+    max: usize,
+    r: RB,
+) -> Result<Range<usize>, EditError> {
+    let Range { start, end } = bounds(max, r);
+    if start > end {
+        Err(EditError::InvalidRange { start, end })
+    } else if max < end {
+        Err(EditError::OutOfBounds { at: end, len: max })
+    } else {
+        Ok(start..end)
+    }
+}
+
+#[inline(always)]
+fn bounds<RB: RangeBounds<usize>>(max: usize, r: RB) -> Range<usize> {
     let start = match r.start_bound() {
         Bound::Unbounded => 0,
         Bound::Excluded(i) => i.saturating_add(1),
@@ -23,14 +57,90 @@ pub(crate) fn get_range<RB: RangeBounds<usize>>(max: usize, r: RB) -> Option<Ran
         Bound::Included(i) => i.saturating_add(1),
     };
 
-    #[cold]
-    #[inline(never)]
-    fn ret_none() {}
+    start..end
+}
 
-    if start > end || max < end {
-        ret_none();
-        None
+/// Splits a range over both sides of a gap buffer into a range for each side
+///
+/// The first range is relative to the start slice, and the second one is relative to the end
+/// slice. The provided range must already be in bounds.
+#[inline(always)]
+pub(crate) fn split_range(start_len: usize, r: Range<usize>) -> [Range<usize>; 2] {
+    [
+        r.start.min(start_len)..r.end.min(start_len),
+        r.start.max(start_len) - start_len..r.end.max(start_len) - start_len,
+    ]
+}
+
+/// Returns the row and column of the provided position in the bytes of a gap buffer
+///
+/// Rows are separated by `\n` bytes. The position must not be greater than the length of both
+/// parts.
+#[inline]
+pub(crate) fn point_of(parts: [&[u8]; 2], at: usize) -> Point {
+    point_from(parts, (0, Point::new(0, 0)), at)
+}
+
+/// Same as [`point_of`] but starts from a position with a known point
+///
+/// Only the bytes between both positions are scanned, along with the start of the line of `at`
+/// when it is before the known position.
+pub(crate) fn point_from(parts: [&[u8]; 2], from: (usize, Point), at: usize) -> Point {
+    let (from, point) = from;
+    let row = if at >= from {
+        let rows = count_byte_in(parts, b'\n', from..at);
+        if rows == 0 {
+            return Point::new(point.row, point.column + at - from);
+        }
+        point.row + rows
     } else {
-        Some(start..end)
+        point.row - count_byte_in(parts, b'\n', at..from)
+    };
+    let column = at - rfind_byte(parts, b'\n', at).map_or(0, |i| i + 1);
+    Point::new(row, column)
+}
+
+/// Returns the position of the first matching byte at or after the provided position
+#[inline]
+pub(crate) fn find_byte(parts: [&[u8]; 2], byte: u8, from: usize) -> Option<usize> {
+    let [start, end] = parts;
+    match start
+        .get(from..)
+        .and_then(|s| s.iter().position(|b| *b == byte))
+    {
+        Some(i) => Some(from + i),
+        None => {
+            let from = from.saturating_sub(start.len());
+            end.get(from..)?
+                .iter()
+                .position(|b| *b == byte)
+                .map(|i| start.len() + from + i)
+        }
+    }
+}
+
+/// Returns the position of the last matching byte before the provided position
+#[inline]
+pub(crate) fn rfind_byte(parts: [&[u8]; 2], byte: u8, before: usize) -> Option<usize> {
+    let [start, end] = parts;
+    let end = &end[..before.saturating_sub(start.len()).min(end.len())];
+    match end.iter().rposition(|b| *b == byte) {
+        Some(i) => Some(start.len() + i),
+        None => start[..before.min(start.len())]
+            .iter()
+            .rposition(|b| *b == byte),
     }
 }
+
+#[inline(always)]
+fn count_byte(byte: u8, s: &[u8]) -> usize {
+    s.iter().filter(|b| **b == byte).count()
+}
+
+/// Returns the number of matching bytes in the provided range
+#[inline]
+pub(crate) fn count_byte_in(parts: [&[u8]; 2], byte: u8, r: Range<usize>) -> usize {
+    let [start, end] = parts;
+    let [start_r, end_r] = split_range(start.len(), r);
+    count_byte(byte, &start[start_r]) + count_byte(byte, &end[end_r])
+}
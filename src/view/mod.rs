@@ -0,0 +1,5 @@
+mod slice;
+mod str;
+
+pub use slice::{GapSlice, Iter};
+pub use str::{CharIndices, Chars, GapStr};
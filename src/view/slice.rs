@@ -0,0 +1,376 @@
+use alloc::vec::Vec;
+use core::{
+    cmp::Ordering,
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    iter::FusedIterator,
+    ops::{Index, RangeBounds},
+    slice,
+};
+
+use crate::utils::{get_range, split_range};
+
+/// A borrowed view over both sides of a gap buffer
+///
+/// This is what a `&[T]` is for a [`Vec`], but for a gap buffer. The items are stored in two
+/// slices, the first one being before the gap and the second one being after the gap. Indexing,
+/// iteration, comparisons and sub-ranging all treat both slices as a single one.
+pub struct GapSlice<'a, T> {
+    start: &'a [T],
+    end: &'a [T],
+}
+
+impl<T> Clone for GapSlice<'_, T> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for GapSlice<'_, T> {}
+
+impl<T> Default for GapSlice<'_, T> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(&[], &[])
+    }
+}
+
+impl<'a, T> GapSlice<'a, T> {
+    /// Initialize a [`GapSlice`] from the slices before and after the gap
+    #[inline(always)]
+    pub const fn new(start: &'a [T], end: &'a [T]) -> Self {
+        Self { start, end }
+    }
+
+    /// Returns the slices before and after the gap
+    #[inline(always)]
+    pub const fn as_parts(&self) -> [&'a [T]; 2] {
+        [self.start, self.end]
+    }
+
+    /// Returns the total length of both slices
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.start.len() + self.end.len()
+    }
+
+    /// Returns true if both slices are empty
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Same as [`slice::get`] but for a single index
+    ///
+    /// Returns [`None`] if the index is out of bounds.
+    #[inline(always)]
+    pub fn get(&self, index: usize) -> Option<&'a T> {
+        match index.checked_sub(self.start.len()) {
+            None => self.start.get(index),
+            Some(i) => self.end.get(i),
+        }
+    }
+
+    /// Returns the first item, or [`None`] if empty
+    #[inline(always)]
+    pub fn first(&self) -> Option<&'a T> {
+        self.start.first().or_else(|| self.end.first())
+    }
+
+    /// Returns the last item, or [`None`] if empty
+    #[inline(always)]
+    pub fn last(&self) -> Option<&'a T> {
+        self.end.last().or_else(|| self.start.last())
+    }
+
+    /// Returns an iterator over the items in both slices
+    #[inline(always)]
+    pub fn iter(&self) -> Iter<'a, T> {
+        Iter {
+            start: self.start.iter(),
+            end: self.end.iter(),
+        }
+    }
+
+    /// Returns a sub view of the provided range
+    ///
+    /// Returns [`None`] if the range is out of bounds.
+    #[inline]
+    pub fn slice<RB: RangeBounds<usize>>(&self, r: RB) -> Option<Self> {
+        let r = get_range(self.len(), r)?;
+        let [start, end] = split_range(self.start.len(), r);
+        Some(Self::new(&self.start[start], &self.end[end]))
+    }
+
+    /// Divides the view into two at an index
+    ///
+    /// # Panics
+    /// If the provided index is greater than [`GapSlice::len`].
+    #[inline]
+    pub fn split_at(&self, mid: usize) -> (Self, Self) {
+        assert!(mid <= self.len(), "mid should never be out of bounds");
+        // the unwraps cannot fail, the bounds are checked above
+        (self.slice(..mid).unwrap(), self.slice(mid..).unwrap())
+    }
+
+    /// Returns true if the view starts with the provided slice
+    #[inline]
+    pub fn starts_with(&self, needle: &[T]) -> bool
+    where
+        T: PartialEq,
+    {
+        self.slice(..needle.len()).is_some_and(|s| s == needle)
+    }
+
+    /// Returns true if the view ends with the provided slice
+    #[inline]
+    pub fn ends_with(&self, needle: &[T]) -> bool
+    where
+        T: PartialEq,
+    {
+        self.len()
+            .checked_sub(needle.len())
+            .is_some_and(|i| self.slice(i..).unwrap() == needle)
+    }
+
+    /// Returns true if the view contains an item with the provided value
+    #[inline]
+    pub fn contains(&self, x: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.start.contains(x) || self.end.contains(x)
+    }
+
+    /// Copies both slices into a single [`Vec`]
+    #[inline]
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let mut v = Vec::with_capacity(self.len());
+        v.extend_from_slice(self.start);
+        v.extend_from_slice(self.end);
+        v
+    }
+}
+
+/// Compares two split slices without iterating over individual items
+#[inline]
+pub(crate) fn parts_eq<T: PartialEq<U>, U>(a: [&[T]; 2], b: [&[U]; 2]) -> bool {
+    let [a_start, a_end] = a;
+    let [b_start, b_end] = b;
+    if a_start.len() + a_end.len() != b_start.len() + b_end.len() {
+        return false;
+    }
+
+    if a_start.len() <= b_start.len() {
+        let (b_mid, b_start_rest) = b_start.split_at(a_start.len());
+        let (a_mid, a_end_rest) = a_end.split_at(b_start_rest.len());
+        a_start == b_mid && a_mid == b_start_rest && a_end_rest == b_end
+    } else {
+        let (a_mid, a_start_rest) = a_start.split_at(b_start.len());
+        let (b_mid, b_end_rest) = b_end.split_at(a_start_rest.len());
+        a_mid == b_start && a_start_rest == b_mid && a_end == b_end_rest
+    }
+}
+
+impl<T> Index<usize> for GapSlice<'_, T> {
+    type Output = T;
+
+    #[inline(always)]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("index out of bounds")
+    }
+}
+
+impl<T: Debug> Debug for GapSlice<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq<U>, U> PartialEq<GapSlice<'_, U>> for GapSlice<'_, T> {
+    #[inline]
+    fn eq(&self, other: &GapSlice<'_, U>) -> bool {
+        parts_eq(self.as_parts(), other.as_parts())
+    }
+}
+
+impl<T: Eq> Eq for GapSlice<'_, T> {}
+
+impl<T: PartialEq<U>, U> PartialEq<[U]> for GapSlice<'_, T> {
+    #[inline]
+    fn eq(&self, other: &[U]) -> bool {
+        parts_eq(self.as_parts(), [other, &[]])
+    }
+}
+
+impl<T: PartialEq<U>, U> PartialEq<&[U]> for GapSlice<'_, T> {
+    #[inline]
+    fn eq(&self, other: &&[U]) -> bool {
+        self == *other
+    }
+}
+
+impl<T: PartialEq<U>, U, const N: usize> PartialEq<[U; N]> for GapSlice<'_, T> {
+    #[inline]
+    fn eq(&self, other: &[U; N]) -> bool {
+        self == other.as_slice()
+    }
+}
+
+impl<T: PartialEq<U>, U> PartialEq<Vec<U>> for GapSlice<'_, T> {
+    #[inline]
+    fn eq(&self, other: &Vec<U>) -> bool {
+        self == other.as_slice()
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for GapSlice<'_, T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord> Ord for GapSlice<'_, T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash> Hash for GapSlice<'_, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for item in self.iter() {
+            item.hash(state);
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for GapSlice<'a, T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &GapSlice<'a, T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the items of a [`GapSlice`]
+#[derive(Clone, Debug)]
+pub struct Iter<'a, T> {
+    start: slice::Iter<'a, T>,
+    end: slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.start.next().or_else(|| self.end.next())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.end.next_back().or_else(|| self.start.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.start.len() + self.end.len()
+    }
+}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{parts_eq, GapSlice};
+
+    #[test]
+    fn get() {
+        let s = GapSlice::new(&[1, 2], &[3]);
+        assert_eq!(s.len(), 3);
+        assert_eq!(s.get(0), Some(&1));
+        assert_eq!(s.get(2), Some(&3));
+        assert_eq!(s.get(3), None);
+        assert_eq!(s.first(), Some(&1));
+        assert_eq!(s.last(), Some(&3));
+        assert_eq!(s[1], 2);
+
+        let s = GapSlice::<u8>::new(&[], &[]);
+        assert!(s.is_empty());
+        assert_eq!(s.first(), None);
+        assert_eq!(s.last(), None);
+    }
+
+    #[test]
+    fn iter() {
+        let s = GapSlice::new(&[1, 2], &[3, 4]);
+        assert_eq!(s.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+        assert_eq!(s.iter().rev().copied().collect::<Vec<_>>(), [4, 3, 2, 1]);
+        assert_eq!(s.iter().len(), 4);
+        assert_eq!(s.to_vec(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn slice() {
+        let s = GapSlice::new(&[1, 2, 3], &[4, 5]);
+        assert_eq!(s.slice(1..4).unwrap().as_parts(), [&[2, 3][..], &[4]]);
+        assert_eq!(s.slice(..2).unwrap().as_parts(), [&[1, 2][..], &[]]);
+        assert_eq!(s.slice(3..).unwrap().as_parts(), [&[][..], &[4, 5]]);
+        assert_eq!(s.slice(3..3).unwrap(), []);
+        assert_eq!(s.slice(2..6), None);
+
+        let (l, r) = s.split_at(4);
+        assert_eq!(l, [1, 2, 3, 4]);
+        assert_eq!(r, [5]);
+        assert!(s.starts_with(&[1, 2, 3, 4]));
+        assert!(!s.starts_with(&[2]));
+        assert!(s.ends_with(&[3, 4, 5]));
+        assert!(!s.ends_with(&[1, 2, 3, 4, 5, 6]));
+        assert!(s.contains(&4));
+    }
+
+    #[test]
+    fn eq() {
+        let a = GapSlice::new(&[1, 2, 3], &[4, 5]);
+        let b = GapSlice::new(&[1], &[2, 3, 4, 5]);
+        assert_eq!(a, b);
+        assert_eq!(b, a);
+        assert_eq!(a, [1, 2, 3, 4, 5]);
+        assert_eq!(a, vec![1, 2, 3, 4, 5]);
+        assert_ne!(a, GapSlice::new(&[1, 2, 3], &[4]));
+        assert_ne!(a, GapSlice::new(&[1, 2, 3], &[4, 6]));
+        assert!(a < GapSlice::new(&[1, 2, 4], &[]));
+
+        assert!(parts_eq([&[][..], &[1, 2]], [&[1, 2][..], &[]]));
+        assert!(!parts_eq([&[1][..], &[1, 2]], [&[1, 1][..], &[1]]));
+    }
+}
@@ -0,0 +1,422 @@
+use alloc::string::String;
+use core::{
+    cmp::Ordering,
+    fmt::{self, Debug, Display, Write},
+    hash::{Hash, Hasher},
+    iter::FusedIterator,
+    ops::RangeBounds,
+    str,
+};
+
+use crate::utils::{get_range, split_range};
+
+use super::slice::{parts_eq, GapSlice};
+
+/// A borrowed view over both sides of a gap string
+///
+/// This is what a `&str` is for a [`String`], but for a gap string. Both sides of the gap are
+/// always valid UTF-8 string slices, so any char boundary in the view is also a char boundary in
+/// one of the sides.
+#[derive(Clone, Copy, Default)]
+pub struct GapStr<'a> {
+    start: &'a str,
+    end: &'a str,
+}
+
+impl<'a> GapStr<'a> {
+    /// Initialize a [`GapStr`] from the string slices before and after the gap
+    #[inline(always)]
+    pub const fn new(start: &'a str, end: &'a str) -> Self {
+        Self { start, end }
+    }
+
+    /// Returns the string slices before and after the gap
+    #[inline(always)]
+    pub const fn as_parts(&self) -> [&'a str; 2] {
+        [self.start, self.end]
+    }
+
+    /// Returns a byte view of both sides
+    #[inline(always)]
+    pub const fn as_bytes(&self) -> GapSlice<'a, u8> {
+        GapSlice::new(self.start.as_bytes(), self.end.as_bytes())
+    }
+
+    /// Returns the total length of both sides in bytes
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.start.len() + self.end.len()
+    }
+
+    /// Returns true if both sides are empty
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Same as [`str::is_char_boundary`] but for both sides
+    #[inline]
+    pub fn is_char_boundary(&self, index: usize) -> bool {
+        match index.checked_sub(self.start.len()) {
+            None => self.start.is_char_boundary(index),
+            Some(i) => self.end.is_char_boundary(i),
+        }
+    }
+
+    /// Returns a sub view of the provided range
+    ///
+    /// Returns [`None`] if the range is out of bounds or is not on a char boundary.
+    #[inline]
+    pub fn slice<RB: RangeBounds<usize>>(&self, r: RB) -> Option<Self> {
+        let r = get_range(self.len(), r)?;
+        let [start, end] = split_range(self.start.len(), r);
+        Some(Self::new(self.start.get(start)?, self.end.get(end)?))
+    }
+
+    /// Same as [`str::get`] but for both sides
+    ///
+    /// This is the same as [`GapStr::slice`].
+    #[inline(always)]
+    pub fn get<RB: RangeBounds<usize>>(&self, r: RB) -> Option<Self> {
+        self.slice(r)
+    }
+
+    /// Divides the view into two at a byte index
+    ///
+    /// # Panics
+    /// If the provided index is greater than [`GapStr::len`] or is not on a char boundary.
+    #[inline]
+    pub fn split_at(&self, mid: usize) -> (Self, Self) {
+        match (self.slice(..mid), self.slice(mid..)) {
+            (Some(l), Some(r)) => (l, r),
+            _ => panic!("mid should be in bounds and on a char boundary"),
+        }
+    }
+
+    /// Returns an iterator over the chars of both sides
+    #[inline(always)]
+    pub fn chars(&self) -> Chars<'a> {
+        Chars {
+            start: self.start.chars(),
+            end: self.end.chars(),
+        }
+    }
+
+    /// Returns an iterator over the chars of both sides, and their byte positions
+    #[inline(always)]
+    pub fn char_indices(&self) -> CharIndices<'a> {
+        CharIndices {
+            start: self.start.char_indices(),
+            end: self.end.char_indices(),
+            offset: self.start.len(),
+        }
+    }
+
+    /// Returns an iterator over the bytes of both sides
+    #[inline(always)]
+    pub fn bytes(&self) -> super::slice::Iter<'a, u8> {
+        self.as_bytes().iter()
+    }
+
+    /// Returns true if the view starts with the provided string slice
+    #[inline]
+    pub fn starts_with(&self, needle: &str) -> bool {
+        self.as_bytes().starts_with(needle.as_bytes())
+    }
+
+    /// Returns true if the view ends with the provided string slice
+    #[inline]
+    pub fn ends_with(&self, needle: &str) -> bool {
+        self.as_bytes().ends_with(needle.as_bytes())
+    }
+}
+
+impl Display for GapStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.start)?;
+        f.write_str(self.end)
+    }
+}
+
+impl Debug for GapStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        for c in self.chars().flat_map(char::escape_debug) {
+            f.write_char(c)?;
+        }
+        f.write_char('"')
+    }
+}
+
+impl PartialEq for GapStr<'_> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        parts_eq(self.as_bytes().as_parts(), other.as_bytes().as_parts())
+    }
+}
+
+impl Eq for GapStr<'_> {}
+
+impl PartialEq<str> for GapStr<'_> {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl PartialEq<&str> for GapStr<'_> {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl PartialEq<String> for GapStr<'_> {
+    #[inline]
+    fn eq(&self, other: &String) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialEq<GapStr<'_>> for str {
+    #[inline]
+    fn eq(&self, other: &GapStr<'_>) -> bool {
+        other == self
+    }
+}
+
+impl PartialEq<GapStr<'_>> for &str {
+    #[inline]
+    fn eq(&self, other: &GapStr<'_>) -> bool {
+        other == *self
+    }
+}
+
+impl PartialOrd for GapStr<'_> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for GapStr<'_> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        // byte wise ordering is the same as the ordering of chars for UTF-8
+        self.as_bytes().cmp(&other.as_bytes())
+    }
+}
+
+impl Hash for GapStr<'_> {
+    /// Hashes the contents in chunks of the same size wherever the gap is, so equal views always
+    /// have equal hashes
+    ///
+    /// For hashers where writing bytes in several calls is the same as writing them at once,
+    /// such as the default hasher of the standard library, this is also the same hash as a
+    /// [`str`] with the same contents.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        const CHUNK: usize = 64;
+        let mut buf = [0; CHUNK];
+        let mut filled = 0;
+        for part in self.as_parts() {
+            let mut bytes = part.as_bytes();
+            if filled > 0 {
+                let n = (CHUNK - filled).min(bytes.len());
+                buf[filled..filled + n].copy_from_slice(&bytes[..n]);
+                filled += n;
+                bytes = &bytes[n..];
+                if filled < CHUNK {
+                    continue;
+                }
+                state.write(&buf);
+            }
+
+            let mut chunks = bytes.chunks_exact(CHUNK);
+            for chunk in &mut chunks {
+                state.write(chunk);
+            }
+            let rest = chunks.remainder();
+            buf[..rest.len()].copy_from_slice(rest);
+            filled = rest.len();
+        }
+        state.write(&buf[..filled]);
+        state.write_u8(0xff);
+    }
+}
+
+/// An iterator over the chars of a [`GapStr`]
+#[derive(Clone, Debug)]
+pub struct Chars<'a> {
+    start: str::Chars<'a>,
+    end: str::Chars<'a>,
+}
+
+impl Iterator for Chars<'_> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.start.next().or_else(|| self.end.next())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (start_min, start_max) = self.start.size_hint();
+        let (end_min, end_max) = self.end.size_hint();
+        (
+            start_min + end_min,
+            start_max.and_then(|s| s.checked_add(end_max?)),
+        )
+    }
+}
+
+impl DoubleEndedIterator for Chars<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.end.next_back().or_else(|| self.start.next_back())
+    }
+}
+
+impl FusedIterator for Chars<'_> {}
+
+/// An iterator over the chars of a [`GapStr`], and their byte positions
+#[derive(Clone, Debug)]
+pub struct CharIndices<'a> {
+    start: str::CharIndices<'a>,
+    end: str::CharIndices<'a>,
+    offset: usize,
+}
+
+impl Iterator for CharIndices<'_> {
+    type Item = (usize, char);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.start
+            .next()
+            .or_else(|| self.end.next().map(|(i, c)| (i + self.offset, c)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (start_min, start_max) = self.start.size_hint();
+        let (end_min, end_max) = self.end.size_hint();
+        (
+            start_min + end_min,
+            start_max.and_then(|s| s.checked_add(end_max?)),
+        )
+    }
+}
+
+impl DoubleEndedIterator for CharIndices<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.end
+            .next_back()
+            .map(|(i, c)| (i + self.offset, c))
+            .or_else(|| self.start.next_back())
+    }
+}
+
+impl FusedIterator for CharIndices<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::{BuildHasher, Hash, Hasher, RandomState};
+
+    use super::GapStr;
+
+    #[test]
+    fn get() {
+        let s = GapStr::new("Hé", "llo");
+        assert_eq!(s.len(), 6);
+        assert_eq!(s.get(..3).unwrap().as_parts(), ["Hé", ""]);
+        assert_eq!(s.get(1..4).unwrap().as_parts(), ["é", "l"]);
+        assert_eq!(s.get(3..).unwrap().as_parts(), ["", "llo"]);
+        assert_eq!(s.get(2..4), None);
+        assert_eq!(s.get(4..7), None);
+        assert!(s.is_char_boundary(3));
+        assert!(!s.is_char_boundary(2));
+
+        assert_eq!(s.slice(1..4), s.get(1..4));
+        assert_eq!(s.slice(2..), None);
+
+        let (l, r) = s.split_at(4);
+        assert_eq!(l, "Hél");
+        assert_eq!(r, "lo");
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_at_panics() {
+        GapStr::new("é", "").split_at(1);
+    }
+
+    #[test]
+    fn chars() {
+        let s = GapStr::new("aé", "b");
+        assert_eq!(s.chars().collect::<String>(), "aéb");
+        assert_eq!(s.chars().rev().collect::<String>(), "béa");
+        assert_eq!(
+            s.char_indices().collect::<Vec<_>>(),
+            [(0, 'a'), (1, 'é'), (3, 'b')]
+        );
+        assert_eq!(
+            s.char_indices().rev().collect::<Vec<_>>(),
+            [(3, 'b'), (1, 'é'), (0, 'a')]
+        );
+        assert_eq!(s.bytes().len(), 4);
+        let (min, max) = s.char_indices().size_hint();
+        assert!(min <= 3 && max >= Some(3));
+    }
+
+    #[test]
+    fn cmp() {
+        let a = GapStr::new("Hel", "lo");
+        let b = GapStr::new("H", "ello");
+        assert_eq!(a, b);
+        assert_eq!(a, "Hello");
+        assert_eq!("Hello", a);
+        assert_eq!(a, String::from("Hello"));
+        assert_ne!(a, "Hell");
+        assert!(a < GapStr::new("Help", ""));
+        assert!(a.starts_with("Hell"));
+        assert!(a.ends_with("llo"));
+        assert!(!a.ends_with("Hello!"));
+        assert_eq!(a.to_string(), "Hello");
+        assert_eq!(format!("{:?}", GapStr::new("a\n", "\"")), r#""a\n\"""#);
+
+        let state = RandomState::new();
+        assert_eq!(state.hash_one(a), state.hash_one("Hello"));
+        assert_eq!(state.hash_one(a), state.hash_one(b));
+    }
+
+    /// Records every write separately, the way hashers that are not streaming see them
+    #[derive(Default)]
+    struct Writes(Vec<Vec<u8>>);
+
+    impl Hasher for Writes {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            self.0.push(bytes.to_vec());
+        }
+    }
+
+    #[test]
+    fn hash_does_not_depend_on_gap() {
+        let s = "a".repeat(100) + "é" + &"b".repeat(100);
+        let writes = |mid: usize| {
+            let mut hasher = Writes::default();
+            GapStr::new(&s[..mid], &s[mid..]).hash(&mut hasher);
+            hasher.0
+        };
+        let expected = writes(0);
+        assert_eq!(expected.len(), 5);
+        for mid in [1, 63, 64, 65, 100, 102, 150, s.len()] {
+            assert_eq!(writes(mid), expected);
+        }
+    }
+}